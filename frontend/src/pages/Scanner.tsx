@@ -2,6 +2,7 @@ import { useState, useEffect } from 'react'
 import { useConnection, useWallet } from '@solana/wallet-adapter-react'
 import { useAuth } from '@/contexts/AuthContext'
 import { PublicKey } from '@solana/web3.js'
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token'
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card'
 import { Button } from '@/components/ui/button'
 import { Input } from '@/components/ui/input'
@@ -12,6 +13,8 @@ import { QrCode, CheckCircle, XCircle, Clock, User, Calendar, MapPin } from 'luc
 import { toast } from 'sonner'
 import { EventData } from '@/services/eventService'
 
+const MPL_TOKEN_METADATA_PROGRAM_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s')
+
 interface TicketInfo {
   owner: string
   event: string
@@ -151,7 +154,12 @@ export default function Scanner() {
         return
       }
 
-      // Update ticket to scanned stage
+      // Update ticket to scanned stage, the NFT's metadata evolves with it
+      const [metadataPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('metadata'), MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(), ticketAccount.nftMint.toBuffer()],
+        MPL_TOKEN_METADATA_PROGRAM_ID
+      )
+      const mintInfo = await connection.getAccountInfo(ticketAccount.nftMint)
       const tx = await program.methods
         .updateTicket({ scanned: {} })
         .accounts({
@@ -160,6 +168,10 @@ export default function Scanner() {
           ticketAccount: ticketPda,
           authority: new PublicKey(selectedEvent.authority),
           scanner: new PublicKey(selectedEvent.scanner),
+          ticketMint: ticketAccount.nftMint,
+          metadataAccount: metadataPda,
+          ticketTokenProgram: mintInfo?.owner ?? TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
         })
         .rpc()
 
@@ -424,34 +424,46 @@ export async function buyEventTicket(
     const eventAccount = await program.account.eventAccount.fetch(eventPublicKey);
     const organizer = eventAccount.authority as PublicKey;
 
-    const ticketId = Date.now() + Math.floor(Math.random() * 1000000);
-    const ticketIdBuffer = Buffer.alloc(8);
-    ticketIdBuffer.writeBigUInt64LE(BigInt(ticketId));
+    // The program numbers tickets itself, this purchase gets the next serial
+    const ticketId = (eventAccount.ticketsSold as number) + 1;
+    const ticketIdBuffer = Buffer.alloc(4);
+    ticketIdBuffer.writeUInt32LE(ticketId);
 
-    // Derive ticket PDA (now includes ticket_id)
+    // Derive ticket PDA from (event, serial)
     const [ticketPda] = web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from(PROGRAM_SEED),
         Buffer.from(TICKET_SEED),
         eventPublicKey.toBuffer(),
-        buyer.toBuffer(),
         ticketIdBuffer,
       ],
       PROGRAM_ID
     );
 
-    // Derive NFT mint PDA (now includes ticket_id)
+    // Derive NFT mint PDA from (event, serial)
     const [nftMintPda] = web3.PublicKey.findProgramAddressSync(
       [
         Buffer.from(PROGRAM_SEED),
         Buffer.from("nft-mint"),
         eventPublicKey.toBuffer(),
-        buyer.toBuffer(),
         ticketIdBuffer,
       ],
       PROGRAM_ID
     );
 
+    // Derive seat reservation PDA, the purchase fails if the seat is already taken
+    const seatReservationPda = seat
+      ? web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from(PROGRAM_SEED),
+            Buffer.from("seat"),
+            eventPublicKey.toBuffer(),
+            Buffer.from(seat),
+          ],
+          PROGRAM_ID
+        )[0]
+      : null;
+
     // Derive metadata PDA
     const [metadataPda] = web3.PublicKey.findProgramAddressSync(
       [
@@ -492,7 +504,7 @@ export async function buyEventTicket(
     const latestBlockhash = await connection.getLatestBlockhash("confirmed");
 
     const tx = await program.methods
-      .buyEventTicket(new BN(ticketPriceLamports), seat || null, new BN(ticketId))
+      .buyEventTicket(new BN(ticketPriceLamports), seat || null, null)
       .accounts({
         buyer: buyer,
         eventAccount: eventPublicKey,
@@ -507,6 +519,7 @@ export async function buyEventTicket(
         systemProgram: SystemProgram.programId,
         tokenMetadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
         rent: web3.SYSVAR_RENT_PUBKEY,
+        seatReservation: seatReservationPda,
       })
       .rpc({
         skipPreflight: false,
@@ -777,6 +790,19 @@ export async function evolveTicketToQR(
     const authority = eventAccount.authority as PublicKey;
     const scanner = eventAccount.scanner as PublicKey;
 
+    // The NFT's metadata evolves with the ticket
+    const ticketAccount = await program.account.ticketAccount.fetch(ticketPublicKey);
+    const nftMint = ticketAccount.nftMint as PublicKey;
+    const [metadataPda] = web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        MPL_TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        nftMint.toBuffer(),
+      ],
+      MPL_TOKEN_METADATA_PROGRAM_ID
+    );
+    const mintInfo = await connection.getAccountInfo(nftMint);
+
     console.log("Evolving ticket to QR stage:", {
       signer: wallet.publicKey.toString(),
       eventAccount: eventPublicKey.toString(),
@@ -794,6 +820,10 @@ export async function evolveTicketToQR(
         ticketAccount: ticketPublicKey,
         authority: authority,
         scanner: scanner,
+        ticketMint: nftMint,
+        metadataAccount: metadataPda,
+        ticketTokenProgram: mintInfo?.owner ?? TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
       })
       .rpc();
 
@@ -875,7 +905,7 @@ export async function listTicketOnMarketplace(
     const latestBlockhash = await connection.getLatestBlockhash("finalized");
 
     const tx = await program.methods
-      .listTicket(new BN(priceLamports), expiresAt ? new BN(expiresAt) : null)
+      .listTicket(new BN(priceLamports), expiresAt ? new BN(expiresAt) : null, null)
       .accounts({
         seller: seller,
         ticketAccount: ticketPublicKey,
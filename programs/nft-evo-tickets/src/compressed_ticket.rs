@@ -0,0 +1,371 @@
+//! Compressed ticket NFTs of `TicketMintMode::Compressed` events, minted as
+//! Bubblegum leaves into the event's Merkle tree. The Bubblegum crate targets an
+//! older Solana, so its instructions and types are mirrored here.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{keccak, program::invoke_signed, pubkey};
+
+use crate::constants::{PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::TicketStage;
+
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// Anchor discriminators of the Bubblegum instructions
+const CREATE_TREE: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+const MINT_V1: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+const TRANSFER: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+const UPDATE_METADATA: [u8; 8] = [170, 182, 43, 239, 97, 78, 225, 186];
+
+const TOKEN_STANDARD_NON_FUNGIBLE: u8 = 0;
+const TOKEN_PROGRAM_VERSION_ORIGINAL: u8 = 0;
+
+/// Bubblegum's `MetadataArgs`, what a compressed ticket's leaf commits to.
+/// Bubblegum enums are held as their variant index.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CompressedMetadata {
+    pub name: String,
+    pub symbol: String, // The ticket's stage, see TicketStage::compressed_symbol
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<u8>,
+    pub collection: Option<CompressedCollection>,
+    pub uses: Option<CompressedUses>,
+    pub token_program_version: u8,
+    pub creators: Vec<CompressedCreator>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompressedCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8, // Percentage, not basis points
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompressedCollection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CompressedUses {
+    pub use_method: u8,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+/// Bubblegum's `UpdateArgs`, None leaves a field unchanged
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+struct UpdateArgs {
+    name: Option<String>,
+    symbol: Option<String>,
+    uri: Option<String>,
+    creators: Option<Vec<CompressedCreator>>,
+    seller_fee_basis_points: Option<u16>,
+    primary_sale_happened: Option<bool>,
+    is_mutable: Option<bool>,
+}
+
+impl CompressedMetadata {
+    /// Metadata of a new ticket at `stage`, creators unverified as for legacy tickets
+    pub fn new(
+        name: String,
+        stage: TicketStage,
+        uri: String,
+        seller_fee_basis_points: u16,
+        creators: Vec<(Pubkey, u8)>,
+    ) -> Self {
+        Self {
+            name,
+            symbol: stage.compressed_symbol().to_string(),
+            uri,
+            seller_fee_basis_points,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(TOKEN_STANDARD_NON_FUNGIBLE),
+            collection: None,
+            uses: None,
+            token_program_version: TOKEN_PROGRAM_VERSION_ORIGINAL,
+            creators: creators
+                .into_iter()
+                .map(|(address, share)| CompressedCreator {
+                    address,
+                    verified: false,
+                    share,
+                })
+                .collect(),
+        }
+    }
+
+    /// Bubblegum's data hash, the metadata hashed again with its royalty
+    pub fn data_hash(&self) -> Result<[u8; 32]> {
+        let metadata_hash = keccak::hashv(&[&self.try_to_vec()?]);
+        Ok(keccak::hashv(&[
+            &metadata_hash.to_bytes(),
+            &self.seller_fee_basis_points.to_le_bytes(),
+        ])
+        .to_bytes())
+    }
+
+    pub fn creator_hash(&self) -> [u8; 32] {
+        let creators: Vec<[u8; 34]> = self
+            .creators
+            .iter()
+            .map(|creator| {
+                let mut bytes = [0u8; 34];
+                bytes[..32].copy_from_slice(creator.address.as_ref());
+                bytes[32] = creator.verified as u8;
+                bytes[33] = creator.share;
+                bytes
+            })
+            .collect();
+        let slices: Vec<&[u8]> = creators.iter().map(|creator| &creator[..]).collect();
+        keccak::hashv(&slices).to_bytes()
+    }
+}
+
+/// A compressed ticket's leaf as read off-chain, proven against `root` by the
+/// tree's proof nodes passed as remaining accounts. Bubblegum rejects leaves
+/// that aren't in the tree, so once a Bubblegum CPI on it succeeds its
+/// metadata, and the stage it carries, can be trusted.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct CompressedTicket {
+    pub root: [u8; 32],
+    pub nonce: u64, // Leaf index, leaves are minted in sale order so the ticket's serial minus one
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub metadata: CompressedMetadata,
+}
+
+impl CompressedTicket {
+    pub fn serial(&self) -> Result<u32> {
+        self.nonce
+            .checked_add(1)
+            .and_then(|serial| u32::try_from(serial).ok())
+            .ok_or(error!(ErrorCode::InvalidCompressedTicket))
+    }
+
+    pub fn stage(&self) -> Result<TicketStage> {
+        TicketStage::from_compressed_symbol(&self.metadata.symbol)
+            .ok_or(error!(ErrorCode::InvalidCompressedTicket))
+    }
+
+    pub fn asset_id(&self, merkle_tree: &Pubkey) -> Pubkey {
+        asset_id(merkle_tree, self.nonce)
+    }
+}
+
+/// Bubblegum asset id of the leaf at `nonce`, what wallets and indexers know the ticket by
+pub fn asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()],
+        &BUBBLEGUM_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Bubblegum tree config PDA of `merkle_tree`
+pub fn tree_config(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_PROGRAM_ID).0
+}
+
+/// Bubblegum accounts of an event's ticket tree. The ticket authority PDA is
+/// the tree's creator and the tree is private, so only the program can mint
+/// tickets into it or update their metadata.
+pub struct TicketTree<'info> {
+    pub merkle_tree: AccountInfo<'info>,
+    pub tree_config: AccountInfo<'info>,
+    pub ticket_authority: AccountInfo<'info>,
+    pub ticket_authority_bump: u8,
+    pub bubblegum_program: AccountInfo<'info>,
+    pub log_wrapper: AccountInfo<'info>,
+    pub compression_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> TicketTree<'info> {
+    /// Initializes the tree's Bubblegum config. The Merkle tree account is
+    /// allocated beforehand for `max_depth` and `max_buffer_size`, owned by the
+    /// compression program.
+    pub fn create(&self, payer: AccountInfo<'info>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+        let accounts = vec![
+            AccountMeta::new(self.tree_config.key(), false),
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new(payer.key(), true),
+            AccountMeta::new_readonly(self.ticket_authority.key(), true),
+            AccountMeta::new_readonly(self.log_wrapper.key(), false),
+            AccountMeta::new_readonly(self.compression_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        let public: Option<bool> = Some(false);
+        self.invoke(
+            CREATE_TREE,
+            (max_depth, max_buffer_size, public),
+            accounts,
+            vec![payer],
+            &[],
+        )
+    }
+
+    /// Mints a compressed ticket to `leaf_owner`, who is also its delegate
+    pub fn mint(
+        &self,
+        payer: AccountInfo<'info>,
+        leaf_owner: AccountInfo<'info>,
+        metadata: CompressedMetadata,
+    ) -> Result<()> {
+        let accounts = vec![
+            AccountMeta::new(self.tree_config.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(payer.key(), true),
+            AccountMeta::new_readonly(self.ticket_authority.key(), true),
+            AccountMeta::new_readonly(self.log_wrapper.key(), false),
+            AccountMeta::new_readonly(self.compression_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        self.invoke(MINT_V1, metadata, accounts, vec![leaf_owner, payer], &[])
+    }
+
+    /// Moves `ticket` to `new_leaf_owner`. Its owner signs, as a PDA of the
+    /// program when `owner_seeds` are given.
+    pub fn transfer(
+        &self,
+        ticket: &CompressedTicket,
+        leaf_owner: AccountInfo<'info>,
+        leaf_delegate: AccountInfo<'info>,
+        new_leaf_owner: AccountInfo<'info>,
+        owner_seeds: &[&[&[u8]]],
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.tree_config.key(), false),
+            AccountMeta::new_readonly(leaf_owner.key(), true),
+            AccountMeta::new_readonly(leaf_delegate.key(), false),
+            AccountMeta::new_readonly(new_leaf_owner.key(), false),
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(self.log_wrapper.key(), false),
+            AccountMeta::new_readonly(self.compression_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+        let mut account_infos = vec![leaf_owner, leaf_delegate, new_leaf_owner];
+        account_infos.extend_from_slice(proof);
+        self.invoke(
+            TRANSFER,
+            (
+                ticket.root,
+                ticket.metadata.data_hash()?,
+                ticket.metadata.creator_hash(),
+                ticket.nonce,
+                ticket.nonce as u32,
+            ),
+            accounts,
+            account_infos,
+            owner_seeds,
+        )
+    }
+
+    /// Moves `ticket` to `stage`, re-rendering its URI when the event has templates
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_stage(
+        &self,
+        payer: AccountInfo<'info>,
+        ticket: &CompressedTicket,
+        leaf_owner: AccountInfo<'info>,
+        leaf_delegate: AccountInfo<'info>,
+        token_metadata_program: AccountInfo<'info>,
+        stage: TicketStage,
+        uri: Option<String>,
+        proof: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        // Tickets aren't in a verified collection, so the tree creator is their update authority
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.tree_config.key(), false),
+            AccountMeta::new_readonly(self.ticket_authority.key(), true),
+            AccountMeta::new_readonly(BUBBLEGUM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(BUBBLEGUM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(BUBBLEGUM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(leaf_owner.key(), false),
+            AccountMeta::new_readonly(leaf_delegate.key(), false),
+            AccountMeta::new_readonly(payer.key(), true),
+            AccountMeta::new(self.merkle_tree.key(), false),
+            AccountMeta::new_readonly(self.log_wrapper.key(), false),
+            AccountMeta::new_readonly(self.compression_program.key(), false),
+            AccountMeta::new_readonly(token_metadata_program.key(), false),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+        let mut account_infos = vec![leaf_owner, leaf_delegate, payer, token_metadata_program];
+        account_infos.extend_from_slice(proof);
+        let update_args = UpdateArgs {
+            symbol: Some(stage.compressed_symbol().to_string()),
+            uri,
+            ..Default::default()
+        };
+        self.invoke(
+            UPDATE_METADATA,
+            (
+                ticket.root,
+                ticket.nonce,
+                ticket.nonce as u32,
+                ticket.metadata.clone(),
+                update_args,
+            ),
+            accounts,
+            account_infos,
+            &[],
+        )
+    }
+
+    /// Invokes Bubblegum with the ticket authority signing alongside `signer_seeds`
+    fn invoke(
+        &self,
+        discriminator: [u8; 8],
+        args: impl AnchorSerialize,
+        accounts: Vec<AccountMeta>,
+        mut account_infos: Vec<AccountInfo<'info>>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let mut data = discriminator.to_vec();
+        args.serialize(&mut data)?;
+        account_infos.extend([
+            self.bubblegum_program.clone(),
+            self.tree_config.clone(),
+            self.merkle_tree.clone(),
+            self.ticket_authority.clone(),
+            self.log_wrapper.clone(),
+            self.compression_program.clone(),
+            self.system_program.clone(),
+        ]);
+
+        let bump = [self.ticket_authority_bump];
+        let ticket_authority_seeds: &[&[u8]] = &[
+            PROGRAM_SEED.as_bytes(),
+            TICKET_AUTHORITY_SEED.as_bytes(),
+            &bump,
+        ];
+        let mut signers = signer_seeds.to_vec();
+        signers.push(ticket_authority_seeds);
+
+        invoke_signed(
+            &Instruction {
+                program_id: BUBBLEGUM_PROGRAM_ID,
+                accounts,
+                data,
+            },
+            &account_infos,
+            &signers,
+        )?;
+
+        Ok(())
+    }
+}
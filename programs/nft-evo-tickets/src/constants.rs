@@ -9,8 +9,72 @@ pub const EVENT_SEED: &str = "event";
 #[constant]
 pub const TICKET_SEED: &str = "ticket";
 
+#[constant]
+pub const NFT_MINT_SEED: &str = "nft-mint";
+
 #[constant]
 pub const LISTING_SEED: &str = "listing";
 
 #[constant]
 pub const ESCROW_SEED: &str = "escrow";
+
+#[constant]
+pub const TIER_SEED: &str = "tier";
+
+#[constant]
+pub const VAULT_SEED: &str = "vault";
+
+#[constant]
+pub const CONFIG_SEED: &str = "config";
+
+#[constant]
+pub const TICKET_AUTHORITY_SEED: &str = "ticket-authority";
+
+#[constant]
+pub const SELLER_LISTINGS_SEED: &str = "seller-listings";
+
+#[constant]
+pub const BID_ESCROW_SEED: &str = "bid-escrow";
+
+#[constant]
+pub const OFFER_SEED: &str = "offer";
+
+#[constant]
+pub const PURCHASES_SEED: &str = "purchases";
+
+#[constant]
+pub const SECTION_SEED: &str = "section";
+
+#[constant]
+pub const SEAT_SEED: &str = "seat";
+
+#[constant]
+pub const ALLOWLIST_SEED: &str = "allowlist";
+
+#[constant]
+pub const MERKLE_ALLOWLIST_SEED: &str = "merkle-allowlist";
+
+#[constant]
+pub const ALLOWLIST_CLAIMS_SEED: &str = "allowlist-claims";
+
+#[constant]
+pub const METADATA_CONFIG_SEED: &str = "metadata-config";
+
+#[constant]
+pub const COLLECTION_SEED: &str = "collection";
+
+#[constant]
+pub const MAX_ALLOWLIST_SLOTS: u32 = 8192; // Capacity of an AllowlistClaims bitmap
+
+#[constant]
+pub const MAX_SEAT_LEN: usize = 32; // Seat ids are PDA seeds, which are capped at 32 bytes
+
+#[constant]
+pub const MAX_ROYALTY_RECIPIENTS: usize = 4; // Metaplex allows at most 5 creators
+
+#[constant]
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+#[constant]
+pub const MAX_METADATA_URI_LEN: usize = 200; // Metaplex rejects longer URIs
+
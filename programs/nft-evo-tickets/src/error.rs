@@ -28,4 +28,148 @@ pub enum ErrorCode {
     EventAlreadyStarted,
     #[msg("Tickets have already been sold")]
     TicketsAlreadySold,
+    #[msg("This ticket tier is sold out")]
+    TierSoldOut,
+    #[msg("Tier supply exceeds the event ticket supply")]
+    TierSupplyExceeded,
+    #[msg("Basis points exceed the allowed maximum")]
+    InvalidBasisPoints,
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("The program is paused")]
+    ProgramPaused,
+    #[msg("This event is paused")]
+    EventPaused,
+    #[msg("Event is not active")]
+    EventNotActive,
+    #[msg("Event has not been cancelled")]
+    EventNotCancelled,
+    #[msg("All tickets must be refunded or the event settled first")]
+    TicketsNotRefunded,
+    #[msg("Event has been cancelled")]
+    EventCancelled,
+    #[msg("The dispute window has not elapsed yet")]
+    DisputeWindowActive,
+    #[msg("Seller does not match the listing")]
+    SellerMismatch,
+    #[msg("Event does not match the ticket")]
+    EventMismatch,
+    #[msg("NFT mint does not match the ticket")]
+    MintMismatch,
+    #[msg("Listing does not match the ticket")]
+    ListingMismatch,
+    #[msg("Token account does not use the event payment mint")]
+    PaymentMintMismatch,
+    #[msg("Token payment accounts are required for this event")]
+    MissingPaymentAccounts,
+    #[msg("Token program does not match the event ticket mint mode")]
+    TicketTokenProgramMismatch,
+    #[msg("Extra account metas are required for transfer hook tickets")]
+    MissingExtraAccountMetas,
+    #[msg("This ticket is soulbound and cannot be transferred")]
+    TicketNonTransferable,
+    #[msg("Transfer hook can only be invoked during a token transfer")]
+    NotTransferring,
+    #[msg("Signer does not hold this ticket")]
+    NotTicketHolder,
+    #[msg("Ticket transfers are disabled for this event")]
+    TransfersDisabled,
+    #[msg("Ticket transfers are closed once the event starts")]
+    TransferWindowClosed,
+    #[msg("This ticket has reached its transfer limit")]
+    TransferLimitReached,
+    #[msg("Listing price exceeds the event's resale markup cap")]
+    ResalePriceTooHigh,
+    #[msg("This ticket was acquired too recently to be listed")]
+    ResaleCooldownActive,
+    #[msg("Seller has reached the listing limit for this event")]
+    ListingLimitReached,
+    #[msg("Royalty shares must be non-zero, unique and sum to the royalty total")]
+    InvalidRoyaltySplit,
+    #[msg("Remaining accounts do not match the event royalty recipients")]
+    RoyaltyRecipientMismatch,
+    #[msg("This wallet has reached the ticket limit for this event")]
+    PurchaseLimitExceeded,
+    #[msg("Seat id is not a seat of the selected section")]
+    InvalidSeat,
+    #[msg("Seat reservation does not match the ticket's seat")]
+    SeatReservationMismatch,
+    #[msg("Tickets for this event must be for a seat in one of its sections")]
+    SeatSectionRequired,
+    #[msg("Sale phases must be in order and close by the end of the event")]
+    InvalidSaleSchedule,
+    #[msg("Ticket sales have not opened yet")]
+    SalesNotOpen,
+    #[msg("Ticket sales have closed")]
+    SalesClosed,
+    #[msg("Buyer is not on the allowlist for the current sale phase")]
+    NotOnAllowlist,
+    #[msg("Allowlist proof does not match the sale phase's Merkle root")]
+    InvalidAllowlistProof,
+    #[msg("This allowlist allocation has been fully claimed")]
+    AllowlistAllocationUsed,
+    #[msg("Dutch auction prices must fall to the floor within the event's sale period")]
+    InvalidDutchAuction,
+    #[msg("The Dutch auction cannot change once tickets have been sold")]
+    DutchAuctionLocked,
+    #[msg("The auction clearing price is not final yet")]
+    AuctionNotFinal,
+    #[msg("No auction rebate is due for this ticket")]
+    NoRebateDue,
+    #[msg("Auction listings need a SOL price, a bid escrow and an end time in the future")]
+    InvalidAuction,
+    #[msg("Auction listings can only be sold through bids")]
+    ListingIsAuction,
+    #[msg("This listing is not an auction")]
+    NotAnAuction,
+    #[msg("The auction has ended")]
+    AuctionEnded,
+    #[msg("The auction has not ended yet")]
+    AuctionNotEnded,
+    #[msg("Bid is below the reserve price or the minimum increment")]
+    BidTooLow,
+    #[msg("Account does not match the auction's highest bidder")]
+    BidderMismatch,
+    #[msg("An auction with bids cannot be cancelled")]
+    AuctionHasBids,
+    #[msg("Offers need a SOL-priced event, a price and an expiry in the future")]
+    InvalidOffer,
+    #[msg("Offer is for a different ticket or event")]
+    OfferMismatch,
+    #[msg("Offer has expired")]
+    OfferExpired,
+    #[msg("Ticket metadata is not controlled by the program's ticket authority")]
+    MetadataAuthorityMismatch,
+    #[msg("Metadata URI is longer than 200 bytes")]
+    MetadataUriTooLong,
+    #[msg("Metadata URI templates need every stage, printable ASCII and known placeholders")]
+    InvalidMetadataTemplate,
+    #[msg("Event metadata config is missing or belongs to another event")]
+    MetadataConfigMismatch,
+    #[msg("Collection accounts are required for events with a collection")]
+    MissingCollectionAccounts,
+    #[msg("Collection mint does not belong to this event")]
+    CollectionMismatch,
+    #[msg("Event collections only hold legacy ticket mints")]
+    CollectionRequiresLegacyMints,
+    #[msg("Tickets of this event are compressed NFTs")]
+    TicketsAreCompressed,
+    #[msg("Event does not issue compressed tickets")]
+    TicketsNotCompressed,
+    #[msg("Merkle tree is missing or not the event's ticket tree")]
+    TicketTreeMismatch,
+    #[msg("Merkle tree cannot hold the event's ticket supply")]
+    TicketTreeTooSmall,
+    #[msg("Compressed ticket leaf does not hold a ticket of this event")]
+    InvalidCompressedTicket,
+    #[msg("Resale price caps and cool-downs need ticket accounts, which compressed tickets don't have")]
+    ResalePolicyNeedsTicketAccounts,
+    #[msg("Vault token account still holds a balance")]
+    VaultNotEmpty,
+    #[msg("Tickets need a metadata URI, set the event's metadata config first")]
+    MetadataConfigRequired,
+    #[msg("Compressed tickets can't be refunded, rebated or limited in transfers")]
+    CompressedTicketsUnsupported,
+    #[msg("Treasury does not match the program config")]
+    TreasuryMismatch,
 }
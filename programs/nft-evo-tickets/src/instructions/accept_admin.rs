@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::ProgramConfig;
+
+#[derive(Accounts)]
+pub struct AcceptAdminCtx<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = program_config.pending_admin.is_some() @ ErrorCode::NoPendingAdmin,
+        constraint = program_config.pending_admin == Some(new_admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+pub fn handler(ctx: Context<AcceptAdminCtx>) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    let previous_admin = program_config.admin;
+
+    program_config.admin = ctx.accounts.new_admin.key();
+    program_config.pending_admin = None;
+
+    emit!(AdminTransferred {
+        previous_admin,
+        new_admin: program_config.admin,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::instructions::buy_marketplace_ticket::royalty_payments;
+use crate::state::{EventAccount, EventStatus, OfferAccount, ProgramConfig, TicketAccount, TicketStage};
+use crate::ticket_token::TicketTransfer;
+
+#[derive(Accounts)]
+pub struct AcceptOfferCtx<'info> {
+    /// Must hold the ticket, pays for the buyer's NFT account if missing
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Listed tickets sit in escrow and have to be unlisted first
+    #[account(
+        mut,
+        constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed,
+        constraint = ticket_account.stage == TicketStage::Qr || ticket_account.stage == TicketStage::Collectible @ ErrorCode::CannotListInCurrentStage
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// Pays the seller out of its escrow, then goes back to the buyer with its rent
+    #[account(
+        mut,
+        close = buyer,
+        constraint = offer_account.event == event_account.key() @ ErrorCode::OfferMismatch,
+        constraint = offer_account.ticket.is_none() || offer_account.ticket == Some(ticket_account.key()) @ ErrorCode::OfferMismatch
+    )]
+    pub offer_account: Account<'info, OfferAccount>,
+
+    #[account(
+        mut,
+        address = offer_account.buyer @ ErrorCode::Unauthorized
+    )]
+    pub buyer: SystemAccount<'info>,
+
+    /// Platform treasury, paid the marketplace fee on events without a royalty split
+    #[account(
+        mut,
+        address = program_config.treasury @ ErrorCode::TreasuryMismatch
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = ticket_token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Seller's NFT token account, must actually hold the ticket
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = seller,
+        token::token_program = ticket_token_program,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NotTicketHolder
+    )]
+    pub seller_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = nft_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub buyer_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Program PDA that can pause the transfer hook while the program moves the ticket
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    pub ticket_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, AcceptOfferCtx<'info>>) -> Result<()> {
+    let offer = &ctx.accounts.offer_account;
+    let price = offer.price_lamports;
+    let current_time = Clock::get()?.unix_timestamp;
+    if let Some(expires_at) = offer.expires_at {
+        require!(current_time <= expires_at, ErrorCode::OfferExpired);
+    }
+
+    // Accepting an offer is a resale like any other, the listing limit aside
+    ctx.accounts.event_account.check_resale_allowed(
+        &ctx.accounts.ticket_account,
+        price,
+        0,
+        current_time,
+    )?;
+
+    let royalty_payments = royalty_payments(
+        &ctx.accounts.event_account,
+        &ctx.accounts.program_config,
+        price,
+        None,
+        Some(ctx.accounts.treasury.to_account_info()),
+        ctx.remaining_accounts,
+    )?;
+    let royalty_amount: u64 = royalty_payments.iter().map(|(_, amount)| amount).sum();
+
+    ctx.accounts.offer_account.sub_lamports(price)?;
+    ctx.accounts.seller.add_lamports(price - royalty_amount)?;
+    for (payee, amount) in royalty_payments {
+        payee.add_lamports(amount)?;
+    }
+
+    TicketTransfer {
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
+        from: ctx.accounts.seller_nft_account.to_account_info(),
+        to: ctx.accounts.buyer_nft_account.to_account_info(),
+        authority: ctx.accounts.seller.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+    }
+    .transfer(&[])?;
+
+    let ticket = &mut ctx.accounts.ticket_account;
+    ticket.owner = ctx.accounts.buyer.key();
+    ticket.acquired_at = current_time;
+
+    emit!(OfferAccepted {
+        offer_account: ctx.accounts.offer_account.key(),
+        ticket_account: ticket.key(),
+        seller: ctx.accounts.seller.key(),
+        buyer: ticket.owner,
+        price_lamports: price,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct OfferAccepted {
+    pub offer_account: Pubkey,
+    pub ticket_account: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub price_lamports: u64,
+}
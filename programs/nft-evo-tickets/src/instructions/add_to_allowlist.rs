@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{ALLOWLIST_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{AllowlistEntry, EventAccount};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToAllowlistCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AllowlistEntry::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            ALLOWLIST_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            wallet.as_ref(),
+        ],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<AddToAllowlistCtx>, wallet: Pubkey) -> Result<()> {
+    let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+    allowlist_entry.event = ctx.accounts.event_account.key();
+    allowlist_entry.wallet = wallet;
+    allowlist_entry.bump = ctx.bumps.allowlist_entry;
+
+    emit!(AllowlistUpdated {
+        event_account: allowlist_entry.event,
+        wallet,
+        allowed: true,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AllowlistUpdated {
+    pub event_account: Pubkey,
+    pub wallet: Pubkey,
+    pub allowed: bool,
+}
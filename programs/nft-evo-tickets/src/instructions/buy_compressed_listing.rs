@@ -0,0 +1,262 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::compressed_ticket::{
+    self, CompressedTicket, TicketTree, ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use crate::constants::{CONFIG_SEED, LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::instructions::buy_marketplace_ticket::royalty_payments;
+use crate::state::{EventAccount, EventStatus, ListingAccount, ProgramConfig, SellerListings};
+
+#[derive(Accounts)]
+#[instruction(ticket: CompressedTicket)]
+pub struct BuyCompressedListingCtx<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// CHECK: The event's ticket tree, checked by Bubblegum
+    #[account(
+        mut,
+        constraint = event_account.merkle_tree == Some(merkle_tree.key()) @ ErrorCode::TicketTreeMismatch
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// The listing for this ticket, it holds the leaf
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            LISTING_SEED.as_bytes(),
+            compressed_ticket::asset_id(&merkle_tree.key(), ticket.nonce).as_ref()
+        ],
+        bump = listing_account.bump,
+        constraint = listing_account.auction.is_none() @ ErrorCode::ListingIsAuction,
+        close = buyer
+    )]
+    pub listing_account: Account<'info, ListingAccount>,
+
+    /// Platform treasury, paid the marketplace fee on events without a royalty split
+    #[account(
+        mut,
+        address = program_config.treasury @ ErrorCode::TreasuryMismatch
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    /// Must be the seller recorded on the listing, otherwise the buyer could pay themselves
+    #[account(
+        mut,
+        address = listing_account.seller @ ErrorCode::SellerMismatch
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seller.key().as_ref()
+        ],
+        bump = seller_listings.bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
+
+    /// CHECK: Bubblegum tree config PDA of merkle_tree
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that created the tree
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Noop program Bubblegum logs leaves to
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// Used for SPL token payments
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Buyer's token account, required when the listing is priced in a payment_mint
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = Some(buyer_payment_account.mint) == listing_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Seller's token account, required when the listing is priced in a payment_mint
+    #[account(
+        mut,
+        token::authority = seller,
+        constraint = Some(seller_payment_account.mint) == listing_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub seller_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury's token account, required when the listing is priced in a payment_mint
+    #[account(
+        mut,
+        token::authority = treasury,
+        constraint = Some(treasury_payment_account.mint) == listing_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub treasury_payment_account: Option<Account<'info, TokenAccount>>,
+}
+
+impl<'info> BuyCompressedListingCtx<'info> {
+    /// Pays `amount` from the buyer to `to`, a wallet for SOL listings or a payment_mint token account
+    fn pay(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        if self.listing_account.payment_mint.is_some() {
+            let buyer_payment_account = self
+                .buyer_payment_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingPaymentAccounts)?;
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: buyer_payment_account.to_account_info(),
+                        to,
+                        authority: self.buyer.to_account_info(),
+                    },
+                ),
+                amount,
+            )
+        } else {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.buyer.to_account_info(),
+                        to,
+                    },
+                ),
+                amount,
+            )
+        }
+    }
+}
+
+/// Remaining accounts are the event's royalty recipients, as for
+/// buy_marketplace_ticket, followed by the tree's proof nodes for `ticket`
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyCompressedListingCtx<'info>>,
+    ticket: CompressedTicket,
+) -> Result<()> {
+    let listing = &ctx.accounts.listing_account;
+    let event_account = &ctx.accounts.event_account;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    if let Some(expires_at) = listing.expires_at {
+        require!(current_time <= expires_at, ErrorCode::ListingExpired);
+    }
+
+    // Seller and treasury are paid in SOL, or into their payment_mint token accounts
+    let (seller_payee, treasury_payee) = if listing.payment_mint.is_some() {
+        let (Some(buyer_payment_account), Some(seller_payment_account)) = (
+            &ctx.accounts.buyer_payment_account,
+            &ctx.accounts.seller_payment_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+        require!(
+            buyer_payment_account.amount >= listing.price_lamports,
+            ErrorCode::InsufficientPayment
+        );
+        let treasury_payee = ctx.accounts.treasury_payment_account.as_ref().map(|a| a.to_account_info());
+        (seller_payment_account.to_account_info(), treasury_payee)
+    } else {
+        require!(
+            ctx.accounts.buyer.lamports() >= listing.price_lamports,
+            ErrorCode::InsufficientPayment
+        );
+        (ctx.accounts.seller.to_account_info(), Some(ctx.accounts.treasury.to_account_info()))
+    };
+
+    let recipient_count = event_account.royalty_recipients.len();
+    require!(
+        ctx.remaining_accounts.len() >= recipient_count,
+        ErrorCode::RoyaltyRecipientMismatch
+    );
+    let (royalty_accounts, proof) = ctx.remaining_accounts.split_at(recipient_count);
+    let royalty_payments = royalty_payments(
+        event_account,
+        &ctx.accounts.program_config,
+        listing.price_lamports,
+        listing.payment_mint,
+        treasury_payee,
+        royalty_accounts,
+    )?;
+
+    let royalty_amount: u64 = royalty_payments.iter().map(|(_, amount)| amount).sum();
+    let seller_amount = listing.price_lamports - royalty_amount;
+
+    ctx.accounts.pay(seller_payee, seller_amount)?;
+    for (recipient, amount) in royalty_payments {
+        ctx.accounts.pay(recipient, amount)?;
+    }
+
+    // Move the leaf from the listing PDA to the buyer
+    let asset_id = ticket.asset_id(&ctx.accounts.merkle_tree.key());
+    let seeds = &[
+        PROGRAM_SEED.as_bytes(),
+        LISTING_SEED.as_bytes(),
+        asset_id.as_ref(),
+        &[ctx.accounts.listing_account.bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let listing = ctx.accounts.listing_account.to_account_info();
+    TicketTree {
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        tree_config: ctx.accounts.tree_config.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .transfer(
+        &ticket,
+        listing.clone(),
+        listing,
+        ctx.accounts.buyer.to_account_info(),
+        signer_seeds,
+        proof,
+    )?;
+
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    seller_listings.active_listings = seller_listings.active_listings.saturating_sub(1);
+
+    Ok(())
+}
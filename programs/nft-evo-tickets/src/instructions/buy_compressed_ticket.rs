@@ -0,0 +1,266 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::compressed_ticket::{
+    self, CompressedMetadata, TicketTree, ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID,
+    NOOP_PROGRAM_ID,
+};
+use crate::constants::{ALLOWLIST_SEED, CONFIG_SEED, METADATA_CONFIG_SEED, PROGRAM_SEED, PURCHASES_SEED, SEAT_SEED, TICKET_AUTHORITY_SEED, TIER_SEED, VAULT_SEED};
+use crate::error::ErrorCode;
+use crate::instructions::buy_event_ticket::PrimarySale;
+use crate::state::{AllowlistClaims, AllowlistEntry, AllowlistProof, BuyerPurchases, EventAccount, EventMetadataConfig, EventStatus, EventVault, MerkleAllowlist, ProgramConfig, SeatReservation, SeatSection, TicketMintMode, TicketStage, TicketTier};
+
+/// Primary sale of a compressed ticket. Same checks and payment as
+/// buy_event_ticket, but the ticket is a leaf of the event's tree rather than
+/// a TicketAccount and mint
+#[derive(Accounts)]
+#[instruction(tier_index: u8, seat: Option<String>)]
+pub struct BuyCompressedTicketCtx<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status == EventStatus::Active @ ErrorCode::EventNotActive,
+        constraint = event_account.ticket_mint_mode == TicketMintMode::Compressed @ ErrorCode::TicketsNotCompressed
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// The tier being purchased; its stored price is what the buyer pays
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            TIER_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[tier_index],
+        ],
+        bump = ticket_tier.bump,
+        constraint = ticket_tier.event == event_account.key() @ ErrorCode::InvalidInput
+    )]
+    pub ticket_tier: Account<'info, TicketTier>,
+
+    /// Vault holding the payment until the event settles or is cancelled
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    /// Buyer's primary purchases for this event, checked against max_tickets_per_wallet
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerPurchases::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            PURCHASES_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            buyer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub buyer_purchases: Account<'info, BuyerPurchases>,
+
+    /// CHECK: The event's ticket tree, checked by Bubblegum
+    #[account(
+        mut,
+        constraint = event_account.merkle_tree == Some(merkle_tree.key()) @ ErrorCode::TicketTreeMismatch
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config PDA of merkle_tree
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that created the tree and mints into it
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Noop program Bubblegum logs leaves to
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// Pays for SPL token priced tickets
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+
+    /// Buyer's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = Some(buyer_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Section the seat belongs to, required once the event has sections
+    #[account(constraint = seat_section.event == event_account.key() @ ErrorCode::EventMismatch)]
+    pub seat_section: Option<Account<'info, SeatSection>>,
+
+    /// Claims the seat for the ticket's asset id, required when buying one
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SeatReservation::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SEAT_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seat.as_deref().unwrap_or_default().as_bytes(),
+        ],
+        bump
+    )]
+    pub seat_reservation: Option<Account<'info, SeatReservation>>,
+
+    /// Buyer's allowlist entry, required during the presale and allowlist windows
+    #[account(
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            ALLOWLIST_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            buyer.key().as_ref(),
+        ],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
+
+    /// Merkle allowlist of the current sale phase, an alternative to an allowlist entry
+    #[account(
+        constraint = merkle_allowlist.event == event_account.key() @ ErrorCode::EventMismatch
+    )]
+    pub merkle_allowlist: Option<Account<'info, MerkleAllowlist>>,
+
+    /// Claimed slots of `merkle_allowlist`
+    #[account(mut)]
+    pub allowlist_claims: Option<Box<Account<'info, AllowlistClaims>>>,
+
+    /// Metadata URI templates, required once the event has them
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = metadata_config.bump
+    )]
+    pub metadata_config: Option<Account<'info, EventMetadataConfig>>,
+}
+
+pub fn handler(
+    ctx: Context<BuyCompressedTicketCtx>,
+    tier_index: u8,
+    seat: Option<String>,
+    allowlist_proof: Option<AllowlistProof>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let (price_paid, _) = PrimarySale {
+        buyer: &ctx.accounts.buyer,
+        event_account: &mut ctx.accounts.event_account,
+        ticket_tier: &mut ctx.accounts.ticket_tier,
+        event_vault: &mut ctx.accounts.event_vault,
+        buyer_purchases: &mut ctx.accounts.buyer_purchases,
+        buyer_purchases_bump: ctx.bumps.buyer_purchases,
+        seat_section: ctx.accounts.seat_section.as_ref(),
+        has_seat_reservation: ctx.accounts.seat_reservation.is_some(),
+        has_allowlist_entry: ctx.accounts.allowlist_entry.is_some(),
+        merkle_allowlist: ctx.accounts.merkle_allowlist.as_ref(),
+        allowlist_claims: ctx.accounts.allowlist_claims.as_deref_mut(),
+        buyer_payment_account: ctx.accounts.buyer_payment_account.as_ref(),
+        vault_payment_account: ctx.accounts.vault_payment_account.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .sell(tier_index, seat.as_ref(), allowlist_proof.as_ref(), current_time)?;
+
+    let event_account = &ctx.accounts.event_account;
+    let buyer = &ctx.accounts.buyer;
+    let stage = if current_time >= event_account.start_ts {
+        TicketStage::Qr
+    } else {
+        TicketStage::Prestige
+    };
+
+    // The tree predates the first sale and only this program mints into it, so the leaf index follows the serial
+    let serial = event_account.tickets_sold;
+    let merkle_tree = ctx.accounts.merkle_tree.key();
+    let asset_id = compressed_ticket::asset_id(&merkle_tree, serial as u64 - 1);
+
+    if let (Some(seat_reservation), Some(bump)) =
+        (&mut ctx.accounts.seat_reservation, ctx.bumps.seat_reservation)
+    {
+        seat_reservation.event = event_account.key();
+        seat_reservation.ticket = asset_id;
+        seat_reservation.bump = bump;
+    }
+
+    let uri = event_account
+        .ticket_uri(stage, serial, seat.as_ref(), &asset_id, ctx.accounts.metadata_config.as_deref())?
+        .ok_or(ErrorCode::MetadataConfigRequired)?;
+    let metadata = CompressedMetadata::new(
+        stage.get_name(&event_account.name, seat.as_ref()),
+        stage,
+        uri,
+        event_account.resale_royalty_bps(&ctx.accounts.program_config),
+        event_account.ticket_creators(),
+    );
+
+    TicketTree {
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        tree_config: ctx.accounts.tree_config.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .mint(buyer.to_account_info(), buyer.to_account_info(), metadata)?;
+
+    // Without a TicketAccount the price paid is only recorded here
+    emit!(CompressedTicketPurchased {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        asset_id,
+        serial,
+        buyer: buyer.key(),
+        price_paid,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct CompressedTicketPurchased {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub asset_id: Pubkey,
+    pub serial: u32,
+    pub buyer: Pubkey,
+    pub price_paid: u64,
+}
@@ -2,39 +2,82 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke_signed;
 use anchor_lang::solana_program::sysvar::clock::Clock;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, MintTo},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::{self, Token, TokenAccount, Transfer},
+    token_interface::{self, MintTo, TokenInterface},
 };
 use mpl_token_metadata::{
     instructions::{
         CreateMasterEditionV3, CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3,
         CreateMetadataAccountV3InstructionArgs,
     },
-    types::{Creator, DataV2},
+    types::{Collection, Creator, DataV2},
 };
 
 use crate::{
-    constants::{PROGRAM_SEED, TICKET_SEED},
+    constants::{ALLOWLIST_SEED, CONFIG_SEED, METADATA_CONFIG_SEED, NFT_MINT_SEED, PROGRAM_SEED, PURCHASES_SEED, SEAT_SEED, TICKET_AUTHORITY_SEED, TICKET_SEED, TIER_SEED, VAULT_SEED},
     error::ErrorCode,
-    state::{EventAccount, TicketAccount, TicketStage},
+    state::{AllowlistClaims, AllowlistEntry, AllowlistProof, BuyerPurchases, EventAccount, EventMetadataConfig, EventStatus, EventVault, MerkleAllowlist, ProgramConfig, SalePhase, SeatReservation, SeatSection, TicketAccount, TicketMintMode, TicketStage, TicketTier},
+    ticket_token::{EventCollection, TicketMint},
 };
 
 #[derive(Accounts)]
-#[instruction(ticket_price_lamports: u64, seat: Option<String>, ticket_id: u64)]
+#[instruction(tier_index: u8, seat: Option<String>)]
 pub struct BuyEventTicketCtx<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status == EventStatus::Active @ ErrorCode::EventNotActive,
+        constraint = event_account.ticket_mint_mode != TicketMintMode::Compressed @ ErrorCode::TicketsAreCompressed
+    )]
     pub event_account: Account<'info, EventAccount>,
 
-    /// The event organizer who will receive payment
-    /// CHECK: This is validated as the event authority
+    /// The tier being purchased; its stored price is what the buyer pays
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            TIER_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[tier_index],
+        ],
+        bump = ticket_tier.bump,
+        constraint = ticket_tier.event == event_account.key() @ ErrorCode::InvalidInput
+    )]
+    pub ticket_tier: Account<'info, TicketTier>,
+
+    /// Vault holding the payment until the event settles or is cancelled
     #[account(
         mut,
-        constraint = organizer.key() == event_account.authority @ ErrorCode::Unauthorized
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    /// Buyer's primary purchases for this event, checked against max_tickets_per_wallet
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerPurchases::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            PURCHASES_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            buyer.key().as_ref(),
+        ],
+        bump
     )]
-    pub organizer: UncheckedAccount<'info>,
+    pub buyer_purchases: Account<'info, BuyerPurchases>,
 
     #[account(
         init,
@@ -44,30 +87,28 @@ pub struct BuyEventTicketCtx<'info> {
             PROGRAM_SEED.as_bytes(),
             TICKET_SEED.as_bytes(),
             event_account.key().as_ref(),
-            buyer.key().as_ref(),
-            &ticket_id.to_le_bytes(),
+            &event_account.next_ticket_serial().to_le_bytes(),
         ],
         bump
     )]
     pub ticket_account: Account<'info, TicketAccount>,
 
+    /// CHECK: Created in the handler with the event's ticket_mint_mode
     #[account(
-        init,
-        payer = buyer,
+        mut,
         seeds = [
             PROGRAM_SEED.as_bytes(),
-            b"nft-mint",
+            NFT_MINT_SEED.as_bytes(),
             event_account.key().as_ref(),
-            buyer.key().as_ref(),
-            &ticket_id.to_le_bytes(),
+            &event_account.next_ticket_serial().to_le_bytes(),
         ],
-        bump,
-        mint::decimals = 0,
-        mint::authority = ticket_account.key(),
-        mint::freeze_authority = ticket_account.key(),
-        mint::token_program = token_program,
+        bump
     )]
-    pub nft_mint: Account<'info, Mint>,
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA holding the transfer hook and Token-2022 metadata authority
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
 
     /// CHECK: Metaplex Metadata PDA
     #[account(mut)]
@@ -77,16 +118,24 @@ pub struct BuyEventTicketCtx<'info> {
     #[account(mut)]
     pub master_edition: UncheckedAccount<'info>,
 
+    /// CHECK: Buyer's ATA for nft_mint, created in the handler
     #[account(
-        init_if_needed,
-        payer = buyer,
-        associated_token::mint = nft_mint,
-        associated_token::authority = buyer,
-        associated_token::token_program = token_program,
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &buyer.key(),
+            &nft_mint.key(),
+            &ticket_token_program.key()
+        )
     )]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub buyer_token_account: UncheckedAccount<'info>,
 
+    /// Pays for SPL token priced tickets and owns legacy ticket mints
     pub token_program: Program<'info, Token>,
+
+    #[account(
+        constraint = ticket_token_program.key() == event_account.ticket_mint_mode.token_program_id() @ ErrorCode::TicketTokenProgramMismatch
+    )]
+    pub ticket_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 
@@ -95,53 +144,297 @@ pub struct BuyEventTicketCtx<'info> {
     pub token_metadata_program: UncheckedAccount<'info>,
 
     pub rent: Sysvar<'info, Rent>,
-}
 
-pub fn handler(
-    ctx: Context<BuyEventTicketCtx>,
-    ticket_price_lamports: u64,
-    seat: Option<String>,
-    ticket_id: u64,
-) -> Result<()> {
-    let event_account = &mut ctx.accounts.event_account;
-    let ticket_account = &mut ctx.accounts.ticket_account;
-    let buyer = &ctx.accounts.buyer;
-    let organizer = &ctx.accounts.organizer;
+    /// Buyer's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = Some(buyer_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_payment_account: Option<Account<'info, TokenAccount>>,
 
-    // Check if tickets are still available
-    require!(
-        event_account.tickets_sold < event_account.ticket_supply,
-        ErrorCode::InvalidInput
-    );
+    /// Vault's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
 
-    // Increment tickets sold
-    event_account.tickets_sold = event_account
-        .tickets_sold
-        .checked_add(1)
-        .ok_or(ErrorCode::InvalidInput)?;
+    /// CHECK: Transfer hook ExtraAccountMetaList PDA, required for TransferHook events
+    #[account(mut)]
+    pub extra_account_meta_list: Option<UncheckedAccount<'info>>,
 
-    msg!("Ticket {} of {} sold", event_account.tickets_sold, event_account.ticket_supply);
+    /// Section the seat belongs to, required once the event has sections
+    #[account(constraint = seat_section.event == event_account.key() @ ErrorCode::EventMismatch)]
+    pub seat_section: Option<Account<'info, SeatSection>>,
 
-    // Transfer payment from buyer to organizer
-    let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
-        buyer.key,
-        organizer.key,
-        ticket_price_lamports,
-    );
+    /// Claims the seat, required when buying one. Creation fails if the seat is taken
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + SeatReservation::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SEAT_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seat.as_deref().unwrap_or_default().as_bytes(),
+        ],
+        bump
+    )]
+    pub seat_reservation: Option<Account<'info, SeatReservation>>,
 
-    anchor_lang::solana_program::program::invoke(
-        &transfer_ix,
-        &[
-            buyer.to_account_info(),
-            organizer.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
+    /// Buyer's allowlist entry, required during the presale and allowlist windows
+    #[account(
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            ALLOWLIST_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            buyer.key().as_ref(),
         ],
-    )?;
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Option<Account<'info, AllowlistEntry>>,
 
-    msg!("Payment transferred: {} lamports to organizer", ticket_price_lamports);
+    /// Merkle allowlist of the current sale phase, an alternative to an allowlist entry
+    #[account(
+        constraint = merkle_allowlist.event == event_account.key() @ ErrorCode::EventMismatch
+    )]
+    pub merkle_allowlist: Option<Account<'info, MerkleAllowlist>>,
 
-    // Check if event has started to determine initial ticket stage
+    /// Claimed slots of `merkle_allowlist`
+    #[account(mut)]
+    pub allowlist_claims: Option<Box<Account<'info, AllowlistClaims>>>,
+
+    /// Metadata URI templates, required once the event has them
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = metadata_config.bump
+    )]
+    pub metadata_config: Option<Account<'info, EventMetadataConfig>>,
+
+    /// CHECK: Mint of the event's collection, required once the event has one
+    #[account(
+        constraint = event_account.collection_mint == Some(collection_mint.key()) @ ErrorCode::CollectionMismatch
+    )]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex metadata PDA of the collection mint, checked by Metaplex
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex master edition PDA of the collection mint, checked by Metaplex
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+}
+
+/// Accounts of a primary sale, shared by buy_event_ticket and buy_compressed_ticket
+pub(crate) struct PrimarySale<'a, 'info> {
+    pub buyer: &'a Signer<'info>,
+    pub event_account: &'a mut Account<'info, EventAccount>,
+    pub ticket_tier: &'a mut Account<'info, TicketTier>,
+    pub event_vault: &'a mut Account<'info, EventVault>,
+    pub buyer_purchases: &'a mut Account<'info, BuyerPurchases>,
+    pub buyer_purchases_bump: u8,
+    pub seat_section: Option<&'a Account<'info, SeatSection>>,
+    pub has_seat_reservation: bool,
+    pub has_allowlist_entry: bool,
+    pub merkle_allowlist: Option<&'a Account<'info, MerkleAllowlist>>,
+    pub allowlist_claims: Option<&'a mut Account<'info, AllowlistClaims>>,
+    pub buyer_payment_account: Option<&'a Account<'info, TokenAccount>>,
+    pub vault_payment_account: Option<&'a Account<'info, TokenAccount>>,
+    pub token_program: &'a Program<'info, Token>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl PrimarySale<'_, '_> {
+    /// Checks the sale phase, supply, seat and purchase limit, counts the sale and
+    /// takes the buyer's payment into the event vault. Returns the price paid and
+    /// whether it was the Dutch auction price
+    pub fn sell(
+        self,
+        tier_index: u8,
+        seat: Option<&String>,
+        allowlist_proof: Option<&AllowlistProof>,
+        current_time: i64,
+    ) -> Result<(u64, bool)> {
+        let event_account = self.event_account;
+        let ticket_tier = self.ticket_tier;
+        let buyer = self.buyer;
+        let event_vault = self.event_vault;
+
+        // Every ticket is minted with a URI rendered from the event's templates
+        require!(event_account.has_metadata_config, ErrorCode::MetadataConfigRequired);
+
+        // Sales only run during the organizer's schedule, early windows are allowlist only
+        let sale_phase = event_account.sale_phase(current_time);
+        match sale_phase {
+            SalePhase::NotOpen => return err!(ErrorCode::SalesNotOpen),
+            SalePhase::Closed => return err!(ErrorCode::SalesClosed),
+            SalePhase::Presale | SalePhase::Allowlist if !self.has_allowlist_entry => {
+                // Without an entry the buyer claims one slot of their Merkle allowlist allocation
+                let (Some(proof), Some(merkle_allowlist), Some(allowlist_claims)) =
+                    (allowlist_proof, self.merkle_allowlist, self.allowlist_claims)
+                else {
+                    return err!(ErrorCode::NotOnAllowlist);
+                };
+                require!(merkle_allowlist.phase == sale_phase, ErrorCode::NotOnAllowlist);
+                require_keys_eq!(
+                    allowlist_claims.allowlist,
+                    merkle_allowlist.key(),
+                    ErrorCode::InvalidInput
+                );
+                merkle_allowlist.verify(&buyer.key(), proof)?;
+                let slot = allowlist_claims.claim(proof)?;
+                msg!("Allowlist slot {} claimed", slot);
+            }
+            SalePhase::Presale | SalePhase::Allowlist => {}
+            SalePhase::Public => {}
+        }
+
+        // Check if tickets are still available
+        require!(
+            event_account.tickets_sold < event_account.ticket_supply,
+            ErrorCode::InvalidInput
+        );
+        require!(ticket_tier.sold < ticket_tier.supply, ErrorCode::TierSoldOut);
+        event_account.check_seat(
+            seat,
+            self.seat_section.map(|section| &**section),
+            self.has_seat_reservation,
+        )?;
+
+        // The counter is kept even without a limit so one set later still applies to earlier purchases
+        let buyer_purchases = self.buyer_purchases;
+        if let Some(max_tickets) = event_account.max_tickets_per_wallet {
+            require!(
+                buyer_purchases.tickets_bought < max_tickets,
+                ErrorCode::PurchaseLimitExceeded
+            );
+        }
+        buyer_purchases.event = event_account.key();
+        buyer_purchases.buyer = buyer.key();
+        buyer_purchases.tickets_bought = buyer_purchases
+            .tickets_bought
+            .checked_add(1)
+            .ok_or(ErrorCode::InvalidInput)?;
+        buyer_purchases.bump = self.buyer_purchases_bump;
+
+        // Increment tickets sold, both for the event and the tier
+        event_account.tickets_sold = event_account
+            .tickets_sold
+            .checked_add(1)
+            .ok_or(ErrorCode::InvalidInput)?;
+        ticket_tier.sold = ticket_tier
+            .sold
+            .checked_add(1)
+            .ok_or(ErrorCode::InvalidInput)?;
+
+        msg!("Ticket {} of {} sold", event_account.tickets_sold, event_account.ticket_supply);
+        msg!("Tier {} ({}): {} of {} sold", tier_index, ticket_tier.name, ticket_tier.sold, ticket_tier.supply);
+
+        // The price always comes from the organizer, the auction, the seat's section or else the tier, never from the buyer
+        let (ticket_price_lamports, bought_at_auction) = match (
+            &event_account.sale_schedule,
+            &event_account.dutch_auction,
+            self.seat_section,
+        ) {
+            (Some(schedule), _, _) if sale_phase == SalePhase::Presale => (schedule.presale_price_lamports, false),
+            (_, Some(auction), _) => {
+                let price = auction.price_at(current_time);
+                event_account.auction_clearing_price_lamports = price;
+                (price, true)
+            }
+            (_, _, Some(seat_section)) => (seat_section.price_lamports, false),
+            _ => (ticket_tier.price_lamports, false),
+        };
+
+        // Auction sales are tracked until their rebate is claimed, so withdrawals leave it in the vault
+        if bought_at_auction {
+            event_vault.unrebated_auction_sales = event_vault
+                .unrebated_auction_sales
+                .checked_add(1)
+                .ok_or(ErrorCode::InvalidInput)?;
+            event_vault.unrebated_auction_proceeds = event_vault
+                .unrebated_auction_proceeds
+                .checked_add(ticket_price_lamports)
+                .ok_or(ErrorCode::InvalidInput)?;
+        }
+
+        // Transfer payment from buyer to the event vault, in SOL unless the event has a payment mint
+        if event_account.payment_mint.is_some() {
+            let (Some(buyer_payment_account), Some(vault_payment_account)) =
+                (self.buyer_payment_account, self.vault_payment_account)
+            else {
+                return err!(ErrorCode::MissingPaymentAccounts);
+            };
+
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: buyer_payment_account.to_account_info(),
+                        to: vault_payment_account.to_account_info(),
+                        authority: buyer.to_account_info(),
+                    },
+                ),
+                ticket_price_lamports,
+            )?;
+
+            msg!("Payment transferred: {} tokens to event vault", ticket_price_lamports);
+        } else {
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                buyer.key,
+                &event_vault.key(),
+                ticket_price_lamports,
+            );
+
+            anchor_lang::solana_program::program::invoke(
+                &transfer_ix,
+                &[
+                    buyer.to_account_info(),
+                    event_vault.to_account_info(),
+                    self.system_program.to_account_info(),
+                ],
+            )?;
+
+            msg!("Payment transferred: {} lamports to event vault", ticket_price_lamports);
+        }
+
+        Ok((ticket_price_lamports, bought_at_auction))
+    }
+}
+
+pub fn handler(
+    ctx: Context<BuyEventTicketCtx>,
+    tier_index: u8,
+    seat: Option<String>,
+    allowlist_proof: Option<AllowlistProof>,
+) -> Result<()> {
     let current_time = Clock::get()?.unix_timestamp;
+    let (ticket_price_lamports, bought_at_auction) = PrimarySale {
+        buyer: &ctx.accounts.buyer,
+        event_account: &mut ctx.accounts.event_account,
+        ticket_tier: &mut ctx.accounts.ticket_tier,
+        event_vault: &mut ctx.accounts.event_vault,
+        buyer_purchases: &mut ctx.accounts.buyer_purchases,
+        buyer_purchases_bump: ctx.bumps.buyer_purchases,
+        seat_section: ctx.accounts.seat_section.as_ref(),
+        has_seat_reservation: ctx.accounts.seat_reservation.is_some(),
+        has_allowlist_entry: ctx.accounts.allowlist_entry.is_some(),
+        merkle_allowlist: ctx.accounts.merkle_allowlist.as_ref(),
+        allowlist_claims: ctx.accounts.allowlist_claims.as_deref_mut(),
+        buyer_payment_account: ctx.accounts.buyer_payment_account.as_ref(),
+        vault_payment_account: ctx.accounts.vault_payment_account.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .sell(tier_index, seat.as_ref(), allowlist_proof.as_ref(), current_time)?;
+
+    let event_account = &ctx.accounts.event_account;
+    let ticket_account = &mut ctx.accounts.ticket_account;
+    let buyer = &ctx.accounts.buyer;
+
+    // Check if event has started to determine initial ticket stage
     let event_has_started = current_time >= event_account.start_ts;
     
     // Initialize ticket account
@@ -149,6 +442,9 @@ pub fn handler(
     ticket_account.owner = buyer.key();
     ticket_account.nft_mint = ctx.accounts.nft_mint.key();
     ticket_account.seat = seat.clone();
+    ticket_account.tier_index = Some(tier_index);
+    ticket_account.price_paid_lamports = ticket_price_lamports;
+    ticket_account.bought_at_auction = bought_at_auction;
     
     // Set ticket stage based on event timing
     if event_has_started {
@@ -163,25 +459,78 @@ pub fn handler(
     ticket_account.was_scanned = false;
     ticket_account.listing_price = None;
     ticket_account.listing_expires_at = None;
+    ticket_account.serial = event_account.tickets_sold;
+    ticket_account.transfer_count = 0;
+    ticket_account.acquired_at = current_time;
     ticket_account.bump = ctx.bumps.ticket_account;
 
-    // Mint 1 NFT token to buyer
+    if let (Some(seat_reservation), Some(bump)) =
+        (&mut ctx.accounts.seat_reservation, ctx.bumps.seat_reservation)
+    {
+        seat_reservation.event = event_account.key();
+        seat_reservation.ticket = ticket_account.key();
+        seat_reservation.bump = bump;
+    }
+
     let event_key = event_account.key();
-    let buyer_key = buyer.key();
-    let ticket_id_bytes = ticket_id.to_le_bytes();
+    let serial_bytes = event_account.tickets_sold.to_le_bytes();
     let seeds = &[
         PROGRAM_SEED.as_bytes(),
         TICKET_SEED.as_bytes(),
         event_key.as_ref(),
-        buyer_key.as_ref(),
-        &ticket_id_bytes,
+        &serial_bytes,
         &[ctx.bumps.ticket_account],
     ];
     let signer = &[&seeds[..]];
 
-    token::mint_to(
+    // Create metadata, sales only open once the organizer has set the event's templates
+    let (ticket_name, metadata_uri) =
+        event_account.ticket_metadata(ticket_account, ctx.accounts.metadata_config.as_deref())?;
+    let metadata_uri = metadata_uri.ok_or(ErrorCode::MetadataConfigRequired)?;
+
+    // Create the mint for the event's mode and the buyer's token account
+    let ticket_mint_mode = event_account.ticket_mint_mode;
+    let ticket_mint = TicketMint {
+        payer: buyer.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
+        mint_authority: ticket_account.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    ticket_mint.create(
+        ticket_mint_mode,
+        &[
+            PROGRAM_SEED.as_bytes(),
+            NFT_MINT_SEED.as_bytes(),
+            event_key.as_ref(),
+            &serial_bytes,
+            &[ctx.bumps.nft_mint],
+        ],
+        seeds,
+        ticket_name.clone(),
+        "TIX".to_string(),
+        metadata_uri.clone(),
+    )?;
+    ticket_mint.create_token_account(
+        buyer.to_account_info(),
+        ctx.accounts.buyer_token_account.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+    )?;
+
+    if ticket_mint_mode == TicketMintMode::TransferHook {
+        let extra_account_meta_list = ctx
+            .accounts
+            .extra_account_meta_list
+            .as_ref()
+            .ok_or(ErrorCode::MissingExtraAccountMetas)?;
+        ticket_mint.init_extra_account_metas(extra_account_meta_list, &ticket_account.key(), &event_key)?;
+    }
+
+    // Mint 1 NFT token to buyer
+    token_interface::mint_to(
         CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.ticket_token_program.to_account_info(),
             MintTo {
                 mint: ctx.accounts.nft_mint.to_account_info(),
                 to: ctx.accounts.buyer_token_account.to_account_info(),
@@ -194,41 +543,53 @@ pub fn handler(
 
     msg!("NFT minted to buyer token account");
 
-    // Create metadata
-    let metadata_uri = format!(
-        "https://nft-evo-tickets.vercel.app/api/metadata/{}/{}",
-        event_account.event_id,
-        buyer.key()
-    );
-
-    // Metaplex has a 32 character limit for NFT names
-    // Truncate event name to ensure total length stays under 32 chars
-    let max_event_name_len = if seat.is_some() { 20 } else { 24 };
-    let truncated_name = if event_account.name.len() > max_event_name_len {
-        format!("{}...", &event_account.name[..max_event_name_len])
-    } else {
-        event_account.name.clone()
-    };
+    // Token-2022 tickets carry their metadata on the mint, Metaplex is only used for legacy mints
+    if ticket_mint_mode != TicketMintMode::Legacy {
+        msg!("✅ Ticket purchased! Token-2022 NFT minted to buyer: {}", buyer.key());
+        return Ok(());
+    }
 
-    let ticket_name = if let Some(seat_id) = &seat {
-        // Format: "Event Name... - S123" (max 32 chars)
-        let seat_suffix = format!(" - S{}", seat_id);
-        if truncated_name.len() + seat_suffix.len() > 32 {
-            format!("{}...{}", &truncated_name[..28], &seat_suffix[seat_suffix.len()-4..])
-        } else {
-            format!("{}{}", truncated_name, seat_suffix)
+    // Tickets join the event's collection, verified once their metadata exists
+    let collection = match event_account.collection_mint {
+        Some(_) => {
+            let (Some(collection_mint), Some(collection_metadata), Some(collection_master_edition)) = (
+                &ctx.accounts.collection_mint,
+                &ctx.accounts.collection_metadata,
+                &ctx.accounts.collection_master_edition,
+            ) else {
+                return err!(ErrorCode::MissingCollectionAccounts);
+            };
+            Some(EventCollection {
+                payer: buyer.to_account_info(),
+                mint: collection_mint.to_account_info(),
+                metadata: collection_metadata.to_account_info(),
+                master_edition: collection_master_edition.to_account_info(),
+                ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+                ticket_authority_bump: ctx.bumps.ticket_authority,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            })
         }
-    } else {
-        // Format: "Event Name... - Ticket" (max 32 chars)
-        format!("{} - Ticket", truncated_name)
+        None => None,
     };
 
+    let creators = event_account
+        .ticket_creators()
+        .into_iter()
+        .map(|(address, share)| Creator {
+            address,
+            verified: false,
+            share,
+        })
+        .collect();
+
     let create_metadata_accounts = CreateMetadataAccountV3 {
         metadata: ctx.accounts.metadata.key(),
         mint: ctx.accounts.nft_mint.key(),
         mint_authority: ticket_account.key(),
         payer: buyer.key(),
-        update_authority: (ticket_account.key(), true),
+        update_authority: (ctx.accounts.ticket_authority.key(), true),
         system_program: ctx.accounts.system_program.key(),
         rent: Some(ctx.accounts.rent.key()),
     };
@@ -238,13 +599,12 @@ pub fn handler(
             name: ticket_name,
             symbol: "TIX".to_string(),
             uri: metadata_uri,
-            seller_fee_basis_points: 0,
-            creators: Some(vec![Creator {
-                address: event_account.authority,
+            seller_fee_basis_points: event_account.resale_royalty_bps(&ctx.accounts.program_config),
+            creators: Some(creators),
+            collection: collection.as_ref().map(|collection| Collection {
                 verified: false,
-                share: 100,
-            }]),
-            collection: None,
+                key: collection.mint.key(),
+            }),
             uses: None,
         },
         is_mutable: true,
@@ -253,6 +613,14 @@ pub fn handler(
 
     let create_metadata_ix = create_metadata_accounts.instruction(create_metadata_args);
 
+    // The ticket authority PDA signs as update authority so the program can evolve the metadata later
+    let ticket_authority_seeds: &[&[u8]] = &[
+        PROGRAM_SEED.as_bytes(),
+        TICKET_AUTHORITY_SEED.as_bytes(),
+        &[ctx.bumps.ticket_authority],
+    ];
+    let metadata_signers = &[&seeds[..], ticket_authority_seeds];
+
     invoke_signed(
         &create_metadata_ix,
         &[
@@ -260,11 +628,11 @@ pub fn handler(
             ctx.accounts.nft_mint.to_account_info(),
             ticket_account.to_account_info(),
             buyer.to_account_info(),
-            ticket_account.to_account_info(),
+            ctx.accounts.ticket_authority.to_account_info(),
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.rent.to_account_info(),
         ],
-        signer,
+        metadata_signers,
     )?;
 
     msg!("Metadata created");
@@ -273,7 +641,7 @@ pub fn handler(
     let create_master_edition_accounts = CreateMasterEditionV3 {
         edition: ctx.accounts.master_edition.key(),
         mint: ctx.accounts.nft_mint.key(),
-        update_authority: ticket_account.key(),
+        update_authority: ctx.accounts.ticket_authority.key(),
         mint_authority: ticket_account.key(),
         payer: buyer.key(),
         metadata: ctx.accounts.metadata.key(),
@@ -294,7 +662,7 @@ pub fn handler(
         &[
             ctx.accounts.master_edition.to_account_info(),
             ctx.accounts.nft_mint.to_account_info(),
-            ticket_account.to_account_info(),
+            ctx.accounts.ticket_authority.to_account_info(),
             ticket_account.to_account_info(),
             buyer.to_account_info(),
             ctx.accounts.metadata.to_account_info(),
@@ -302,9 +670,13 @@ pub fn handler(
             ctx.accounts.system_program.to_account_info(),
             ctx.accounts.rent.to_account_info(),
         ],
-        signer,
+        metadata_signers,
     )?;
 
+    if let Some(collection) = collection {
+        collection.verify(ctx.accounts.metadata.to_account_info())?;
+    }
+
     msg!(
         "✅ Ticket purchased! NFT minted to buyer: {}",
         buyer.key()
@@ -1,18 +1,27 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token::{self, Token, TokenAccount, Transfer},
+    token_interface::{self, CloseAccount, Mint, TokenInterface},
 };
 use anchor_lang::system_program;
 
-use crate::constants::{LISTING_SEED, PROGRAM_SEED};
+use crate::constants::{CONFIG_SEED, LISTING_SEED, MAX_BASIS_POINTS, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
 use crate::error::ErrorCode;
-use crate::state::{EventAccount, ListingAccount, TicketAccount};
+use crate::state::{EventAccount, EventStatus, ListingAccount, ProgramConfig, RoyaltyRecipient, SellerListings, TicketAccount};
+use crate::ticket_token::TicketTransfer;
 
 #[derive(Accounts)]
 pub struct BuyMarketplaceTicketCtx<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
     
     /// The ticket being purchased
     #[account(
@@ -30,97 +39,250 @@ pub struct BuyMarketplaceTicketCtx<'info> {
             ticket_account.key().as_ref()
         ],
         bump = listing_account.bump,
+        constraint = listing_account.ticket == ticket_account.key() @ ErrorCode::ListingMismatch,
+        constraint = listing_account.auction.is_none() @ ErrorCode::ListingIsAuction,
         close = buyer
     )]
     pub listing_account: Account<'info, ListingAccount>,
     
     /// Event account for validation
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
     pub event_account: Account<'info, EventAccount>,
+
+    /// Platform treasury, paid the marketplace fee on events without a royalty split
+    #[account(
+        mut,
+        address = program_config.treasury @ ErrorCode::TreasuryMismatch
+    )]
+    pub treasury: SystemAccount<'info>,
     
-    #[account(mut)]
+    /// Must be the seller recorded on the listing, otherwise the buyer could pay themselves
+    #[account(
+        mut,
+        address = listing_account.seller @ ErrorCode::SellerMismatch
+    )]
     pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            ticket_account.event.as_ref(),
+            seller.key().as_ref()
+        ],
+        bump = seller_listings.bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
     
-    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = ticket_token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     
     /// Escrow NFT token account
     #[account(
         mut,
         associated_token::mint = nft_mint,
-        associated_token::authority = listing_account
+        associated_token::authority = listing_account,
+        associated_token::token_program = ticket_token_program
     )]
-    pub escrow_nft_account: Account<'info, TokenAccount>,
+    pub escrow_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
     
     /// Buyer's NFT token account
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = nft_mint,
-        associated_token::authority = buyer
+        associated_token::authority = buyer,
+        associated_token::token_program = ticket_token_program
     )]
-    pub buyer_nft_account: Account<'info, TokenAccount>,
-    
-    // pub event_authority: Option<SystemAccount<'info>>,
+    pub buyer_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: Program PDA that can pause the transfer hook while the program moves the ticket
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
     
+    /// Used for SPL token payments
     pub token_program: Program<'info, Token>,
+    pub ticket_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// Buyer's token account, required when the listing is priced in a payment_mint
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = Some(buyer_payment_account.mint) == listing_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Seller's token account, required when the listing is priced in a payment_mint
+    #[account(
+        mut,
+        token::authority = seller,
+        constraint = Some(seller_payment_account.mint) == listing_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub seller_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Treasury's token account, required when the listing is priced in a payment_mint
+    #[account(
+        mut,
+        token::authority = treasury,
+        constraint = Some(treasury_payment_account.mint) == listing_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub treasury_payment_account: Option<Account<'info, TokenAccount>>,
 }
 
-pub fn handler(ctx: Context<BuyMarketplaceTicketCtx>) -> Result<()> {
-    let ticket_key = ctx.accounts.ticket_account.key();
-    let ticket = &mut ctx.accounts.ticket_account;
-    let listing = &ctx.accounts.listing_account;
-    
+impl<'info> BuyMarketplaceTicketCtx<'info> {
+    /// Pays `amount` from the buyer to `to`, a wallet for SOL listings or a payment_mint token account
+    fn pay(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        if self.listing_account.payment_mint.is_some() {
+            let buyer_payment_account = self
+                .buyer_payment_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingPaymentAccounts)?;
+            token::transfer(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: buyer_payment_account.to_account_info(),
+                        to,
+                        authority: self.buyer.to_account_info(),
+                    },
+                ),
+                amount,
+            )
+        } else {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.buyer.to_account_info(),
+                        to,
+                    },
+                ),
+                amount,
+            )
+        }
+    }
+}
+
+/// Checks a remaining account against the royalty recipient it is paying
+fn check_royalty_account(
+    recipient: &RoyaltyRecipient,
+    account: &AccountInfo,
+    payment_mint: Option<Pubkey>,
+) -> Result<()> {
+    require!(account.is_writable, ErrorCode::RoyaltyRecipientMismatch);
+    match payment_mint {
+        None => require_keys_eq!(account.key(), recipient.address, ErrorCode::RoyaltyRecipientMismatch),
+        Some(payment_mint) => {
+            require_keys_eq!(*account.owner, token::ID, ErrorCode::RoyaltyRecipientMismatch);
+            let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+            require!(
+                token_account.mint == payment_mint && token_account.owner == recipient.address,
+                ErrorCode::RoyaltyRecipientMismatch
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Royalties owed on a resale at `price`, paired with the accounts they are paid to.
+/// The organizer's royalty split is paid straight to its recipients, passed as
+/// `remaining_accounts`, otherwise the platform-wide marketplace fee goes to the treasury
+pub(crate) fn royalty_payments<'info>(
+    event_account: &EventAccount,
+    program_config: &ProgramConfig,
+    price: u64,
+    payment_mint: Option<Pubkey>,
+    treasury_payee: Option<AccountInfo<'info>>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Vec<(AccountInfo<'info>, u64)>> {
+    if event_account.royalty_recipients.is_empty() {
+        let fee_basis_points = program_config.marketplace_fee_bps as u128;
+        let fee_amount = (price as u128 * fee_basis_points / MAX_BASIS_POINTS as u128) as u64;
+        let treasury_payee = treasury_payee.ok_or(ErrorCode::MissingPaymentAccounts)?;
+        return Ok(vec![(treasury_payee, fee_amount)]);
+    }
     require!(
-        ctx.accounts.buyer.lamports() >= listing.price_lamports,
-        ErrorCode::InsufficientPayment
+        remaining_accounts.len() == event_account.royalty_recipients.len(),
+        ErrorCode::RoyaltyRecipientMismatch
     );
+    event_account
+        .royalty_recipients
+        .iter()
+        .zip(remaining_accounts)
+        .map(|(recipient, account)| {
+            check_royalty_account(recipient, account, payment_mint)?;
+            let amount = price as u128 * recipient.share_bps as u128 / MAX_BASIS_POINTS as u128;
+            Ok((account.clone(), amount as u64))
+        })
+        .collect()
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, BuyMarketplaceTicketCtx<'info>>) -> Result<()> {
+    let ticket_key = ctx.accounts.ticket_account.key();
+    let listing = &ctx.accounts.listing_account;
+    let event_account = &ctx.accounts.event_account;
     
     // Check if listing expired
+    let current_time = Clock::get()?.unix_timestamp;
     if let Some(expires_at) = listing.expires_at {
-        let current_time = Clock::get()?.unix_timestamp;
         require!(current_time <= expires_at, ErrorCode::ListingExpired);
     }
-    
-    // Calculate fees (5% to event authority if provided)
-    let fee_basis_points = 500; // 5%
-    let fee_amount = (listing.price_lamports * fee_basis_points) / 10000;
-    let seller_amount = listing.price_lamports - fee_amount;
-    
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: ctx.accounts.seller.to_account_info(),
-            },
-        ),
-        seller_amount,
+
+    // Seller and treasury are paid in SOL, or into their payment_mint token accounts
+    let (seller_payee, treasury_payee) = if listing.payment_mint.is_some() {
+        let (Some(buyer_payment_account), Some(seller_payment_account)) = (
+            &ctx.accounts.buyer_payment_account,
+            &ctx.accounts.seller_payment_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+        require!(
+            buyer_payment_account.amount >= listing.price_lamports,
+            ErrorCode::InsufficientPayment
+        );
+        let treasury_payee = ctx.accounts.treasury_payment_account.as_ref().map(|a| a.to_account_info());
+        (seller_payment_account.to_account_info(), treasury_payee)
+    } else {
+        require!(
+            ctx.accounts.buyer.lamports() >= listing.price_lamports,
+            ErrorCode::InsufficientPayment
+        );
+        (ctx.accounts.seller.to_account_info(), Some(ctx.accounts.treasury.to_account_info()))
+    };
+
+    let royalty_payments = royalty_payments(
+        event_account,
+        &ctx.accounts.program_config,
+        listing.price_lamports,
+        listing.payment_mint,
+        treasury_payee,
+        ctx.remaining_accounts,
     )?;
 
-   // Pay fee (if any)
-    if fee_amount > 0 {
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.buyer.to_account_info(),
-                    to: ctx.accounts.event_account.to_account_info(),
-                },
-            ),
-            fee_amount,
-        )?;
+    let royalty_amount: u64 = royalty_payments.iter().map(|(_, amount)| amount).sum();
+    let seller_amount = listing.price_lamports - royalty_amount;
+
+    ctx.accounts.pay(seller_payee, seller_amount)?;
+    for (recipient, amount) in royalty_payments {
+        ctx.accounts.pay(recipient, amount)?;
     }
     
     // Transfer NFT from escrow to buyer
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.escrow_nft_account.to_account_info(),
-        to: ctx.accounts.buyer_nft_account.to_account_info(),
-        authority: ctx.accounts.listing_account.to_account_info(), // Listing PDA is authority
-    };
-    
-    let cpi_program = ctx.accounts.token_program.to_account_info();
     let seeds = &[
         PROGRAM_SEED.as_bytes(),
         LISTING_SEED.as_bytes(),
@@ -129,12 +291,35 @@ pub fn handler(ctx: Context<BuyMarketplaceTicketCtx>) -> Result<()> {
     ];
     let signer_seeds = &[&seeds[..]];
 
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-    
-    token::transfer(cpi_ctx, 1)?;
+    TicketTransfer {
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
+        from: ctx.accounts.escrow_nft_account.to_account_info(),
+        to: ctx.accounts.buyer_nft_account.to_account_info(),
+        authority: ctx.accounts.listing_account.to_account_info(), // Listing PDA is authority
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+    }
+    .transfer(signer_seeds)?;
+
+    // The escrow only ever held this ticket, hand its rent back to the seller
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.ticket_token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow_nft_account.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.listing_account.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
     
+    let ticket = &mut ctx.accounts.ticket_account;
     ticket.owner = ctx.accounts.buyer.key();
     ticket.is_listed = false;
+    ticket.acquired_at = current_time;
+
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    seller_listings.active_listings = seller_listings.active_listings.saturating_sub(1);
     
     Ok(())
 }
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::compressed_ticket::{
+    self, CompressedTicket, TicketTree, ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use crate::constants::{LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, ListingAccount, SellerListings};
+
+#[derive(Accounts)]
+#[instruction(ticket: CompressedTicket)]
+pub struct CancelCompressedListingCtx<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub event_account: Account<'info, EventAccount>,
+
+    /// CHECK: The event's ticket tree, checked by Bubblegum
+    #[account(
+        mut,
+        constraint = event_account.merkle_tree == Some(merkle_tree.key()) @ ErrorCode::TicketTreeMismatch
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// The listing to cancel, it holds the leaf
+    #[account(
+        mut,
+        constraint = listing_account.seller == seller.key() @ ErrorCode::Unauthorized,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            LISTING_SEED.as_bytes(),
+            compressed_ticket::asset_id(&merkle_tree.key(), ticket.nonce).as_ref()
+        ],
+        bump = listing_account.bump,
+        close = seller
+    )]
+    pub listing_account: Account<'info, ListingAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seller.key().as_ref()
+        ],
+        bump = seller_listings.bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
+
+    /// CHECK: Bubblegum tree config PDA of merkle_tree
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that created the tree
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Noop program Bubblegum logs leaves to
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The tree's proof nodes for `ticket` are passed as remaining accounts
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CancelCompressedListingCtx<'info>>,
+    ticket: CompressedTicket,
+) -> Result<()> {
+    let asset_id = ticket.asset_id(&ctx.accounts.merkle_tree.key());
+    let seeds = &[
+        PROGRAM_SEED.as_bytes(),
+        LISTING_SEED.as_bytes(),
+        asset_id.as_ref(),
+        &[ctx.accounts.listing_account.bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    // Listing PDAs are the leaf's owner and delegate since the ticket was listed
+    let listing = ctx.accounts.listing_account.to_account_info();
+    TicketTree {
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        tree_config: ctx.accounts.tree_config.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .transfer(
+        &ticket,
+        listing.clone(),
+        listing,
+        ctx.accounts.seller.to_account_info(),
+        signer_seeds,
+        ctx.remaining_accounts,
+    )?;
+
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    seller_listings.active_listings = seller_listings.active_listings.saturating_sub(1);
+
+    Ok(())
+}
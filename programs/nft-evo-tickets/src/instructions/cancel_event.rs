@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, TicketMintMode};
+
+#[derive(Accounts)]
+pub struct CancelEventCtx<'info> {
+    pub authority: Signer<'info>,
+
+    /// Compressed tickets have no ticket account to claim a refund with
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = event_account.status == EventStatus::Active @ ErrorCode::EventNotActive,
+        constraint = event_account.ticket_mint_mode != TicketMintMode::Compressed @ ErrorCode::CompressedTicketsUnsupported
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(ctx: Context<CancelEventCtx>) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+
+    // Sales and resales stop here, holders claim their money back via claim_refund
+    event_account.status = EventStatus::Cancelled;
+
+    emit!(EventCancelled {
+        event_id: event_account.event_id,
+        authority: ctx.accounts.authority.key(),
+        event_account: event_account.key(),
+        tickets_sold: event_account.tickets_sold,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct EventCancelled {
+    pub event_id: u64,
+    pub authority: Pubkey,
+    pub event_account: Pubkey,
+    pub tickets_sold: u32,
+}
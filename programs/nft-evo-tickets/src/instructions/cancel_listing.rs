@@ -1,9 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
 
-use crate::constants::{LISTING_SEED, PROGRAM_SEED};
+use crate::constants::{BID_ESCROW_SEED, LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
 use crate::error::ErrorCode;
-use crate::state::{ListingAccount, TicketAccount};
+use crate::state::{BidEscrow, ListingAccount, SellerListings, TicketAccount};
+use crate::ticket_token::TicketTransfer;
 
 #[derive(Accounts)]
 pub struct CancelListingCtx<'info> {
@@ -28,43 +29,78 @@ pub struct CancelListingCtx<'info> {
             ticket_account.key().as_ref()
         ],
         bump = listing_account.bump,
+        constraint = listing_account.ticket == ticket_account.key() @ ErrorCode::ListingMismatch,
+        constraint = listing_account.auction.and_then(|auction| auction.highest_bidder).is_none() @ ErrorCode::AuctionHasBids,
         close = seller
     )]
     pub listing_account: Account<'info, ListingAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            ticket_account.event.as_ref(),
+            seller.key().as_ref()
+        ],
+        bump = seller_listings.bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
     
-    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     
     /// Escrow NFT token account (owned by listing PDA)
     #[account(
         mut,
         associated_token::mint = nft_mint,
         associated_token::authority = listing_account,
+        associated_token::token_program = token_program,
     )]
-    pub escrow_nft_account: Account<'info, TokenAccount>,
+    pub escrow_nft_account: InterfaceAccount<'info, TokenAccount>,
     
     /// Seller's NFT token account
     #[account(
         mut,
         associated_token::mint = nft_mint,
-        associated_token::authority = seller
+        associated_token::authority = seller,
+        associated_token::token_program = token_program
     )]
-    pub seller_nft_account: Account<'info, TokenAccount>,
+    pub seller_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Program PDA that can pause the transfer hook while the program moves the ticket
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
+
+    /// Bid escrow of an auction listing, closed along with it
+    #[account(
+        mut,
+        close = seller,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            BID_ESCROW_SEED.as_bytes(),
+            listing_account.key().as_ref()
+        ],
+        bump = bid_escrow.bump
+    )]
+    pub bid_escrow: Option<Account<'info, BidEscrow>>,
 }
 
 pub fn handler(ctx: Context<CancelListingCtx>) -> Result<()> {
+    require!(
+        ctx.accounts.listing_account.auction.is_some() == ctx.accounts.bid_escrow.is_some(),
+        ErrorCode::InvalidAuction
+    );
     let ticket_key = ctx.accounts.ticket_account.key();
     let ticket = &mut ctx.accounts.ticket_account;
     
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.escrow_nft_account.to_account_info(),
-        to: ctx.accounts.seller_nft_account.to_account_info(),
-        authority: ctx.accounts.listing_account.to_account_info(),
-    };
-    
-    let cpi_program = ctx.accounts.token_program.to_account_info();
     let seeds = &[
         PROGRAM_SEED.as_bytes(),
         LISTING_SEED.as_bytes(),
@@ -73,11 +109,32 @@ pub fn handler(ctx: Context<CancelListingCtx>) -> Result<()> {
     ];
     let signer_seeds = &[&seeds[..]];
 
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-    
-    token::transfer(cpi_ctx, 1)?;
+    TicketTransfer {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
+        from: ctx.accounts.escrow_nft_account.to_account_info(),
+        to: ctx.accounts.seller_nft_account.to_account_info(),
+        authority: ctx.accounts.listing_account.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+    }
+    .transfer(signer_seeds)?;
+
+    // The escrow only ever held this ticket, hand its rent back to the seller
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow_nft_account.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.listing_account.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
     
     ticket.is_listed = false;
+
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    seller_listings.active_listings = seller_listings.active_listings.saturating_sub(1);
     
     Ok(())
 }
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::OfferAccount;
+
+#[derive(Accounts)]
+pub struct CancelOfferCtx<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Closing it returns the escrowed lamports along with the rent
+    #[account(
+        mut,
+        has_one = buyer @ ErrorCode::Unauthorized,
+        close = buyer
+    )]
+    pub offer_account: Account<'info, OfferAccount>,
+}
+
+pub fn handler(ctx: Context<CancelOfferCtx>) -> Result<()> {
+    emit!(OfferCancelled {
+        offer_account: ctx.accounts.offer_account.key(),
+        buyer: ctx.accounts.buyer.key(),
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct OfferCancelled {
+    pub offer_account: Pubkey,
+    pub buyer: Pubkey,
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface;
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED, VAULT_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, EventVault, ProgramConfig, TicketAccount};
+
+#[derive(Accounts)]
+pub struct ClaimAuctionRebateCtx<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Still open once proceeds are withdrawn, which leave unclaimed rebates in the vault
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    /// Only tickets bought at the auction price are owed a rebate, and only once
+    #[account(mut)]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    /// Whoever holds the NFT gets the rebate, like a refund
+    #[account(
+        token::mint = ticket_account.nft_mint,
+        token::authority = holder,
+        constraint = holder_nft_account.amount == 1 @ ErrorCode::NotTicketHolder
+    )]
+    pub holder_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Used for SPL token rebates
+    pub token_program: Program<'info, Token>,
+
+    /// Vault's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Holder's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = holder,
+        constraint = Some(holder_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub holder_payment_account: Option<Account<'info, TokenAccount>>,
+}
+
+pub fn handler(ctx: Context<ClaimAuctionRebateCtx>) -> Result<()> {
+    let event_account = &ctx.accounts.event_account;
+    require!(
+        event_account.dutch_auction.is_some_and(|auction| auction.rebate),
+        ErrorCode::NoRebateDue
+    );
+    let clearing_price = event_account
+        .auction_clearing_price(Clock::get()?.unix_timestamp)
+        .ok_or(ErrorCode::AuctionNotFinal)?;
+
+    let ticket_account = &mut ctx.accounts.ticket_account;
+    require!(ticket_account.bought_at_auction, ErrorCode::NoRebateDue);
+    let rebate_amount = ticket_account.price_paid_lamports.saturating_sub(clearing_price);
+    require!(rebate_amount > 0, ErrorCode::NoRebateDue);
+
+    // The sale is settled, so withdrawals no longer hold back its rebate
+    let event_vault = &mut ctx.accounts.event_vault;
+    event_vault.unrebated_auction_sales = event_vault.unrebated_auction_sales.saturating_sub(1);
+    event_vault.unrebated_auction_proceeds = event_vault
+        .unrebated_auction_proceeds
+        .saturating_sub(ticket_account.price_paid_lamports);
+
+    // The ticket now cost the clearing price, for refunds and the resale cap alike
+    ticket_account.price_paid_lamports = clearing_price;
+    ticket_account.bought_at_auction = false;
+
+    if event_account.payment_mint.is_some() {
+        let (Some(vault_payment_account), Some(holder_payment_account)) = (
+            &ctx.accounts.vault_payment_account,
+            &ctx.accounts.holder_payment_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+
+        let event_key = event_account.key();
+        let seeds = &[
+            PROGRAM_SEED.as_bytes(),
+            VAULT_SEED.as_bytes(),
+            event_key.as_ref(),
+            &[ctx.accounts.event_vault.bump],
+        ];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: vault_payment_account.to_account_info(),
+                    to: holder_payment_account.to_account_info(),
+                    authority: ctx.accounts.event_vault.to_account_info(),
+                },
+                &[&seeds[..]],
+            ),
+            rebate_amount,
+        )?;
+    } else {
+        // The vault is program owned so we move lamports directly
+        ctx.accounts.event_vault.sub_lamports(rebate_amount)?;
+        ctx.accounts.holder.add_lamports(rebate_amount)?;
+    }
+
+    emit!(AuctionRebateClaimed {
+        event_account: event_account.key(),
+        ticket_account: ticket_account.key(),
+        holder: ctx.accounts.holder.key(),
+        clearing_price,
+        amount: rebate_amount,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AuctionRebateClaimed {
+    pub event_account: Pubkey,
+    pub ticket_account: Pubkey,
+    pub holder: Pubkey,
+    pub clearing_price: u64,
+    pub amount: u64,
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Burn, CloseAccount, Mint, TokenInterface};
+
+use crate::constants::{PROGRAM_SEED, VAULT_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, EventVault, SeatReservation, TicketAccount};
+
+#[derive(Accounts)]
+pub struct ClaimRefundCtx<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = event_account.status == EventStatus::Cancelled @ ErrorCode::EventNotCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    /// Closed on refund, so a ticket can only be refunded once
+    #[account(
+        mut,
+        close = holder,
+        constraint = ticket_account.event == event_account.key() @ ErrorCode::InvalidInput,
+        constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::InvalidInput,
+        mint::token_program = ticket_token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Whoever holds the NFT gets the refund, even if the ticket's stored owner is stale
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = holder,
+        token::token_program = ticket_token_program,
+        constraint = holder_nft_account.amount == 1 @ ErrorCode::NotTicketHolder
+    )]
+    pub holder_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Used for SPL token refunds
+    pub token_program: Program<'info, Token>,
+    pub ticket_token_program: Interface<'info, TokenInterface>,
+
+    /// Vault's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Holder's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = holder,
+        constraint = Some(holder_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub holder_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// The ticket's seat, required for seated tickets and released with the refund
+    #[account(
+        mut,
+        close = holder,
+        constraint = seat_reservation.ticket == ticket_account.key() @ ErrorCode::SeatReservationMismatch
+    )]
+    pub seat_reservation: Option<Account<'info, SeatReservation>>,
+}
+
+pub fn handler(ctx: Context<ClaimRefundCtx>) -> Result<()> {
+    let refund_amount = ctx.accounts.ticket_account.price_paid_lamports;
+    require!(
+        ctx.accounts.ticket_account.seat.is_some() == ctx.accounts.seat_reservation.is_some(),
+        ErrorCode::SeatReservationMismatch
+    );
+
+    // Burn the ticket NFT and give the ATA rent back to the holder
+    token_interface::burn(
+        CpiContext::new(
+            ctx.accounts.ticket_token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.nft_mint.to_account_info(),
+                from: ctx.accounts.holder_nft_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    token_interface::close_account(CpiContext::new(
+        ctx.accounts.ticket_token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.holder_nft_account.to_account_info(),
+            destination: ctx.accounts.holder.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        },
+    ))?;
+
+    // Pay back the face value out of the vault, in the currency the ticket was bought with
+    if ctx.accounts.event_account.payment_mint.is_some() {
+        let (Some(vault_payment_account), Some(holder_payment_account)) = (
+            &ctx.accounts.vault_payment_account,
+            &ctx.accounts.holder_payment_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+
+        if refund_amount > 0 {
+            let event_key = ctx.accounts.event_account.key();
+            let seeds = &[
+                PROGRAM_SEED.as_bytes(),
+                VAULT_SEED.as_bytes(),
+                event_key.as_ref(),
+                &[ctx.accounts.event_vault.bump],
+            ];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: vault_payment_account.to_account_info(),
+                        to: holder_payment_account.to_account_info(),
+                        authority: ctx.accounts.event_vault.to_account_info(),
+                    },
+                    &[&seeds[..]],
+                ),
+                refund_amount,
+            )?;
+        }
+    } else if refund_amount > 0 {
+        // The vault is program owned so we move lamports directly
+        ctx.accounts.event_vault.sub_lamports(refund_amount)?;
+        ctx.accounts.holder.add_lamports(refund_amount)?;
+    }
+
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.tickets_refunded = event_account
+        .tickets_refunded
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+
+    emit!(RefundClaimed {
+        event_account: event_account.key(),
+        ticket_account: ctx.accounts.ticket_account.key(),
+        holder: ctx.accounts.holder.key(),
+        amount: refund_amount,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RefundClaimed {
+    pub event_account: Pubkey,
+    pub ticket_account: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+}
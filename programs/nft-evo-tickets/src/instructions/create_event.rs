@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{associated_token::AssociatedToken, token::Token};
 
-use crate::constants::{EVENT_SEED, PROGRAM_SEED};
+use crate::constants::{COLLECTION_SEED, EVENT_SEED, PROGRAM_SEED, TICKET_AUTHORITY_SEED, VAULT_SEED};
 use crate::error::ErrorCode;
-use crate::state::EventAccount;
+use crate::state::{EventAccount, EventStatus, EventVault, TicketMintMode, TransferPolicy};
+use crate::ticket_token::EventCollection;
 
 #[derive(Accounts)]
 #[instruction(event_id: u64, name: String, start_ts: i64, end_ts: i64, ticket_supply: u32, cover_image_url: String)]
@@ -19,8 +21,51 @@ pub struct CreateEventCtx<'info> {
         bump
     )]
     pub event_account: Account<'info, EventAccount>,
+
+    /// PDA holding primary sale proceeds: [PROGRAM_SEED, VAULT_SEED, event_account]
+    #[account(
+        init,
+        payer = organizer,
+        space = 8 + EventVault::INIT_SPACE,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
     
     pub system_program: Program<'info, System>,
+
+    // The event collection NFT is created when these accounts are passed, see EventCollection
+
+    /// CHECK: Collection mint, created in the handler: [PROGRAM_SEED, COLLECTION_SEED, event_account]
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), COLLECTION_SEED.as_bytes(), event_account.key().as_ref()],
+        bump
+    )]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex metadata PDA of the collection mint, checked by Metaplex
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex master edition PDA of the collection mint, checked by Metaplex
+    #[account(mut)]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Ticket authority's ATA for the collection mint, checked by the ATA program
+    #[account(mut)]
+    pub collection_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA that holds the collection and is its update authority
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: Option<UncheckedAccount<'info>>,
 }
 
 pub fn handler(
@@ -58,7 +103,75 @@ pub fn handler(
     event_account.ticket_supply = ticket_supply;
     event_account.version = 2; // Version 2: Clean events with proper validation
     event_account.cover_image_url = cover_image_url;
+    event_account.tier_count = 0;
+    event_account.tiered_supply = 0;
+    event_account.is_paused = false;
+    event_account.status = EventStatus::Active;
+    event_account.tickets_refunded = 0;
+    event_account.payment_mint = None;
+    event_account.ticket_mint_mode = TicketMintMode::Legacy;
+    event_account.transfer_policy = TransferPolicy::Allowed;
+    event_account.max_transfers_per_ticket = None;
+    event_account.max_resale_markup_bps = None;
+    event_account.resale_cooldown_secs = None;
+    event_account.max_listings_per_wallet = None;
+    event_account.royalty_bps = 0;
+    event_account.royalty_recipients = Vec::new();
+    event_account.max_tickets_per_wallet = None;
+    event_account.collection_mint = ctx.accounts.collection_mint.as_ref().map(|mint| mint.key());
     event_account.bump = ctx.bumps.event_account;
+
+    let event_vault = &mut ctx.accounts.event_vault;
+    event_vault.total_withdrawn = 0;
+    event_vault.unrebated_auction_sales = 0;
+    event_vault.unrebated_auction_proceeds = 0;
+    event_vault.event = event_account_key;
+    event_vault.bump = ctx.bumps.event_vault;
+
+    if let Some(collection_mint) = &ctx.accounts.collection_mint {
+        let (
+            Some(collection_metadata),
+            Some(collection_master_edition),
+            Some(collection_token_account),
+            Some(ticket_authority),
+            Some(token_program),
+            Some(associated_token_program),
+            Some(token_metadata_program),
+        ) = (
+            &ctx.accounts.collection_metadata,
+            &ctx.accounts.collection_master_edition,
+            &ctx.accounts.collection_token_account,
+            &ctx.accounts.ticket_authority,
+            &ctx.accounts.token_program,
+            &ctx.accounts.associated_token_program,
+            &ctx.accounts.token_metadata_program,
+        ) else {
+            return err!(ErrorCode::MissingCollectionAccounts);
+        };
+
+        EventCollection {
+            payer: ctx.accounts.organizer.to_account_info(),
+            mint: collection_mint.to_account_info(),
+            metadata: collection_metadata.to_account_info(),
+            master_edition: collection_master_edition.to_account_info(),
+            ticket_authority: ticket_authority.to_account_info(),
+            ticket_authority_bump: ctx.bumps.ticket_authority.unwrap_or_default(),
+            token_program: token_program.to_account_info(),
+            token_metadata_program: token_metadata_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        }
+        .create(
+            &[
+                PROGRAM_SEED.as_bytes(),
+                COLLECTION_SEED.as_bytes(),
+                event_account_key.as_ref(),
+                &[ctx.bumps.collection_mint.unwrap_or_default()],
+            ],
+            collection_token_account.to_account_info(),
+            associated_token_program.to_account_info(),
+            name.clone(),
+        )?;
+    }
     
     // Emit event for indexing
     emit!(EventCreated {
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{PROGRAM_SEED, SECTION_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, SeatSection};
+
+#[derive(Accounts)]
+pub struct CreateSeatSectionCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// PDA for the section: [PROGRAM_SEED, SECTION_SEED, event, section_index]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SeatSection::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SECTION_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[event_account.section_count],
+        ],
+        bump
+    )]
+    pub seat_section: Account<'info, SeatSection>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateSeatSectionCtx>,
+    name: String,
+    rows: u8,
+    seats_per_row: u16,
+    price_lamports: u64,
+) -> Result<()> {
+    // The name starts every seat id, which has to fit in a PDA seed
+    require!(!name.is_empty() && name.len() <= 16, ErrorCode::InvalidInput);
+    require!(rows > 0 && seats_per_row > 0, ErrorCode::InvalidInput);
+
+    let event_account = &mut ctx.accounts.event_account;
+    let section_index = event_account.section_count;
+    event_account.section_count = section_index
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+
+    let seat_section = &mut ctx.accounts.seat_section;
+    seat_section.event = event_account.key();
+    seat_section.section_index = section_index;
+    seat_section.name = name.clone();
+    seat_section.rows = rows;
+    seat_section.seats_per_row = seats_per_row;
+    seat_section.price_lamports = price_lamports;
+    seat_section.bump = ctx.bumps.seat_section;
+
+    emit!(SeatSectionCreated {
+        event_account: event_account.key(),
+        seat_section: seat_section.key(),
+        section_index,
+        name,
+        rows,
+        seats_per_row,
+        price_lamports,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SeatSectionCreated {
+    pub event_account: Pubkey,
+    pub seat_section: Pubkey,
+    pub section_index: u8,
+    pub name: String,
+    pub rows: u8,
+    pub seats_per_row: u16,
+    pub price_lamports: u64,
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{PROGRAM_SEED, TIER_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, TicketTier};
+
+#[derive(Accounts)]
+#[instruction(name: String, price_lamports: u64, supply: u32)]
+pub struct CreateTicketTierCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// PDA for the tier: [PROGRAM_SEED, TIER_SEED, event, tier_index]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + TicketTier::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            TIER_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[event_account.tier_count],
+        ],
+        bump
+    )]
+    pub ticket_tier: Account<'info, TicketTier>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateTicketTierCtx>,
+    name: String,
+    price_lamports: u64,
+    supply: u32,
+) -> Result<()> {
+    require!(!name.is_empty() && name.len() <= 32, ErrorCode::InvalidInput);
+    require!(supply > 0, ErrorCode::InvalidInput);
+
+    let event_account = &mut ctx.accounts.event_account;
+
+    // Tiers carve up the event supply, they never extend it
+    let tiered_supply = event_account
+        .tiered_supply
+        .checked_add(supply)
+        .ok_or(ErrorCode::InvalidInput)?;
+    require!(
+        tiered_supply <= event_account.ticket_supply,
+        ErrorCode::TierSupplyExceeded
+    );
+
+    let tier_index = event_account.tier_count;
+    event_account.tier_count = tier_index
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+    event_account.tiered_supply = tiered_supply;
+
+    let ticket_tier = &mut ctx.accounts.ticket_tier;
+    ticket_tier.event = event_account.key();
+    ticket_tier.tier_index = tier_index;
+    ticket_tier.name = name.clone();
+    ticket_tier.price_lamports = price_lamports;
+    ticket_tier.supply = supply;
+    ticket_tier.sold = 0;
+    ticket_tier.bump = ctx.bumps.ticket_tier;
+
+    emit!(TicketTierCreated {
+        event_account: event_account.key(),
+        ticket_tier: ticket_tier.key(),
+        tier_index,
+        name,
+        price_lamports,
+        supply,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TicketTierCreated {
+    pub event_account: Pubkey,
+    pub ticket_tier: Pubkey,
+    pub tier_index: u8,
+    pub name: String,
+    pub price_lamports: u64,
+    pub supply: u32,
+}
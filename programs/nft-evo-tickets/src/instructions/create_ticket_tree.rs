@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+use crate::compressed_ticket::{
+    TicketTree, ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use crate::constants::{PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, TicketMintMode};
+
+#[derive(Accounts)]
+pub struct CreateTicketTreeCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Leaf indexes follow ticket serials, so the tree has to exist before the first sale
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = event_account.tickets_sold == 0 @ ErrorCode::TicketsAlreadySold,
+        constraint = event_account.collection_mint.is_none() @ ErrorCode::CollectionRequiresLegacyMints
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// CHECK: Allocated by the organizer for max_depth and max_buffer_size, owned by the compression program
+    #[account(mut, owner = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config PDA of merkle_tree, created by Bubblegum
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that creates the tree, the only one allowed to mint into it
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Noop program Bubblegum logs leaves to
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateTicketTreeCtx>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    require!(
+        max_depth < 32 && 1u64 << max_depth >= event_account.ticket_supply as u64,
+        ErrorCode::TicketTreeTooSmall
+    );
+
+    TicketTree {
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        tree_config: ctx.accounts.tree_config.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .create(ctx.accounts.authority.to_account_info(), max_depth, max_buffer_size)?;
+
+    event_account.merkle_tree = Some(ctx.accounts.merkle_tree.key());
+    event_account.ticket_mint_mode = TicketMintMode::Compressed;
+    event_account.check_compressed_features()?;
+
+    emit!(TicketTreeCreated {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        merkle_tree: ctx.accounts.merkle_tree.key(),
+        max_depth,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TicketTreeCreated {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+}
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount};
 
-use crate::constants::{EVENT_SEED, PROGRAM_SEED};
+use crate::constants::{EVENT_SEED, PROGRAM_SEED, VAULT_SEED};
 use crate::error::ErrorCode;
-use crate::state::EventAccount;
+use crate::state::{EventAccount, EventStatus, EventVault};
 
 #[derive(Accounts)]
 #[instruction(event_id: u64)]
@@ -19,6 +20,25 @@ pub struct DeleteEventCtx<'info> {
         constraint = event_account.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    /// Required when the event has a payment_mint
+    pub token_program: Option<Program<'info, Token>>,
+
+    /// Vault's token account, closed along with the vault when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
 }
 
 pub fn handler(
@@ -27,6 +47,45 @@ pub fn handler(
 ) -> Result<()> {
     let event_account = &ctx.accounts.event_account;
 
+    // Never orphan paid tickets: every holder must have been refunded, or the event settled
+    let all_refunded = event_account.status == EventStatus::Cancelled
+        && event_account.tickets_refunded == event_account.tickets_sold;
+    require!(
+        event_account.tickets_sold == 0
+            || all_refunded
+            || event_account.status == EventStatus::Settled,
+        ErrorCode::TicketsNotRefunded
+    );
+
+    // Don't leave the vault's token account behind, its tokens must be refunded or withdrawn first
+    if event_account.payment_mint.is_some() {
+        let (Some(token_program), Some(vault_payment_account)) = (
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault_payment_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+        require!(vault_payment_account.amount == 0, ErrorCode::VaultNotEmpty);
+
+        let event_key = event_account.key();
+        let seeds = &[
+            PROGRAM_SEED.as_bytes(),
+            VAULT_SEED.as_bytes(),
+            event_key.as_ref(),
+            &[ctx.accounts.event_vault.bump],
+        ];
+
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: vault_payment_account.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.event_vault.to_account_info(),
+            },
+            &[&seeds[..]],
+        ))?;
+    }
+
     // Emit event for indexing
     emit!(EventDeleted {
         event_id: event_account.event_id,
@@ -34,8 +93,7 @@ pub fn handler(
         event_account: event_account.key(),
     });
 
-    // The account will be automatically closed due to the `close = authority` constraint
-    // This will delete the event regardless of tickets sold
+    // The event and vault accounts themselves are closed by the `close = authority` constraints
     Ok(())
 }
 
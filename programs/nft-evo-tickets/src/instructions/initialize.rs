@@ -1,10 +1,56 @@
 use anchor_lang::prelude::*;
 
+use crate::constants::{CONFIG_SEED, MAX_BASIS_POINTS, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::program::NftEvoTickets;
+use crate::state::ProgramConfig;
+
 #[derive(Accounts)]
-pub struct InitializeCtx {
+pub struct InitializeCtx<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// Singleton PDA for the program config: [PROGRAM_SEED, CONFIG_SEED]
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Only the upgrade authority may create the config, so it can't be front-run
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, NftEvoTickets>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<InitializeCtx>) -> Result<()> {
-    msg!("Greetings from: {:?}", ctx.program_id);
+pub fn handler(
+    ctx: Context<InitializeCtx>,
+    treasury: Pubkey,
+    marketplace_fee_bps: u16,
+    max_royalty_bps: u16,
+    dispute_window_secs: i64,
+) -> Result<()> {
+    require!(marketplace_fee_bps <= MAX_BASIS_POINTS, ErrorCode::InvalidBasisPoints);
+    require!(max_royalty_bps <= MAX_BASIS_POINTS, ErrorCode::InvalidBasisPoints);
+    require!(dispute_window_secs >= 0, ErrorCode::InvalidInput);
+
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.admin = ctx.accounts.admin.key();
+    program_config.pending_admin = None;
+    program_config.treasury = treasury;
+    program_config.marketplace_fee_bps = marketplace_fee_bps;
+    program_config.max_royalty_bps = max_royalty_bps;
+    program_config.dispute_window_secs = dispute_window_secs;
+    program_config.is_paused = false;
+    program_config.bump = ctx.bumps.program_config;
+
+    msg!("Program config initialized by: {}", program_config.admin);
     Ok(())
 }
@@ -1,8 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::constants::{EVENT_SEED, PROGRAM_SEED};
+use crate::constants::{EVENT_SEED, PROGRAM_SEED, VAULT_SEED};
 use crate::error::ErrorCode;
-use crate::state::EventAccount;
+use crate::state::{EventAccount, EventStatus, EventVault, TicketMintMode, TransferPolicy};
 
 #[derive(Accounts)]
 #[instruction(event_id: u64, name: String, start_ts: i64, end_ts: i64)]
@@ -18,6 +18,16 @@ pub struct InitializeEventCtx<'info> {
         bump
     )]
     pub event_account: Account<'info, EventAccount>,
+
+    /// PDA holding primary sale proceeds: [PROGRAM_SEED, VAULT_SEED, event_account]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + EventVault::INIT_SPACE,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
     pub system_program: Program<'info, System>,
 }
 
@@ -41,7 +51,29 @@ pub fn handler(
     event_account.ticket_supply = 0;
     event_account.version = 0; // Legacy version without ticket_supply
     event_account.cover_image_url = String::new(); // No cover image for legacy events
+    event_account.tier_count = 0;
+    event_account.tiered_supply = 0;
+    event_account.is_paused = false;
+    event_account.status = EventStatus::Active;
+    event_account.tickets_refunded = 0;
+    event_account.payment_mint = None;
+    event_account.ticket_mint_mode = TicketMintMode::Legacy;
+    event_account.transfer_policy = TransferPolicy::Allowed;
+    event_account.max_transfers_per_ticket = None;
+    event_account.max_resale_markup_bps = None;
+    event_account.resale_cooldown_secs = None;
+    event_account.max_listings_per_wallet = None;
+    event_account.royalty_bps = 0;
+    event_account.royalty_recipients = Vec::new();
+    event_account.max_tickets_per_wallet = None;
     event_account.bump = ctx.bumps.event_account;
+
+    let event_vault = &mut ctx.accounts.event_vault;
+    event_vault.total_withdrawn = 0;
+    event_vault.unrebated_auction_sales = 0;
+    event_vault.unrebated_auction_proceeds = 0;
+    event_vault.event = event_account.key();
+    event_vault.bump = ctx.bumps.event_vault;
     Ok(())
 }
 
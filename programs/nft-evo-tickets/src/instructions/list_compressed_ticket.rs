@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+
+use crate::compressed_ticket::{
+    self, CompressedTicket, TicketTree, ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use crate::constants::{CONFIG_SEED, LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, ListingAccount, ProgramConfig, SellerListings, TicketStage};
+
+/// Fixed price listing of a compressed ticket. The leaf moves to the listing
+/// PDA, listings are keyed by the ticket's asset id
+#[derive(Accounts)]
+#[instruction(price_lamports: u64, expires_at: Option<i64>, ticket: CompressedTicket)]
+pub struct ListCompressedTicketCtx<'info> {
+    /// Must own the leaf, Bubblegum checks the signature
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// CHECK: The event's ticket tree, checked by Bubblegum
+    #[account(
+        mut,
+        constraint = event_account.merkle_tree == Some(merkle_tree.key()) @ ErrorCode::TicketTreeMismatch
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + ListingAccount::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            LISTING_SEED.as_bytes(),
+            compressed_ticket::asset_id(&merkle_tree.key(), ticket.nonce).as_ref()
+        ],
+        bump
+    )]
+    pub listing_account: Account<'info, ListingAccount>,
+
+    /// Seller's open listings for this event, checked against max_listings_per_wallet
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + SellerListings::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seller.key().as_ref()
+        ],
+        bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
+
+    /// CHECK: Bubblegum tree config PDA of merkle_tree
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: The ticket's delegate, checked against the leaf by Bubblegum
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that created the tree
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Noop program Bubblegum logs leaves to
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// The tree's proof nodes for `ticket` are passed as remaining accounts
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ListCompressedTicketCtx<'info>>,
+    price_lamports: u64,
+    expires_at: Option<i64>,
+    ticket: CompressedTicket,
+) -> Result<()> {
+    let seller_key = ctx.accounts.seller.key();
+    let current_time = Clock::get()?.unix_timestamp;
+
+    if let Some(expires) = expires_at {
+        require!(expires > current_time, ErrorCode::InvalidInput);
+    }
+    let stage = ticket.stage()?;
+    require!(
+        stage == TicketStage::Qr || stage == TicketStage::Collectible,
+        ErrorCode::CannotListInCurrentStage
+    );
+
+    let event_account = &ctx.accounts.event_account;
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    event_account.check_compressed_resale_allowed(seller_listings.active_listings)?;
+
+    seller_listings.event = event_account.key();
+    seller_listings.seller = seller_key;
+    seller_listings.active_listings = seller_listings
+        .active_listings
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+    seller_listings.bump = ctx.bumps.seller_listings;
+
+    // The listing PDA holds the leaf until it is sold or the listing is cancelled
+    TicketTree {
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        tree_config: ctx.accounts.tree_config.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .transfer(
+        &ticket,
+        ctx.accounts.seller.to_account_info(),
+        ctx.accounts.leaf_delegate.to_account_info(),
+        ctx.accounts.listing_account.to_account_info(),
+        &[],
+        ctx.remaining_accounts,
+    )?;
+
+    let listing = &mut ctx.accounts.listing_account;
+    listing.ticket = ticket.asset_id(&ctx.accounts.merkle_tree.key());
+    listing.seller = seller_key;
+    listing.price_lamports = price_lamports;
+    listing.payment_mint = event_account.payment_mint;
+    listing.created_at = current_time;
+    listing.expires_at = expires_at;
+    listing.auction = None;
+    listing.bump = ctx.bumps.listing_account;
+
+    Ok(())
+}
@@ -1,29 +1,41 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount, Transfer},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::constants::{LISTING_SEED, PROGRAM_SEED};
+use crate::constants::{BID_ESCROW_SEED, CONFIG_SEED, LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
 use crate::error::ErrorCode;
-use crate::state::{EventAccount, ListingAccount, TicketAccount, TicketStage};
+use crate::state::{AuctionTerms, BidEscrow, EventAccount, EventStatus, ListingAccount, ListingAuction, ProgramConfig, SellerListings, TicketAccount, TicketStage};
+use crate::ticket_token::TicketTransfer;
 
 #[derive(Accounts)]
 #[instruction(price_lamports: u64, expires_at: Option<i64>)]
 pub struct ListTicketCtx<'info> {
     #[account(mut)]
     pub seller: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
     
-    /// The ticket being listed
+    /// The ticket being listed, ownership is proven by seller_nft_account rather than the stored owner
     #[account(
         mut,
-        constraint = ticket_account.owner == seller.key() @ ErrorCode::Unauthorized,
         constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed,
         constraint = ticket_account.stage == TicketStage::Qr || ticket_account.stage == TicketStage::Collectible @ ErrorCode::CannotListInCurrentStage
     )]
     pub ticket_account: Account<'info, TicketAccount>,
     
     /// Event account for validation
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
     pub event_account: Account<'info, EventAccount>,
     
     /// PDA for the listing
@@ -39,37 +51,79 @@ pub struct ListTicketCtx<'info> {
         bump
     )]
     pub listing_account: Account<'info, ListingAccount>,
+
+    /// Seller's open listings for this event, checked against max_listings_per_wallet
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + SellerListings::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            ticket_account.event.as_ref(),
+            seller.key().as_ref()
+        ],
+        bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
     
-    pub nft_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
     
-    /// Seller's NFT token account
+    /// Seller's NFT token account, must actually hold the ticket
     #[account(
         mut,
-        associated_token::mint = nft_mint,
-        associated_token::authority = seller
+        token::mint = nft_mint,
+        token::authority = seller,
+        token::token_program = token_program,
+        constraint = seller_nft_account.amount == 1 @ ErrorCode::NotTicketHolder
     )]
-    pub seller_nft_account: Account<'info, TokenAccount>,
+    pub seller_nft_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         init_if_needed,
         payer = seller,
         associated_token::mint = nft_mint,
         associated_token::authority = listing_account,
+        associated_token::token_program = token_program,
         // escrow account's authority is the listing PDA
         // This ensures the listing PDA can control the NFT transfer during a sale
     )]
-    pub escrow_nft_account: Account<'info, TokenAccount>,
+    pub escrow_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Program PDA that can pause the transfer hook while the program moves the ticket
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+
+    /// Holds the highest bid, required for auction listings
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + BidEscrow::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            BID_ESCROW_SEED.as_bytes(),
+            listing_account.key().as_ref()
+        ],
+        bump
+    )]
+    pub bid_escrow: Option<Account<'info, BidEscrow>>,
 }
 
 pub fn handler(
     ctx: Context<ListTicketCtx>,
     price_lamports: u64,
     expires_at: Option<i64>,
+    auction: Option<AuctionTerms>,
 ) -> Result<()> {
     let ticket_key = ctx.accounts.ticket_account.key();
     let seller_key = ctx.accounts.seller.key();
@@ -78,17 +132,47 @@ pub fn handler(
     if let Some(expires) = expires_at {
         require!(expires > current_time, ErrorCode::InvalidInput);
     }
+
+    // Auctions run until end_ts instead of expiring, and bids are escrowed in lamports
+    if let Some(terms) = &auction {
+        require!(
+            ctx.accounts.bid_escrow.is_some()
+                && ctx.accounts.event_account.payment_mint.is_none()
+                && expires_at.is_none()
+                && terms.end_ts > current_time
+                && terms.min_increment_lamports > 0,
+            ErrorCode::InvalidAuction
+        );
+    } else {
+        require!(ctx.accounts.bid_escrow.is_none(), ErrorCode::InvalidAuction);
+    }
+
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    ctx.accounts.event_account.check_resale_allowed(
+        &ctx.accounts.ticket_account,
+        price_lamports,
+        seller_listings.active_listings,
+        current_time,
+    )?;
+
+    seller_listings.event = ctx.accounts.ticket_account.event;
+    seller_listings.seller = seller_key;
+    seller_listings.active_listings = seller_listings
+        .active_listings
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+    seller_listings.bump = ctx.bumps.seller_listings;
     
-    let cpi_accounts = Transfer {
+    TicketTransfer {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
         from: ctx.accounts.seller_nft_account.to_account_info(),
         to: ctx.accounts.escrow_nft_account.to_account_info(),
         authority: ctx.accounts.seller.to_account_info(),
-    };
-    
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    
-    token::transfer(cpi_ctx, 1)?;
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+    }
+    .transfer(&[])?;
     
     let ticket = &mut ctx.accounts.ticket_account;
     let listing = &mut ctx.accounts.listing_account;
@@ -96,10 +180,22 @@ pub fn handler(
     listing.ticket = ticket_key;
     listing.seller = seller_key;
     listing.price_lamports = price_lamports;
+    listing.payment_mint = ctx.accounts.event_account.payment_mint;
     listing.created_at = current_time;
     listing.expires_at = expires_at;
+    listing.auction = auction.map(|terms| ListingAuction {
+        terms,
+        highest_bidder: None,
+        highest_bid_lamports: 0,
+    });
     listing.bump = ctx.bumps.listing_account;
+
+    if let Some(bid_escrow) = &mut ctx.accounts.bid_escrow {
+        bid_escrow.listing = listing.key();
+        bid_escrow.bump = ctx.bumps.bid_escrow.ok_or(ErrorCode::InvalidAuction)?;
+    }
     
+    ticket.owner = seller_key;
     ticket.is_listed = true;
     
     Ok(())
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::constants::{CONFIG_SEED, OFFER_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, OfferAccount, ProgramConfig, TicketAccount};
+
+#[derive(Accounts)]
+#[instruction(ticket: Option<Pubkey>)]
+pub struct MakeOfferCtx<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// The ticket wanted, required for ticket offers
+    #[account(constraint = ticket_account.event == event_account.key() @ ErrorCode::EventMismatch)]
+    pub ticket_account: Option<Account<'info, TicketAccount>>,
+
+    /// Escrows the offered lamports until the offer is accepted or cancelled
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + OfferAccount::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            OFFER_SEED.as_bytes(),
+            ticket.unwrap_or(event_account.key()).as_ref(),
+            buyer.key().as_ref()
+        ],
+        bump
+    )]
+    pub offer_account: Account<'info, OfferAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<MakeOfferCtx>,
+    ticket: Option<Pubkey>,
+    price_lamports: u64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    let event_account = &ctx.accounts.event_account;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        event_account.payment_mint.is_none()
+            && price_lamports > 0
+            && expires_at.is_none_or(|expires| expires > current_time),
+        ErrorCode::InvalidOffer
+    );
+    require!(
+        ticket == ctx.accounts.ticket_account.as_ref().map(|t| t.key()),
+        ErrorCode::OfferMismatch
+    );
+    // Event-wide offers are checked against the cap once a ticket is picked
+    if let Some(ticket_account) = &ctx.accounts.ticket_account {
+        event_account.check_resale_price(ticket_account, price_lamports)?;
+    }
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.buyer.to_account_info(),
+                to: ctx.accounts.offer_account.to_account_info(),
+            },
+        ),
+        price_lamports,
+    )?;
+
+    let offer = &mut ctx.accounts.offer_account;
+    offer.buyer = ctx.accounts.buyer.key();
+    offer.event = event_account.key();
+    offer.ticket = ticket;
+    offer.price_lamports = price_lamports;
+    offer.created_at = current_time;
+    offer.expires_at = expires_at;
+    offer.bump = ctx.bumps.offer_account;
+
+    emit!(OfferMade {
+        offer_account: offer.key(),
+        buyer: offer.buyer,
+        event_account: offer.event,
+        ticket,
+        price_lamports,
+        expires_at,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct OfferMade {
+    pub offer_account: Pubkey,
+    pub buyer: Pubkey,
+    pub event_account: Pubkey,
+    pub ticket: Option<Pubkey>, // None: any ticket of the event
+    pub price_lamports: u64,
+    pub expires_at: Option<i64>,
+}
@@ -2,20 +2,23 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::program::invoke_signed;
 use anchor_lang::solana_program::sysvar::clock::Clock;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{self, Mint, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::{self, Token, TokenAccount, Transfer},
+    token_interface::{self, TokenInterface},
 };
 use mpl_token_metadata::{
     instructions::{
         CreateMasterEditionV3, CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3,
         CreateMetadataAccountV3InstructionArgs,
     },
-    types::{Creator, DataV2},
+    types::{Collection, Creator, DataV2},
 };
 
 use crate::{
-    constants::{PROGRAM_SEED, TICKET_SEED},
-    state::{EventAccount, TicketAccount, TicketStage},
+    constants::{CONFIG_SEED, METADATA_CONFIG_SEED, NFT_MINT_SEED, PROGRAM_SEED, SEAT_SEED, TICKET_AUTHORITY_SEED, TICKET_SEED, VAULT_SEED},
+    error::ErrorCode,
+    state::{EventAccount, EventMetadataConfig, EventStatus, EventVault, ProgramConfig, SeatReservation, SeatSection, TicketAccount, TicketMintMode, TicketStage},
+    ticket_token::{EventCollection, TicketMint},
 };
 
 #[derive(Accounts)]
@@ -24,9 +27,30 @@ pub struct MintTicketCtx<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    #[account(has_one = authority)]
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status == EventStatus::Active @ ErrorCode::EventNotActive,
+        constraint = event_account.ticket_mint_mode != TicketMintMode::Compressed @ ErrorCode::TicketsAreCompressed
+    )]
     pub event_account: Account<'info, EventAccount>,
 
+    /// Vault a priced ticket is paid into, so it is refunded like a sale
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
     #[account(
         init,
         payer = authority,
@@ -35,7 +59,7 @@ pub struct MintTicketCtx<'info> {
             PROGRAM_SEED.as_bytes(),
             TICKET_SEED.as_bytes(),
             event_account.key().as_ref(),
-            owner.key().as_ref()
+            &event_account.next_ticket_serial().to_le_bytes()
         ],
         bump
     )]
@@ -44,24 +68,22 @@ pub struct MintTicketCtx<'info> {
     /// CHECK: This is not checked in the handler - future ticket owner
     pub owner: UncheckedAccount<'info>,
 
-    /// The mint account for the NFT ticket
+    /// CHECK: The mint account for the NFT ticket, created in the handler with the event's ticket_mint_mode
     #[account(
-        init,
-        payer = authority,
+        mut,
         seeds = [
             PROGRAM_SEED.as_bytes(),
-            b"nft-mint",
+            NFT_MINT_SEED.as_bytes(),
             event_account.key().as_ref(),
-            owner.key().as_ref(),
+            &event_account.next_ticket_serial().to_le_bytes(),
         ],
-        bump,
-        mint::decimals = 0,
-        mint::authority = ticket_account.key(),
-        mint::freeze_authority = ticket_account.key(),
-        mint::token_program = token_program,
+        bump
     )]
-    pub nft_mint: Account<'info, Mint>,
-    
+    pub nft_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA holding the transfer hook and Token-2022 metadata authority
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
 
     /// CHECK: Metaplex Metadata PDA for the mint
     #[account(mut)]
@@ -71,16 +93,23 @@ pub struct MintTicketCtx<'info> {
     #[account(mut)]
     pub master_edition: UncheckedAccount<'info>,
 
-    /// CHECK: Owner's ATA for nft_mint
+    /// CHECK: Owner's ATA for nft_mint, created in the handler
     #[account(
-        init_if_needed,
-        payer = authority,
-        associated_token::mint = nft_mint,
-        associated_token::authority = owner
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &owner.key(),
+            &nft_mint.key(),
+            &ticket_token_program.key()
+        )
     )]
-    pub token_account: Account<'info, TokenAccount>,
+    pub token_account: UncheckedAccount<'info>,
 
     pub token_program: Program<'info, Token>,
+
+    #[account(
+        constraint = ticket_token_program.key() == event_account.ticket_mint_mode.token_program_id() @ ErrorCode::TicketTokenProgramMismatch
+    )]
+    pub ticket_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 
@@ -89,21 +118,137 @@ pub struct MintTicketCtx<'info> {
     pub token_metadata_program: UncheckedAccount<'info>,
 
     pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Transfer hook ExtraAccountMetaList PDA, required for TransferHook events
+    #[account(mut)]
+    pub extra_account_meta_list: Option<UncheckedAccount<'info>>,
+
+    /// Section the seat belongs to, required once the event has sections
+    #[account(constraint = seat_section.event == event_account.key() @ ErrorCode::EventMismatch)]
+    pub seat_section: Option<Account<'info, SeatSection>>,
+
+    /// Claims the seat, required when minting one. Creation fails if the seat is taken
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SeatReservation::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SEAT_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seat.as_deref().unwrap_or_default().as_bytes(),
+        ],
+        bump
+    )]
+    pub seat_reservation: Option<Account<'info, SeatReservation>>,
+
+    /// Metadata URI templates, required once the event has them
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = metadata_config.bump
+    )]
+    pub metadata_config: Option<Account<'info, EventMetadataConfig>>,
+
+    /// CHECK: Mint of the event's collection, required once the event has one
+    #[account(
+        constraint = event_account.collection_mint == Some(collection_mint.key()) @ ErrorCode::CollectionMismatch
+    )]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex metadata PDA of the collection mint, checked by Metaplex
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex master edition PDA of the collection mint, checked by Metaplex
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// Authority's token account, required for a priced ticket when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = authority,
+        constraint = Some(authority_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub authority_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Vault's token account, required for a priced ticket when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
 }
 
 
 pub fn handler(
     ctx: Context<MintTicketCtx>,
     seat: Option<String>,
-    metadata_uri_override: Option<String>,
+    price_lamports: u64,
 ) -> Result<()> {
-    let _event = &ctx.accounts.event_account;
+    // Organizer-minted tickets count against supply so refund accounting stays exact
+    let event_account = &mut ctx.accounts.event_account;
+    require!(
+        event_account.tickets_sold < event_account.ticket_supply,
+        ErrorCode::InvalidInput
+    );
+    event_account.check_seat(
+        seat.as_ref(),
+        ctx.accounts.seat_section.as_deref(),
+        ctx.accounts.seat_reservation.is_some(),
+    )?;
+    event_account.tickets_sold = event_account
+        .tickets_sold
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+
+    // A priced ticket is paid for like a sale, so its refund and resale cap are backed by the vault
+    if price_lamports > 0 {
+        if ctx.accounts.event_account.payment_mint.is_some() {
+            let (Some(authority_payment_account), Some(vault_payment_account)) = (
+                &ctx.accounts.authority_payment_account,
+                &ctx.accounts.vault_payment_account,
+            ) else {
+                return err!(ErrorCode::MissingPaymentAccounts);
+            };
+
+            token::transfer(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: authority_payment_account.to_account_info(),
+                        to: vault_payment_account.to_account_info(),
+                        authority: ctx.accounts.authority.to_account_info(),
+                    },
+                ),
+                price_lamports,
+            )?;
+        } else {
+            let transfer_ix = anchor_lang::solana_program::system_instruction::transfer(
+                ctx.accounts.authority.key,
+                &ctx.accounts.event_vault.key(),
+                price_lamports,
+            );
+
+            anchor_lang::solana_program::program::invoke(
+                &transfer_ix,
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    ctx.accounts.event_vault.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+    }
+
+    let event = &ctx.accounts.event_account;
     let nft_mint = &ctx.accounts.nft_mint;
     let authority = &ctx.accounts.authority;
   
-    let event_key = _event.key();
+    let event_key = event.key();
     let owner_key = ctx.accounts.owner.key();
     let ticket_bump = ctx.bumps.ticket_account;
+    let serial = event.tickets_sold; // Equals next_ticket_serial() at validation time
+    let serial_bytes = serial.to_le_bytes();
 
     // Define signer_seeds here so it's in scope for Metaplex CPIs
     let signer_seeds: &[&[&[u8]]] = &[
@@ -111,28 +256,14 @@ pub fn handler(
             PROGRAM_SEED.as_bytes(),
             TICKET_SEED.as_bytes(),
             event_key.as_ref(),
-            owner_key.as_ref(),
+            &serial_bytes,
             &[ticket_bump],
         ]
     ];
 
-    // 1) Mint 1 to ATA
-    token::mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::MintTo {
-                mint: ctx.accounts.nft_mint.to_account_info(),
-                to: ctx.accounts.token_account.to_account_info(),
-                authority: ctx.accounts.ticket_account.to_account_info(),
-            },
-            signer_seeds,
-        ),
-        1,
-    )?;
-
     // Check if event has started to determine initial ticket stage
     let current_time = Clock::get()?.unix_timestamp;
-    let event_has_started = current_time >= _event.start_ts;
+    let event_has_started = current_time >= event.start_ts;
     
     let ticket = &mut ctx.accounts.ticket_account;
     ticket.event = event_key;
@@ -147,49 +278,138 @@ pub fn handler(
         msg!("Event has not started, ticket minted in Prestige stage");
     }
     
-    ticket.seat = seat.map(|mut s| { s.truncate(32); s });
+    ticket.seat = seat;
     ticket.nft_mint = ctx.accounts.nft_mint.key();
+    ticket.tier_index = None;
+    ticket.price_paid_lamports = price_lamports;
+    ticket.bought_at_auction = false;
     ticket.is_listed = false;
+    ticket.serial = serial;
+    ticket.transfer_count = 0;
+    ticket.acquired_at = current_time;
     ticket.bump = ticket_bump;
 
-    // Create NFT metadata
-    let creators = vec![Creator {
-        address: ctx.accounts.authority.key(),
-        verified: true,
-        share: 100,
-    }];
-
-    // Helper to clamp string bytes
-    fn clamp_bytes(mut s: String, max: usize) -> String {
-        if s.len() > max {
-            let mut cut = max;
-            while !s.is_char_boundary(cut) { cut -= 1; }
-            s.truncate(cut);
-        }
-        s
+    if let (Some(seat_reservation), Some(bump)) =
+        (&mut ctx.accounts.seat_reservation, ctx.bumps.seat_reservation)
+    {
+        seat_reservation.event = event_key;
+        seat_reservation.ticket = ticket.key();
+        seat_reservation.bump = bump;
     }
 
-    let mut metadata_uri = if let Some(uri) = metadata_uri_override {
-        uri
-    } else {
-        ticket.stage.get_http_metadata_uri(&_event.name, ticket.seat.as_ref())
+    // Create NFT metadata, crediting the event's royalty recipients when it has a split
+    let creators = event
+        .ticket_creators()
+        .into_iter()
+        .map(|(address, share)| Creator {
+            address,
+            verified: address == authority.key(),
+            share,
+        })
+        .collect();
+
+    // The URI is rendered from the event's templates, like every other mint
+    let (name, metadata_uri) = event.ticket_metadata(ticket, ctx.accounts.metadata_config.as_deref())?;
+    let metadata_uri = metadata_uri.ok_or(ErrorCode::MetadataConfigRequired)?;
+    let symbol = "TIX".to_string();
+
+    // 1) Create the mint for the event's mode and the owner's ATA
+    let ticket_mint_mode = event.ticket_mint_mode;
+    let ticket_mint = TicketMint {
+        payer: authority.to_account_info(),
+        mint: nft_mint.to_account_info(),
+        mint_authority: ctx.accounts.ticket_account.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
     };
+    ticket_mint.create(
+        ticket_mint_mode,
+        &[
+            PROGRAM_SEED.as_bytes(),
+            NFT_MINT_SEED.as_bytes(),
+            event_key.as_ref(),
+            &serial_bytes,
+            &[ctx.bumps.nft_mint],
+        ],
+        signer_seeds[0],
+        name.clone(),
+        symbol.clone(),
+        metadata_uri.clone(),
+    )?;
+    ticket_mint.create_token_account(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_account.to_account_info(),
+        ctx.accounts.associated_token_program.to_account_info(),
+    )?;
 
-    let mut name = ticket.stage.get_name(&_event.name, ticket.seat.as_ref());
-    name = clamp_bytes(name, 32);
+    if ticket_mint_mode == TicketMintMode::TransferHook {
+        let extra_account_meta_list = ctx
+            .accounts
+            .extra_account_meta_list
+            .as_ref()
+            .ok_or(ErrorCode::MissingExtraAccountMetas)?;
+        ticket_mint.init_extra_account_metas(
+            extra_account_meta_list,
+            &ctx.accounts.ticket_account.key(),
+            &event_key,
+        )?;
+    }
 
-    let mut symbol = "TIX".to_string();
-    symbol = clamp_bytes(symbol, 10);
+    // 2) Mint 1 to ATA
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.ticket_token_program.to_account_info(),
+            token_interface::MintTo {
+                mint: nft_mint.to_account_info(),
+                to: ctx.accounts.token_account.to_account_info(),
+                authority: ctx.accounts.ticket_account.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+
+    // Token-2022 tickets carry their metadata on the mint, Metaplex is only used for legacy mints
+    if ticket_mint_mode != TicketMintMode::Legacy {
+        return Ok(());
+    }
 
-    metadata_uri = clamp_bytes(metadata_uri, 200);
+    // Tickets join the event's collection, verified once their metadata exists
+    let collection = match event.collection_mint {
+        Some(_) => {
+            let (Some(collection_mint), Some(collection_metadata), Some(collection_master_edition)) = (
+                &ctx.accounts.collection_mint,
+                &ctx.accounts.collection_metadata,
+                &ctx.accounts.collection_master_edition,
+            ) else {
+                return err!(ErrorCode::MissingCollectionAccounts);
+            };
+            Some(EventCollection {
+                payer: authority.to_account_info(),
+                mint: collection_mint.to_account_info(),
+                metadata: collection_metadata.to_account_info(),
+                master_edition: collection_master_edition.to_account_info(),
+                ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+                ticket_authority_bump: ctx.bumps.ticket_authority,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            })
+        }
+        None => None,
+    };
 
     let data_v2 = DataV2 {
         name,
         symbol,
         uri: metadata_uri,
-        seller_fee_basis_points: 500,
+        seller_fee_basis_points: event.resale_royalty_bps(&ctx.accounts.program_config),
         creators: Some(creators),
-        collection: None,
+        collection: collection.as_ref().map(|collection| Collection {
+            verified: false,
+            key: collection.mint.key(),
+        }),
         uses: None,
     };
     
@@ -198,12 +418,12 @@ pub fn handler(
         is_mutable: true,
         collection_details: None,
     };
-    let metadat_ix = CreateMetadataAccountV3 {
+    let metadata_ix = CreateMetadataAccountV3 {
         metadata: ctx.accounts.metadata.key(),
         mint: nft_mint.key(),
         mint_authority: ctx.accounts.ticket_account.key(),
         payer: authority.key(),
-        update_authority: (authority.key(), true).into(),
+        update_authority: (ctx.accounts.ticket_authority.key(), true),
         system_program: ctx.accounts.system_program.key(),
         rent: Some(ctx.accounts.rent.key()),
     }.instruction(metadata_args);
@@ -214,12 +434,18 @@ pub fn handler(
         nft_mint.to_account_info(),
         ctx.accounts.ticket_account.to_account_info(), // mint authority
         authority.to_account_info(), // payer
-        authority.to_account_info(), // update authority
+        ctx.accounts.ticket_authority.to_account_info(), // update authority
         ctx.accounts.system_program.to_account_info(),
         ctx.accounts.rent.to_account_info(),
     ];
 
-    invoke_signed(&metadat_ix, metadata_acct_infos, signer_seeds)?;
+    // The ticket authority PDA signs as update authority so the program can evolve the metadata later
+    let ticket_authority_seeds: &[&[u8]] = &[
+        PROGRAM_SEED.as_bytes(),
+        TICKET_AUTHORITY_SEED.as_bytes(),
+        &[ctx.bumps.ticket_authority],
+    ];
+    invoke_signed(&metadata_ix, metadata_acct_infos, &[signer_seeds[0], ticket_authority_seeds])?;
 
     let master_edition_args = CreateMasterEditionV3InstructionArgs {
         max_supply: Some(0),
@@ -227,7 +453,7 @@ pub fn handler(
     let master_edition_ix = CreateMasterEditionV3 {
         edition: ctx.accounts.master_edition.key(),
         mint: nft_mint.key(),
-        update_authority: authority.key(),
+        update_authority: ctx.accounts.ticket_authority.key(),
         mint_authority: ctx.accounts.ticket_account.key(), // PDA is mint authority
         payer: authority.key(),
         metadata: ctx.accounts.metadata.key(),
@@ -240,7 +466,7 @@ pub fn handler(
         ctx.accounts.token_metadata_program.to_account_info(), // Metaplex Program
         ctx.accounts.master_edition.to_account_info(), // edition account (PDA)
         nft_mint.to_account_info(),
-        authority.to_account_info(), // update authority
+        ctx.accounts.ticket_authority.to_account_info(), // update authority
         ctx.accounts.ticket_account.to_account_info(), // mint authority
         authority.to_account_info(), // payer
         ctx.accounts.metadata.to_account_info(),
@@ -249,7 +475,11 @@ pub fn handler(
         ctx.accounts.rent.to_account_info(),
     ];
 
-    invoke_signed(&master_edition_ix, master_edition_acct_infos, signer_seeds)?;
+    invoke_signed(&master_edition_ix, master_edition_acct_infos, &[signer_seeds[0], ticket_authority_seeds])?;
+
+    if let Some(collection) = collection {
+        collection.verify(ctx.accounts.metadata.to_account_info())?;
+    }
 
     Ok(())
 }
\ No newline at end of file
@@ -38,4 +38,124 @@ pub mod delete_event;
 pub use delete_event::{DeleteEventCtx, handler as delete_event_handler};
 
 pub mod update_event;
-pub use update_event::{UpdateEventCtx, handler as update_event_handler};
\ No newline at end of file
+pub use update_event::{UpdateEventCtx, handler as update_event_handler};
+
+pub mod create_ticket_tier;
+pub use create_ticket_tier::{CreateTicketTierCtx, handler as create_ticket_tier_handler};
+
+pub mod update_ticket_tier;
+pub use update_ticket_tier::{UpdateTicketTierCtx, handler as update_ticket_tier_handler};
+
+pub mod update_config;
+pub use update_config::{UpdateConfigCtx, handler as update_config_handler};
+
+pub mod transfer_admin;
+pub use transfer_admin::{TransferAdminCtx, handler as transfer_admin_handler};
+
+pub mod accept_admin;
+pub use accept_admin::{AcceptAdminCtx, handler as accept_admin_handler};
+
+pub mod set_program_paused;
+pub use set_program_paused::{SetProgramPausedCtx, handler as set_program_paused_handler};
+
+pub mod set_event_paused;
+pub use set_event_paused::{SetEventPausedCtx, handler as set_event_paused_handler};
+
+pub mod cancel_event;
+pub use cancel_event::{CancelEventCtx, handler as cancel_event_handler};
+
+pub mod claim_refund;
+pub use claim_refund::{ClaimRefundCtx, handler as claim_refund_handler};
+
+pub mod withdraw_event_proceeds;
+pub use withdraw_event_proceeds::{WithdrawEventProceedsCtx, handler as withdraw_event_proceeds_handler};
+
+pub mod set_payment_mint;
+pub use set_payment_mint::{SetPaymentMintCtx, handler as set_payment_mint_handler};
+
+pub mod set_ticket_mint_mode;
+pub use set_ticket_mint_mode::{SetTicketMintModeCtx, handler as set_ticket_mint_mode_handler};
+
+pub mod transfer_hook;
+pub use transfer_hook::{TransferHookCtx, handler as transfer_hook_handler};
+
+pub mod sync_ticket_owner;
+pub use sync_ticket_owner::{SyncTicketOwnerCtx, handler as sync_ticket_owner_handler};
+
+pub mod set_transfer_policy;
+pub use set_transfer_policy::{SetTransferPolicyCtx, handler as set_transfer_policy_handler};
+
+pub mod transfer_ticket;
+pub use transfer_ticket::{TransferTicketCtx, handler as transfer_ticket_handler};
+
+pub mod set_resale_policy;
+pub use set_resale_policy::{SetResalePolicyCtx, handler as set_resale_policy_handler};
+
+pub mod set_royalty_split;
+pub use set_royalty_split::{SetRoyaltySplitCtx, handler as set_royalty_split_handler};
+
+pub mod set_purchase_limit;
+pub use set_purchase_limit::{SetPurchaseLimitCtx, handler as set_purchase_limit_handler};
+
+pub mod create_seat_section;
+pub use create_seat_section::{CreateSeatSectionCtx, handler as create_seat_section_handler};
+
+pub mod update_seat_section;
+pub use update_seat_section::{UpdateSeatSectionCtx, handler as update_seat_section_handler};
+
+pub mod swap_seat;
+pub use swap_seat::{SwapSeatCtx, handler as swap_seat_handler};
+
+pub mod set_sale_schedule;
+pub use set_sale_schedule::{SetSaleScheduleCtx, handler as set_sale_schedule_handler};
+
+pub mod add_to_allowlist;
+pub use add_to_allowlist::{AddToAllowlistCtx, handler as add_to_allowlist_handler};
+
+pub mod remove_from_allowlist;
+pub use remove_from_allowlist::{RemoveFromAllowlistCtx, handler as remove_from_allowlist_handler};
+
+pub mod set_allowlist_root;
+pub use set_allowlist_root::{SetAllowlistRootCtx, handler as set_allowlist_root_handler};
+
+pub mod set_dutch_auction;
+pub use set_dutch_auction::{SetDutchAuctionCtx, handler as set_dutch_auction_handler};
+
+pub mod claim_auction_rebate;
+pub use claim_auction_rebate::{ClaimAuctionRebateCtx, handler as claim_auction_rebate_handler};
+
+pub mod place_bid;
+pub use place_bid::{PlaceBidCtx, handler as place_bid_handler};
+
+pub mod settle_auction;
+pub use settle_auction::{SettleAuctionCtx, handler as settle_auction_handler};
+
+pub mod make_offer;
+pub use make_offer::{MakeOfferCtx, handler as make_offer_handler};
+
+pub mod cancel_offer;
+pub use cancel_offer::{CancelOfferCtx, handler as cancel_offer_handler};
+
+pub mod accept_offer;
+pub use accept_offer::{AcceptOfferCtx, handler as accept_offer_handler};
+
+pub mod set_metadata_config;
+pub use set_metadata_config::{SetMetadataConfigCtx, handler as set_metadata_config_handler};
+
+pub mod create_ticket_tree;
+pub use create_ticket_tree::{CreateTicketTreeCtx, handler as create_ticket_tree_handler};
+
+pub mod buy_compressed_ticket;
+pub use buy_compressed_ticket::{BuyCompressedTicketCtx, handler as buy_compressed_ticket_handler};
+
+pub mod update_compressed_ticket;
+pub use update_compressed_ticket::{UpdateCompressedTicketCtx, handler as update_compressed_ticket_handler};
+
+pub mod list_compressed_ticket;
+pub use list_compressed_ticket::{ListCompressedTicketCtx, handler as list_compressed_ticket_handler};
+
+pub mod cancel_compressed_listing;
+pub use cancel_compressed_listing::{CancelCompressedListingCtx, handler as cancel_compressed_listing_handler};
+
+pub mod buy_compressed_listing;
+pub use buy_compressed_listing::{BuyCompressedListingCtx, handler as buy_compressed_listing_handler};
@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::constants::{BID_ESCROW_SEED, CONFIG_SEED, LISTING_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{BidEscrow, EventAccount, EventStatus, ListingAccount, ProgramConfig, TicketAccount};
+
+#[derive(Accounts)]
+pub struct PlaceBidCtx<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(constraint = ticket_account.is_listed @ ErrorCode::TicketNotListed)]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            LISTING_SEED.as_bytes(),
+            ticket_account.key().as_ref()
+        ],
+        bump = listing_account.bump,
+        constraint = listing_account.seller != bidder.key() @ ErrorCode::Unauthorized
+    )]
+    pub listing_account: Account<'info, ListingAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            BID_ESCROW_SEED.as_bytes(),
+            listing_account.key().as_ref()
+        ],
+        bump = bid_escrow.bump
+    )]
+    pub bid_escrow: Account<'info, BidEscrow>,
+
+    /// CHECK: Refunded the bid being topped, must be the listing's highest bidder
+    #[account(mut)]
+    pub previous_bidder: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<PlaceBidCtx>, amount: u64) -> Result<()> {
+    let listing = &mut ctx.accounts.listing_account;
+    let mut auction = listing.auction.ok_or(ErrorCode::NotAnAuction)?;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(current_time < auction.terms.end_ts, ErrorCode::AuctionEnded);
+
+    // An outbid bidder is refunded straight to their wallet, which a refund below the
+    // rent-exempt minimum could leave unable to receive it, blocking every later bid
+    let min_bid = auction
+        .min_bid(listing.price_lamports)
+        .ok_or(ErrorCode::InvalidInput)?
+        .max(Rent::get()?.minimum_balance(0));
+    require!(amount >= min_bid, ErrorCode::BidTooLow);
+    ctx.accounts
+        .event_account
+        .check_resale_price(&ctx.accounts.ticket_account, amount)?;
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.bidder.to_account_info(),
+                to: ctx.accounts.bid_escrow.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    // The escrow only ever holds the highest bid, the one it replaces goes straight back
+    if let Some(highest_bidder) = auction.highest_bidder {
+        let previous_bidder = ctx
+            .accounts
+            .previous_bidder
+            .as_ref()
+            .ok_or(ErrorCode::BidderMismatch)?;
+        require_keys_eq!(previous_bidder.key(), highest_bidder, ErrorCode::BidderMismatch);
+        ctx.accounts.bid_escrow.sub_lamports(auction.highest_bid_lamports)?;
+        previous_bidder.add_lamports(auction.highest_bid_lamports)?;
+    }
+
+    // Anti-sniping: a late bid leaves the others extension_secs to answer
+    let extended_end_ts = current_time.saturating_add(auction.terms.extension_secs as i64);
+    if extended_end_ts > auction.terms.end_ts {
+        auction.terms.end_ts = extended_end_ts;
+    }
+    auction.highest_bidder = Some(ctx.accounts.bidder.key());
+    auction.highest_bid_lamports = amount;
+    listing.auction = Some(auction);
+
+    emit!(BidPlaced {
+        listing_account: listing.key(),
+        bidder: ctx.accounts.bidder.key(),
+        amount,
+        end_ts: auction.terms.end_ts,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct BidPlaced {
+    pub listing_account: Pubkey,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub end_ts: i64,
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{ALLOWLIST_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::instructions::add_to_allowlist::AllowlistUpdated;
+use crate::state::{AllowlistEntry, EventAccount};
+
+#[derive(Accounts)]
+pub struct RemoveFromAllowlistCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            ALLOWLIST_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            allowlist_entry.wallet.as_ref(),
+        ],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+pub fn handler(ctx: Context<RemoveFromAllowlistCtx>) -> Result<()> {
+    emit!(AllowlistUpdated {
+        event_account: ctx.accounts.event_account.key(),
+        wallet: ctx.accounts.allowlist_entry.wallet,
+        allowed: false,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{ALLOWLIST_CLAIMS_SEED, MAX_ALLOWLIST_SLOTS, MERKLE_ALLOWLIST_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{AllowlistClaims, EventAccount, MerkleAllowlist, SalePhase};
+
+#[derive(Accounts)]
+#[instruction(phase: SalePhase)]
+pub struct SetAllowlistRootCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MerkleAllowlist::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            MERKLE_ALLOWLIST_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[phase as u8],
+        ],
+        bump
+    )]
+    pub merkle_allowlist: Account<'info, MerkleAllowlist>,
+
+    /// Claimed slots survive a rotation, so a new root must keep each wallet's slot range
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + AllowlistClaims::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            ALLOWLIST_CLAIMS_SEED.as_bytes(),
+            merkle_allowlist.key().as_ref(),
+        ],
+        bump
+    )]
+    pub allowlist_claims: Box<Account<'info, AllowlistClaims>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<SetAllowlistRootCtx>,
+    phase: SalePhase,
+    root: [u8; 32],
+    slot_count: u32,
+) -> Result<()> {
+    require!(
+        matches!(phase, SalePhase::Presale | SalePhase::Allowlist),
+        ErrorCode::InvalidInput
+    );
+    require!(slot_count <= MAX_ALLOWLIST_SLOTS, ErrorCode::InvalidInput);
+
+    let merkle_allowlist = &mut ctx.accounts.merkle_allowlist;
+    merkle_allowlist.event = ctx.accounts.event_account.key();
+    merkle_allowlist.phase = phase;
+    merkle_allowlist.root = root;
+    merkle_allowlist.slot_count = slot_count;
+    merkle_allowlist.bump = ctx.bumps.merkle_allowlist;
+
+    let allowlist_claims = &mut ctx.accounts.allowlist_claims;
+    allowlist_claims.allowlist = merkle_allowlist.key();
+    allowlist_claims.bump = ctx.bumps.allowlist_claims;
+
+    emit!(AllowlistRootSet {
+        event_account: merkle_allowlist.event,
+        phase,
+        root,
+        slot_count,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AllowlistRootSet {
+    pub event_account: Pubkey,
+    pub phase: SalePhase,
+    pub root: [u8; 32],
+    pub slot_count: u32,
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{DutchAuction, EventAccount};
+
+#[derive(Accounts)]
+pub struct SetDutchAuctionCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(ctx: Context<SetDutchAuctionCtx>, dutch_auction: Option<DutchAuction>) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    // Every buyer of an auction has to see the same price curve
+    require!(event_account.tickets_sold == 0, ErrorCode::DutchAuctionLocked);
+    if let Some(auction) = &dutch_auction {
+        auction.validate(event_account.end_ts)?;
+    }
+
+    event_account.dutch_auction = dutch_auction;
+    event_account.check_compressed_features()?;
+
+    emit!(DutchAuctionSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        dutch_auction,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct DutchAuctionSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub dutch_auction: Option<DutchAuction>,
+}
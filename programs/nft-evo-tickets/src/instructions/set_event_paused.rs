@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, ProgramConfig};
+
+#[derive(Accounts)]
+pub struct SetEventPausedCtx<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Either the event organizer or the platform admin may pause an event
+    #[account(
+        mut,
+        constraint = signer.key() == event_account.authority
+            || signer.key() == program_config.admin @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(ctx: Context<SetEventPausedCtx>, is_paused: bool) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.is_paused = is_paused;
+
+    emit!(EventPauseChanged {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        signer: ctx.accounts.signer.key(),
+        is_paused,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct EventPauseChanged {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub signer: Pubkey,
+    pub is_paused: bool,
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{METADATA_CONFIG_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventMetadataConfig, StageUriTemplates};
+
+#[derive(Accounts)]
+pub struct SetMetadataConfigCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + EventMetadataConfig::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            METADATA_CONFIG_SEED.as_bytes(),
+            event_account.key().as_ref(),
+        ],
+        bump
+    )]
+    pub metadata_config: Account<'info, EventMetadataConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetMetadataConfigCtx>, templates: StageUriTemplates) -> Result<()> {
+    templates.validate()?;
+
+    // Minted tickets keep their URI until their next stage change
+    let metadata_config = &mut ctx.accounts.metadata_config;
+    metadata_config.event = ctx.accounts.event_account.key();
+    metadata_config.templates = templates.clone();
+    metadata_config.bump = ctx.bumps.metadata_config;
+
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.has_metadata_config = true;
+
+    emit!(MetadataConfigSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        templates,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct MetadataConfigSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub templates: StageUriTemplates,
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{Mint, Token, TokenAccount},
+};
+
+use crate::constants::{PROGRAM_SEED, VAULT_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventVault};
+
+#[derive(Accounts)]
+pub struct SetPaymentMintCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Currency can only change before any ticket is sold
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = event_account.tickets_sold == 0 @ ErrorCode::TicketsAlreadySold
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    /// Omit to price the event in SOL
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    /// Vault token account that receives token payments
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_vault
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetPaymentMintCtx>) -> Result<()> {
+    let payment_mint = ctx.accounts.payment_mint.as_ref().map(|mint| mint.key());
+    if payment_mint.is_some() {
+        require!(
+            ctx.accounts.vault_payment_account.is_some(),
+            ErrorCode::MissingPaymentAccounts
+        );
+    }
+
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.payment_mint = payment_mint;
+
+    emit!(PaymentMintSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        payment_mint,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PaymentMintSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub payment_mint: Option<Pubkey>,
+}
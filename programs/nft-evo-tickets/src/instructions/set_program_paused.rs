@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::ProgramConfig;
+
+#[derive(Accounts)]
+pub struct SetProgramPausedCtx<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+pub fn handler(ctx: Context<SetProgramPausedCtx>, is_paused: bool) -> Result<()> {
+    ctx.accounts.program_config.is_paused = is_paused;
+
+    emit!(ProgramPauseChanged {
+        admin: ctx.accounts.admin.key(),
+        is_paused,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ProgramPauseChanged {
+    pub admin: Pubkey,
+    pub is_paused: bool,
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::EventAccount;
+
+#[derive(Accounts)]
+pub struct SetPurchaseLimitCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(ctx: Context<SetPurchaseLimitCtx>, max_tickets_per_wallet: Option<u16>) -> Result<()> {
+    if let Some(max_tickets) = max_tickets_per_wallet {
+        require!(max_tickets > 0, ErrorCode::InvalidInput);
+    }
+
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.max_tickets_per_wallet = max_tickets_per_wallet;
+
+    emit!(PurchaseLimitSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        max_tickets_per_wallet,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PurchaseLimitSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub max_tickets_per_wallet: Option<u16>,
+}
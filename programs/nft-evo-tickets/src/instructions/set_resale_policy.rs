@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::EventAccount;
+
+#[derive(Accounts)]
+pub struct SetResalePolicyCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(
+    ctx: Context<SetResalePolicyCtx>,
+    max_resale_markup_bps: Option<u16>,
+    resale_cooldown_secs: Option<i64>,
+    max_listings_per_wallet: Option<u16>,
+) -> Result<()> {
+    if let Some(cooldown_secs) = resale_cooldown_secs {
+        require!(cooldown_secs >= 0, ErrorCode::InvalidInput);
+    }
+
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.max_resale_markup_bps = max_resale_markup_bps;
+    event_account.resale_cooldown_secs = resale_cooldown_secs;
+    event_account.max_listings_per_wallet = max_listings_per_wallet;
+
+    emit!(ResalePolicySet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        max_resale_markup_bps,
+        resale_cooldown_secs,
+        max_listings_per_wallet,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ResalePolicySet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub max_resale_markup_bps: Option<u16>,
+    pub resale_cooldown_secs: Option<i64>,
+    pub max_listings_per_wallet: Option<u16>,
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, MAX_ROYALTY_RECIPIENTS, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, ProgramConfig, RoyaltyRecipient};
+
+#[derive(Accounts)]
+pub struct SetRoyaltySplitCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    /// Royalties are baked into the NFT creators at mint, so the split is fixed once tickets sell
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = event_account.tickets_sold == 0 @ ErrorCode::TicketsAlreadySold
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+/// Pass no recipients and a zero royalty to fall back to the platform marketplace fee
+pub fn handler(
+    ctx: Context<SetRoyaltySplitCtx>,
+    royalty_bps: u16,
+    royalty_recipients: Vec<RoyaltyRecipient>,
+) -> Result<()> {
+    require!(
+        royalty_recipients.len() <= MAX_ROYALTY_RECIPIENTS,
+        ErrorCode::InvalidRoyaltySplit
+    );
+    require!(
+        royalty_bps <= ctx.accounts.program_config.max_royalty_bps,
+        ErrorCode::InvalidBasisPoints
+    );
+
+    let mut total_bps: u16 = 0;
+    for (i, recipient) in royalty_recipients.iter().enumerate() {
+        require!(recipient.share_bps > 0, ErrorCode::InvalidRoyaltySplit);
+        require!(
+            royalty_recipients[..i].iter().all(|r| r.address != recipient.address),
+            ErrorCode::InvalidRoyaltySplit
+        );
+        total_bps = total_bps
+            .checked_add(recipient.share_bps)
+            .ok_or(ErrorCode::InvalidRoyaltySplit)?;
+    }
+    require!(total_bps == royalty_bps, ErrorCode::InvalidRoyaltySplit);
+
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.royalty_bps = royalty_bps;
+    event_account.royalty_recipients = royalty_recipients.clone();
+
+    emit!(RoyaltySplitSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        royalty_bps,
+        royalty_recipients,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct RoyaltySplitSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub royalty_bps: u16,
+    pub royalty_recipients: Vec<RoyaltyRecipient>,
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, SaleSchedule};
+
+#[derive(Accounts)]
+pub struct SetSaleScheduleCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(ctx: Context<SetSaleScheduleCtx>, sale_schedule: Option<SaleSchedule>) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    if let Some(schedule) = &sale_schedule {
+        schedule.validate(event_account.end_ts)?;
+    }
+
+    event_account.sale_schedule = sale_schedule;
+
+    emit!(SaleScheduleSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        sale_schedule,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SaleScheduleSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub sale_schedule: Option<SaleSchedule>,
+}
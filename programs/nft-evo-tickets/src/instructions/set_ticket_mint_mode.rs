@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, TicketMintMode};
+
+#[derive(Accounts)]
+pub struct SetTicketMintModeCtx<'info> {
+    pub authority: Signer<'info>,
+
+    /// Existing tickets keep their mint, so the mode can only change before the first sale
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = event_account.tickets_sold == 0 @ ErrorCode::TicketsAlreadySold
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(ctx: Context<SetTicketMintModeCtx>, ticket_mint_mode: TicketMintMode) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    // Metaplex collections hold Metaplex metadata, which Token-2022 tickets don't have
+    require!(
+        event_account.collection_mint.is_none() || ticket_mint_mode == TicketMintMode::Legacy,
+        ErrorCode::CollectionRequiresLegacyMints
+    );
+    // Compressed tickets need the event's tree, create_ticket_tree switches to them
+    require!(
+        event_account.merkle_tree.is_some() || ticket_mint_mode != TicketMintMode::Compressed,
+        ErrorCode::TicketTreeMismatch
+    );
+    event_account.ticket_mint_mode = ticket_mint_mode;
+    event_account.check_compressed_features()?;
+
+    emit!(TicketMintModeSet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        ticket_mint_mode,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TicketMintModeSet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub ticket_mint_mode: TicketMintMode,
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, TransferPolicy};
+
+#[derive(Accounts)]
+pub struct SetTransferPolicyCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+pub fn handler(
+    ctx: Context<SetTransferPolicyCtx>,
+    transfer_policy: TransferPolicy,
+    max_transfers_per_ticket: Option<u16>,
+) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    event_account.transfer_policy = transfer_policy;
+    event_account.max_transfers_per_ticket = max_transfers_per_ticket;
+    event_account.check_compressed_features()?;
+
+    emit!(TransferPolicySet {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        transfer_policy,
+        max_transfers_per_ticket,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TransferPolicySet {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub transfer_policy: TransferPolicy,
+    pub max_transfers_per_ticket: Option<u16>,
+}
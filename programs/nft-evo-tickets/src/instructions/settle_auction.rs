@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::{BID_ESCROW_SEED, CONFIG_SEED, LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::instructions::buy_marketplace_ticket::royalty_payments;
+use crate::state::{BidEscrow, EventAccount, ListingAccount, ProgramConfig, SellerListings, TicketAccount};
+use crate::ticket_token::TicketTransfer;
+
+#[derive(Accounts)]
+pub struct SettleAuctionCtx<'info> {
+    /// Anyone can settle an ended auction, they only pay for the recipient's NFT account if missing
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        constraint = ticket_account.is_listed @ ErrorCode::TicketNotListed
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            LISTING_SEED.as_bytes(),
+            ticket_account.key().as_ref()
+        ],
+        bump = listing_account.bump,
+        close = seller
+    )]
+    pub listing_account: Account<'info, ListingAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            BID_ESCROW_SEED.as_bytes(),
+            listing_account.key().as_ref()
+        ],
+        bump = bid_escrow.bump,
+        close = seller
+    )]
+    pub bid_escrow: Account<'info, BidEscrow>,
+
+    /// Not checked for cancellation, so an auction can always be unwound
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// Platform treasury, paid the marketplace fee on events without a royalty split
+    #[account(
+        mut,
+        address = program_config.treasury @ ErrorCode::TreasuryMismatch
+    )]
+    pub treasury: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        address = listing_account.seller @ ErrorCode::SellerMismatch
+    )]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            ticket_account.event.as_ref(),
+            seller.key().as_ref()
+        ],
+        bump = seller_listings.bump
+    )]
+    pub seller_listings: Account<'info, SellerListings>,
+
+    /// CHECK: Gets the ticket, must be the highest bidder or the seller when there were no bids
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = ticket_token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = listing_account,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub escrow_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub recipient_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Program PDA that can pause the transfer hook while the program moves the ticket
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    pub ticket_token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, SettleAuctionCtx<'info>>) -> Result<()> {
+    let ticket_key = ctx.accounts.ticket_account.key();
+    let auction = ctx.accounts.listing_account.auction.ok_or(ErrorCode::NotAnAuction)?;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(current_time >= auction.terms.end_ts, ErrorCode::AuctionNotEnded);
+
+    let recipient = auction.highest_bidder.unwrap_or(ctx.accounts.seller.key());
+    require_keys_eq!(ctx.accounts.recipient.key(), recipient, ErrorCode::BidderMismatch);
+
+    // The winning bid is split like a marketplace sale, out of the bid escrow
+    if auction.highest_bidder.is_some() {
+        let price = auction.highest_bid_lamports;
+        let royalty_payments = royalty_payments(
+            &ctx.accounts.event_account,
+            &ctx.accounts.program_config,
+            price,
+            None,
+            Some(ctx.accounts.treasury.to_account_info()),
+            ctx.remaining_accounts,
+        )?;
+        let royalty_amount: u64 = royalty_payments.iter().map(|(_, amount)| amount).sum();
+
+        ctx.accounts.bid_escrow.sub_lamports(price)?;
+        ctx.accounts.seller.add_lamports(price - royalty_amount)?;
+        for (payee, amount) in royalty_payments {
+            payee.add_lamports(amount)?;
+        }
+    }
+
+    let seeds = &[
+        PROGRAM_SEED.as_bytes(),
+        LISTING_SEED.as_bytes(),
+        ticket_key.as_ref(),
+        &[ctx.accounts.listing_account.bump]
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    TicketTransfer {
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
+        from: ctx.accounts.escrow_nft_account.to_account_info(),
+        to: ctx.accounts.recipient_nft_account.to_account_info(),
+        authority: ctx.accounts.listing_account.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+    }
+    .transfer(signer_seeds)?;
+
+    // The escrow only ever held this ticket, hand its rent back to the seller
+    token_interface::close_account(CpiContext::new_with_signer(
+        ctx.accounts.ticket_token_program.to_account_info(),
+        CloseAccount {
+            account: ctx.accounts.escrow_nft_account.to_account_info(),
+            destination: ctx.accounts.seller.to_account_info(),
+            authority: ctx.accounts.listing_account.to_account_info(),
+        },
+        signer_seeds,
+    ))?;
+
+    let ticket = &mut ctx.accounts.ticket_account;
+    ticket.is_listed = false;
+    if auction.highest_bidder.is_some() {
+        ticket.owner = recipient;
+        ticket.acquired_at = current_time;
+    }
+
+    let seller_listings = &mut ctx.accounts.seller_listings;
+    seller_listings.active_listings = seller_listings.active_listings.saturating_sub(1);
+
+    emit!(AuctionSettled {
+        listing_account: ctx.accounts.listing_account.key(),
+        ticket_account: ticket_key,
+        winner: auction.highest_bidder,
+        price: auction.highest_bid_lamports,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub listing_account: Pubkey,
+    pub ticket_account: Pubkey,
+    pub winner: Option<Pubkey>, // None: no bids, the ticket went back to the seller
+    pub price: u64,
+}
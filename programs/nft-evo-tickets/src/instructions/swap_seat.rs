@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{TokenAccount, TokenInterface};
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED, SEAT_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, ProgramConfig, SeatReservation, SeatSection, TicketAccount};
+
+#[derive(Accounts)]
+#[instruction(new_seat: String)]
+pub struct SwapSeatCtx<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status == EventStatus::Active @ ErrorCode::EventNotActive
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed,
+        constraint = !ticket_account.was_scanned @ ErrorCode::InvalidTicketStage
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    /// Holder's NFT token account, must actually hold the ticket
+    #[account(
+        token::mint = ticket_account.nft_mint,
+        token::authority = holder,
+        token::token_program = token_program,
+        constraint = holder_nft_account.amount == 1 @ ErrorCode::NotTicketHolder
+    )]
+    pub holder_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The seat being given up, released back to the event
+    #[account(
+        mut,
+        close = holder,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SEAT_SEED.as_bytes(),
+            ticket_account.event.as_ref(),
+            ticket_account.seat.as_deref().unwrap_or_default().as_bytes(),
+        ],
+        bump = current_reservation.bump,
+        constraint = current_reservation.ticket == ticket_account.key() @ ErrorCode::SeatReservationMismatch
+    )]
+    pub current_reservation: Account<'info, SeatReservation>,
+
+    /// Claims the new seat, creation fails if it is taken
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + SeatReservation::INIT_SPACE,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SEAT_SEED.as_bytes(),
+            ticket_account.event.as_ref(),
+            new_seat.as_bytes(),
+        ],
+        bump
+    )]
+    pub new_reservation: Account<'info, SeatReservation>,
+
+    /// Section of both seats, required once the event has sections
+    #[account(constraint = seat_section.event == event_account.key() @ ErrorCode::EventMismatch)]
+    pub seat_section: Option<Account<'info, SeatSection>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves a ticket to another free seat. In seated events both seats must be in
+/// the same section, since the holder paid that section's price
+pub fn handler(ctx: Context<SwapSeatCtx>, new_seat: String) -> Result<()> {
+    let event_account = &ctx.accounts.event_account;
+    event_account.check_seat(Some(&new_seat), ctx.accounts.seat_section.as_deref(), true)?;
+    if let Some(seat_section) = &ctx.accounts.seat_section {
+        let current_seat = ctx.accounts.ticket_account.seat.as_deref().unwrap_or_default();
+        seat_section.check_seat(current_seat)?;
+    }
+
+    let new_reservation = &mut ctx.accounts.new_reservation;
+    new_reservation.event = event_account.key();
+    new_reservation.ticket = ctx.accounts.ticket_account.key();
+    new_reservation.bump = ctx.bumps.new_reservation;
+
+    let ticket_account = &mut ctx.accounts.ticket_account;
+    let old_seat = ticket_account.seat.replace(new_seat.clone());
+
+    emit!(SeatSwapped {
+        event_account: event_account.key(),
+        ticket_account: ticket_account.key(),
+        holder: ctx.accounts.holder.key(),
+        old_seat: old_seat.unwrap_or_default(),
+        new_seat,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SeatSwapped {
+    pub event_account: Pubkey,
+    pub ticket_account: Pubkey,
+    pub holder: Pubkey,
+    pub old_seat: String,
+    pub new_seat: String,
+}
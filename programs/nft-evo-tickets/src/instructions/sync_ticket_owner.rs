@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::error::ErrorCode;
+use crate::instructions::transfer_hook::TicketOwnerChanged;
+use crate::state::TicketAccount;
+
+#[derive(Accounts)]
+pub struct SyncTicketOwnerCtx<'info> {
+    /// Listed tickets sit in escrow, their owner is the seller until the listing closes
+    #[account(
+        mut,
+        constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(address = ticket_account.nft_mint @ ErrorCode::MintMismatch)]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Any token account currently holding the ticket
+    #[account(
+        token::mint = nft_mint,
+        constraint = holder_token_account.amount == 1 @ ErrorCode::NotTicketHolder
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Permissionless, points the ticket at whoever holds its NFT after a wallet-to-wallet transfer
+pub fn handler(ctx: Context<SyncTicketOwnerCtx>) -> Result<()> {
+    let ticket = &mut ctx.accounts.ticket_account;
+    let holder = ctx.accounts.holder_token_account.owner;
+    if ticket.owner == holder {
+        return Ok(());
+    }
+
+    let from = ticket.owner;
+    ticket.owner = holder;
+    ticket.acquired_at = Clock::get()?.unix_timestamp;
+
+    emit!(TicketOwnerChanged {
+        ticket_account: ticket.key(),
+        from,
+        to: holder,
+    });
+
+    Ok(())
+}
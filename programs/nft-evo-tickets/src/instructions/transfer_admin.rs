@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::ProgramConfig;
+
+#[derive(Accounts)]
+pub struct TransferAdminCtx<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+/// First step of a two-step handover, takes effect once the new admin calls `accept_admin`
+pub fn handler(ctx: Context<TransferAdminCtx>, new_admin: Pubkey) -> Result<()> {
+    require!(new_admin != Pubkey::default(), ErrorCode::InvalidInput);
+
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.pending_admin = Some(new_admin);
+
+    emit!(AdminTransferProposed {
+        admin: program_config.admin,
+        pending_admin: new_admin,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, TicketAccount};
+
+/// Accounts of the transfer hook Execute instruction, in the order Token-2022 passes them.
+/// The ticket and event come from the mint's ExtraAccountMetaList.
+#[derive(Accounts)]
+pub struct TransferHookCtx<'info> {
+    #[account(token::mint = mint)]
+    pub source_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Owner or delegate of the source account, already verified by Token-2022
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by Token-2022 when resolving the extra accounts
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = ticket_account.nft_mint == mint.key() @ ErrorCode::MintMismatch,
+        constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused
+    )]
+    pub event_account: Account<'info, EventAccount>,
+}
+
+/// Runs on every wallet-to-wallet transfer of a TransferHook ticket, applies the
+/// event's transfer policy and moves `TicketAccount.owner` along with the token
+pub fn handler(ctx: Context<TransferHookCtx>, _amount: u64) -> Result<()> {
+    // Anyone can call the program with these accounts, only trust it mid-transfer
+    let source_info = ctx.accounts.source_token.to_account_info();
+    require_keys_eq!(*source_info.owner, spl_token_2022::ID, ErrorCode::NotTransferring);
+    let data = source_info.try_borrow_data()?;
+    let source = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    let transferring = source.get_extension::<TransferHookAccount>()?.transferring;
+    require!(bool::from(transferring), ErrorCode::NotTransferring);
+
+    let ticket = &mut ctx.accounts.ticket_account;
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts
+        .event_account
+        .check_transfer_allowed(ticket.transfer_count, current_time)?;
+
+    let from = ticket.owner;
+    ticket.owner = ctx.accounts.destination_token.owner;
+    ticket.acquired_at = current_time;
+    ticket.transfer_count = ticket
+        .transfer_count
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+
+    emit!(TicketOwnerChanged {
+        ticket_account: ticket.key(),
+        from,
+        to: ticket.owner,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TicketOwnerChanged {
+    pub ticket_account: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
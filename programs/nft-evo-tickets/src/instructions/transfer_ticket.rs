@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, ProgramConfig, TicketAccount};
+use crate::ticket_token::TicketTransfer;
+
+#[derive(Accounts)]
+pub struct TransferTicketCtx<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = event_account.key() == ticket_account.event @ ErrorCode::EventMismatch,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        constraint = !ticket_account.is_listed @ ErrorCode::TicketAlreadyListed
+    )]
+    pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = token_program
+    )]
+    pub nft_mint: InterfaceAccount<'info, Mint>,
+
+    /// Owner's NFT token account, must actually hold the ticket
+    #[account(
+        mut,
+        token::mint = nft_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+        constraint = owner_nft_account.amount == 1 @ ErrorCode::NotTicketHolder
+    )]
+    pub owner_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Any wallet can receive a ticket
+    #[account(constraint = recipient.key() != owner.key() @ ErrorCode::InvalidInput)]
+    pub recipient: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = nft_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_nft_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Program PDA that can pause the transfer hook while the program moves the ticket
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Gifts a ticket to another wallet under the organizer's transfer policy
+pub fn handler(ctx: Context<TransferTicketCtx>) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    ctx.accounts
+        .event_account
+        .check_transfer_allowed(ctx.accounts.ticket_account.transfer_count, current_time)?;
+
+    TicketTransfer {
+        token_program: ctx.accounts.token_program.to_account_info(),
+        mint: ctx.accounts.nft_mint.to_account_info(),
+        from: ctx.accounts.owner_nft_account.to_account_info(),
+        to: ctx.accounts.recipient_nft_account.to_account_info(),
+        authority: ctx.accounts.owner.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+    }
+    .transfer(&[])?;
+
+    let ticket = &mut ctx.accounts.ticket_account;
+    ticket.owner = ctx.accounts.recipient.key();
+    ticket.acquired_at = current_time;
+    ticket.transfer_count = ticket
+        .transfer_count
+        .checked_add(1)
+        .ok_or(ErrorCode::InvalidInput)?;
+
+    emit!(TicketTransferred {
+        ticket_account: ticket.key(),
+        from: ctx.accounts.owner.key(),
+        to: ticket.owner,
+        transfer_count: ticket.transfer_count,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TicketTransferred {
+    pub ticket_account: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub transfer_count: u16,
+}
@@ -0,0 +1,172 @@
+use anchor_lang::prelude::*;
+
+use crate::compressed_ticket::{
+    CompressedTicket, TicketTree, ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use crate::constants::{CONFIG_SEED, METADATA_CONFIG_SEED, PROGRAM_SEED, SEAT_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventMetadataConfig, ProgramConfig, SeatReservation, TicketStage};
+
+/// Stage changes of compressed tickets, proven from their leaf: the authority
+/// moves tickets to QR, the scanner scans them and anyone upgrades scanned
+/// tickets to collectibles once the event is over
+#[derive(Accounts)]
+#[instruction(new_stage: TicketStage, ticket: CompressedTicket, seat: Option<String>)]
+pub struct UpdateCompressedTicketCtx<'info> {
+    /// Pays for the tree update
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        has_one = authority,
+        has_one = scanner,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// CHECK: The authority of the event account.
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: The scanner of the event account.
+    pub scanner: UncheckedAccount<'info>,
+
+    /// CHECK: The event's ticket tree, checked by Bubblegum
+    #[account(
+        mut,
+        constraint = event_account.merkle_tree == Some(merkle_tree.key()) @ ErrorCode::TicketTreeMismatch
+    )]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config PDA of merkle_tree
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: The ticket's owner, checked against the leaf by Bubblegum
+    pub leaf_owner: UncheckedAccount<'info>,
+    /// CHECK: The ticket's delegate, checked against the leaf by Bubblegum
+    pub leaf_delegate: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that created the tree, the update authority of its tickets
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum program
+    #[account(address = BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Noop program Bubblegum logs leaves to
+    #[account(address = NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: SPL account compression program
+    #[account(address = ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Reservation of the ticket's seat, required for seated tickets
+    #[account(
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SEAT_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            seat.as_deref().unwrap_or_default().as_bytes(),
+        ],
+        bump = seat_reservation.bump
+    )]
+    pub seat_reservation: Option<Account<'info, SeatReservation>>,
+
+    /// Metadata URI templates, required once the event has them
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = metadata_config.bump
+    )]
+    pub metadata_config: Option<Account<'info, EventMetadataConfig>>,
+}
+
+/// The tree's proof nodes for `ticket` are passed as remaining accounts
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, UpdateCompressedTicketCtx<'info>>,
+    new_stage: TicketStage,
+    ticket: CompressedTicket,
+    seat: Option<String>,
+) -> Result<()> {
+    let event_account = &ctx.accounts.event_account;
+    let signer = &ctx.accounts.signer;
+    // Bubblegum rejects the update unless the leaf holds this metadata, so its stage is the ticket's
+    let stage = ticket.stage()?;
+
+    match new_stage {
+        TicketStage::Qr => {
+            // Only the event authority can move a ticket to the QR stage.
+            require!(signer.key() == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        },
+        TicketStage::Scanned => {
+            // Only the designated scanner can mark a ticket as scanned.
+            require!(signer.key() == ctx.accounts.scanner.key(), ErrorCode::Unauthorized);
+            require!(stage == TicketStage::Qr, ErrorCode::InvalidTicketStage);
+        },
+        TicketStage::Collectible => {
+            require!(Clock::get()?.unix_timestamp > event_account.end_ts, ErrorCode::EventNotOver);
+            require!(stage == TicketStage::Scanned, ErrorCode::TicketNotScanned);
+        },
+        _ => {
+            return err!(ErrorCode::InvalidTicketStage);
+        }
+    }
+
+    // Seats aren't in the leaf, seated tickets prove theirs with its reservation
+    let asset_id = ticket.asset_id(&ctx.accounts.merkle_tree.key());
+    match (&seat, &ctx.accounts.seat_reservation) {
+        (Some(_), Some(seat_reservation)) => {
+            require_keys_eq!(seat_reservation.ticket, asset_id, ErrorCode::SeatReservationMismatch);
+        }
+        (None, None) => require!(event_account.section_count == 0, ErrorCode::SeatSectionRequired),
+        _ => return err!(ErrorCode::SeatReservationMismatch),
+    }
+
+    // The leaf evolves with the ticket, its URI is only rewritten for events with templates
+    let uri = event_account.ticket_uri(
+        new_stage,
+        ticket.serial()?,
+        seat.as_ref(),
+        &asset_id,
+        ctx.accounts.metadata_config.as_deref(),
+    )?;
+    TicketTree {
+        merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+        tree_config: ctx.accounts.tree_config.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+        log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+        compression_program: ctx.accounts.compression_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .update_stage(
+        signer.to_account_info(),
+        &ticket,
+        ctx.accounts.leaf_owner.to_account_info(),
+        ctx.accounts.leaf_delegate.to_account_info(),
+        ctx.accounts.token_metadata_program.to_account_info(),
+        new_stage,
+        uri,
+        ctx.remaining_accounts,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, MAX_BASIS_POINTS, PROGRAM_SEED};
+use crate::error::ErrorCode;
+use crate::state::ProgramConfig;
+
+#[derive(Accounts)]
+pub struct UpdateConfigCtx<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateConfigCtx>,
+    treasury: Pubkey,
+    marketplace_fee_bps: u16,
+    max_royalty_bps: u16,
+    dispute_window_secs: i64,
+) -> Result<()> {
+    require!(marketplace_fee_bps <= MAX_BASIS_POINTS, ErrorCode::InvalidBasisPoints);
+    require!(max_royalty_bps <= MAX_BASIS_POINTS, ErrorCode::InvalidBasisPoints);
+    require!(dispute_window_secs >= 0, ErrorCode::InvalidInput);
+
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.treasury = treasury;
+    program_config.marketplace_fee_bps = marketplace_fee_bps;
+    program_config.max_royalty_bps = max_royalty_bps;
+    program_config.dispute_window_secs = dispute_window_secs;
+
+    emit!(ConfigUpdated {
+        admin: program_config.admin,
+        treasury,
+        marketplace_fee_bps,
+        max_royalty_bps,
+        dispute_window_secs,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub marketplace_fee_bps: u16,
+    pub max_royalty_bps: u16,
+    pub dispute_window_secs: i64,
+}
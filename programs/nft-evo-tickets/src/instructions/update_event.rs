@@ -38,6 +38,10 @@ pub fn handler(
     require!(current_time < ctx.accounts.event_account.start_ts, ErrorCode::EventAlreadyStarted);
 
     require!(ctx.accounts.event_account.tickets_sold == 0, ErrorCode::TicketsAlreadySold);
+    require!(
+        ticket_supply >= ctx.accounts.event_account.tiered_supply,
+        ErrorCode::TierSupplyExceeded
+    );
 
     let event_account = &mut ctx.accounts.event_account;
 
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{PROGRAM_SEED, SECTION_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, SeatSection};
+
+#[derive(Accounts)]
+#[instruction(section_index: u8)]
+pub struct UpdateSeatSectionCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// PDA for the section: [PROGRAM_SEED, SECTION_SEED, event, section_index]
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            SECTION_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[section_index],
+        ],
+        bump = seat_section.bump
+    )]
+    pub seat_section: Account<'info, SeatSection>,
+}
+
+/// Reprices a section. The layout is fixed once created since reserved seat ids depend on it
+pub fn handler(
+    ctx: Context<UpdateSeatSectionCtx>,
+    section_index: u8,
+    price_lamports: u64,
+) -> Result<()> {
+    let seat_section = &mut ctx.accounts.seat_section;
+    seat_section.price_lamports = price_lamports;
+
+    emit!(SeatSectionUpdated {
+        event_account: ctx.accounts.event_account.key(),
+        seat_section: seat_section.key(),
+        section_index,
+        price_lamports,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SeatSectionUpdated {
+    pub event_account: Pubkey,
+    pub seat_section: Pubkey,
+    pub section_index: u8,
+    pub price_lamports: u64,
+}
@@ -1,21 +1,30 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Mint;
-use mpl_token_metadata::instructions::{UpdateMetadataAccountV2, UpdateMetadataAccountV2InstructionArgs};
-use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token_interface::{Mint, TokenInterface};
 
-use crate::state::{EventAccount, TicketAccount, TicketStage};
+use crate::constants::{CONFIG_SEED, EVENT_SEED, METADATA_CONFIG_SEED, PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::state::{EventAccount, EventMetadataConfig, ProgramConfig, TicketAccount, TicketStage};
 use crate::error::ErrorCode;
+use crate::ticket_token::TicketMetadataUpdate;
 
 #[derive(Accounts)]
 pub struct UpdateTicketMetadata<'info> {
+    /// Also tops up Token-2022 mints whose metadata grows with the new URI
     #[account(mut)]
     pub signer: Signer<'info>,
 
     #[account(
-        seeds = [b"event".as_ref(), authority.key().as_ref(), &event_account.event_id.to_le_bytes()],
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), EVENT_SEED.as_bytes(), &event_account.event_id.to_le_bytes()],
         bump = event_account.bump,
         has_one = authority,
-        has_one = scanner
+        has_one = scanner,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused
     )]
     pub event_account: Account<'info, EventAccount>,
 
@@ -25,24 +34,46 @@ pub struct UpdateTicketMetadata<'info> {
     )]
     pub ticket_account: Account<'info, TicketAccount>,
 
-    pub ticket_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = ticket_token_program
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
 
-    /// CHECK: This is the metadata account of the ticket mint. It is validated by the Metaplex program.
+    /// CHECK: Metaplex metadata PDA of the ticket mint, checked when the ticket is a legacy mint
     #[account(mut)]
     pub metadata_account: UncheckedAccount<'info>,
 
     /// CHECK: The authority of the event account.
     pub authority: UncheckedAccount<'info>,
-    
+
     /// CHECK: The scanner of the event account.
     pub scanner: UncheckedAccount<'info>,
 
+    /// CHECK: Program PDA that is the update authority of every ticket's metadata
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    pub ticket_token_program: Interface<'info, TokenInterface>,
+
     /// CHECK: Metaplex Token Metadata Program
     #[account(address = mpl_token_metadata::ID)]
     pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Metadata URI templates, required once the event has them
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = metadata_config.bump
+    )]
+    pub metadata_config: Option<Account<'info, EventMetadataConfig>>,
 }
 
-pub fn handler(ctx: Context<UpdateTicketMetadata>, new_stage: TicketStage, new_uri: String) -> Result<()> {
+/// `update_ticket` with a URI of the organizer's choosing, `new_uri` replaces the
+/// stage's URI from the event's templates when set.
+pub fn handler(ctx: Context<UpdateTicketMetadata>, new_stage: TicketStage, new_uri: Option<String>) -> Result<()> {
     let ticket = &mut ctx.accounts.ticket_account;
     let signer = &ctx.accounts.signer;
     let event_account = &ctx.accounts.event_account;
@@ -51,6 +82,9 @@ pub fn handler(ctx: Context<UpdateTicketMetadata>, new_stage: TicketStage, new_u
     let can_set_qr = signer.key() == authority.key();
     let can_set_scanned = signer.key() == ctx.accounts.scanner.key();
 
+    // The scanner only moves tickets along, the artwork is the organizer's call
+    require!(new_uri.is_none() || can_set_qr, ErrorCode::Unauthorized);
+
     match new_stage {
         TicketStage::Qr => {
             require!(can_set_qr, ErrorCode::Unauthorized);
@@ -67,48 +101,24 @@ pub fn handler(ctx: Context<UpdateTicketMetadata>, new_stage: TicketStage, new_u
         }
     }
 
-    msg!("Updating metadata URI to: {}", new_uri);
-
-    let authority_key = authority.key();
-    let event_id_bytes = event_account.event_id.to_le_bytes();
-    let bump_bytes = [event_account.bump];
-    let seeds = &[
-        "event".as_bytes(),
-        authority_key.as_ref(),
-        &event_id_bytes,
-        &bump_bytes,
-    ];
-    let signer_seeds = &[&seeds[..]];
-
-    let ix = UpdateMetadataAccountV2 {
-        metadata: *ctx.accounts.metadata_account.key,
-        update_authority: event_account.key(),
+    let (name, stage_uri) = event_account.ticket_metadata(ticket, ctx.accounts.metadata_config.as_deref())?;
+    let new_uri = new_uri.or(stage_uri);
+    if let Some(uri) = &new_uri {
+        msg!("Updating metadata URI to: {}", uri);
     }
-    .instruction(UpdateMetadataAccountV2InstructionArgs {
-        data: Some(mpl_token_metadata::types::DataV2 {
-            name: ctx.accounts.event_account.name.clone(), // Keep the name
-            symbol: "EVOT".to_string(), // Keep the symbol
-            uri: new_uri, // The new URI
-            seller_fee_basis_points: 0,
-            creators: None,
-            collection: None,
-            uses: None,
-        }),
-        new_update_authority: None,
-        primary_sale_happened: Some(true),
-        is_mutable: Some(true),
-    });
-
-    invoke_signed(
-        &ix,
-        &[
-            ctx.accounts.metadata_account.to_account_info(),
-            event_account.to_account_info(),
-        ],
-        signer_seeds,
-    )?;
-
-    msg!("Metadata URI updated successfully.");
+
+    TicketMetadataUpdate {
+        payer: signer.to_account_info(),
+        mint: ctx.accounts.ticket_mint.to_account_info(),
+        metadata: ctx.accounts.metadata_account.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .update(name, new_uri)?;
+
+    msg!("Metadata updated successfully.");
     Ok(())
 }
-
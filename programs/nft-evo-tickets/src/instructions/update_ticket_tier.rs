@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{PROGRAM_SEED, TIER_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, TicketTier};
+
+#[derive(Accounts)]
+#[instruction(tier_index: u8)]
+pub struct UpdateTicketTierCtx<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    /// PDA for the tier: [PROGRAM_SEED, TIER_SEED, event, tier_index]
+    #[account(
+        mut,
+        seeds = [
+            PROGRAM_SEED.as_bytes(),
+            TIER_SEED.as_bytes(),
+            event_account.key().as_ref(),
+            &[tier_index],
+        ],
+        bump = ticket_tier.bump
+    )]
+    pub ticket_tier: Account<'info, TicketTier>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateTicketTierCtx>,
+    tier_index: u8,
+    price_lamports: u64,
+    supply: u32,
+) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    let ticket_tier = &mut ctx.accounts.ticket_tier;
+
+    // Supply can shrink, but never below what has already been sold
+    require!(supply > 0, ErrorCode::InvalidInput);
+    require!(supply >= ticket_tier.sold, ErrorCode::TicketsAlreadySold);
+
+    let tiered_supply = event_account
+        .tiered_supply
+        .checked_sub(ticket_tier.supply)
+        .and_then(|s| s.checked_add(supply))
+        .ok_or(ErrorCode::InvalidInput)?;
+    require!(
+        tiered_supply <= event_account.ticket_supply,
+        ErrorCode::TierSupplyExceeded
+    );
+
+    event_account.tiered_supply = tiered_supply;
+    ticket_tier.price_lamports = price_lamports;
+    ticket_tier.supply = supply;
+
+    emit!(TicketTierUpdated {
+        event_account: event_account.key(),
+        ticket_tier: ticket_tier.key(),
+        tier_index,
+        price_lamports,
+        supply,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct TicketTierUpdated {
+    pub event_account: Pubkey,
+    pub ticket_tier: Pubkey,
+    pub tier_index: u8,
+    pub price_lamports: u64,
+    pub supply: u32,
+}
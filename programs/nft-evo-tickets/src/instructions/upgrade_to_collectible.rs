@@ -1,27 +1,87 @@
 use anchor_lang::prelude::*;
-use crate::state::{EventAccount, TicketAccount, TicketStage};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+use crate::constants::{CONFIG_SEED, METADATA_CONFIG_SEED, PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::state::{EventAccount, EventMetadataConfig, ProgramConfig, TicketAccount, TicketStage};
 use crate::error::ErrorCode;
+use crate::ticket_token::TicketMetadataUpdate;
 
 #[derive(Accounts)]
 pub struct UpgradeToCollectible<'info> {
+    /// Also tops up Token-2022 mints whose metadata grows with the new stage
+    #[account(mut)]
     pub user: Signer<'info>,
 
     #[account(
-        constraint = Clock::get()?.unix_timestamp > event_account.end_ts @ ErrorCode::EventNotOver
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        constraint = Clock::get()?.unix_timestamp > event_account.end_ts @ ErrorCode::EventNotOver,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused
     )]
     pub event_account: Account<'info, EventAccount>,
 
     #[account(
         mut,
         constraint = ticket_account.event == event_account.key(),
-        constraint = ticket_account.was_scanned == true @ ErrorCode::TicketNotScanned,
+        constraint = ticket_account.was_scanned @ ErrorCode::TicketNotScanned,
         constraint = ticket_account.stage == TicketStage::Scanned @ ErrorCode::InvalidTicketStage
     )]
     pub ticket_account: Account<'info, TicketAccount>,
+
+    #[account(
+        mut,
+        address = ticket_account.nft_mint @ ErrorCode::MintMismatch,
+        mint::token_program = ticket_token_program
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Metaplex metadata PDA of the ticket mint, checked when the ticket is a legacy mint
+    #[account(mut)]
+    pub metadata_account: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that is the update authority of every ticket's metadata
+    #[account(seeds = [PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()], bump)]
+    pub ticket_authority: UncheckedAccount<'info>,
+
+    pub ticket_token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Metaplex Token Metadata Program
+    #[account(address = mpl_token_metadata::ID)]
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Metadata URI templates, required once the event has them
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = metadata_config.bump
+    )]
+    pub metadata_config: Option<Account<'info, EventMetadataConfig>>,
 }
 
 pub fn handler(ctx: Context<UpgradeToCollectible>) -> Result<()> {
     let ticket = &mut ctx.accounts.ticket_account;
     ticket.stage = TicketStage::Collectible;
+
+    let (name, uri) = ctx
+        .accounts
+        .event_account
+        .ticket_metadata(ticket, ctx.accounts.metadata_config.as_deref())?;
+    TicketMetadataUpdate {
+        payer: ctx.accounts.user.to_account_info(),
+        mint: ctx.accounts.ticket_mint.to_account_info(),
+        metadata: ctx.accounts.metadata_account.to_account_info(),
+        ticket_authority: ctx.accounts.ticket_authority.to_account_info(),
+        ticket_authority_bump: ctx.bumps.ticket_authority,
+        token_program: ctx.accounts.ticket_token_program.to_account_info(),
+        token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .update(name, uri)?;
+
     Ok(())
 }
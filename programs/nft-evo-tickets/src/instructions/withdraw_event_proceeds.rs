@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+use crate::constants::{CONFIG_SEED, PROGRAM_SEED, VAULT_SEED};
+use crate::error::ErrorCode;
+use crate::state::{EventAccount, EventStatus, EventVault, ProgramConfig};
+
+#[derive(Accounts)]
+pub struct WithdrawEventProceedsCtx<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()],
+        bump = program_config.bump,
+        constraint = !program_config.is_paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::Unauthorized,
+        constraint = !event_account.is_paused @ ErrorCode::EventPaused,
+        constraint = event_account.status != EventStatus::Cancelled @ ErrorCode::EventCancelled
+    )]
+    pub event_account: Account<'info, EventAccount>,
+
+    #[account(
+        mut,
+        seeds = [PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event_account.key().as_ref()],
+        bump = event_vault.bump
+    )]
+    pub event_vault: Account<'info, EventVault>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// Vault's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = event_vault,
+        constraint = Some(vault_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// Organizer's token account, required when the event has a payment_mint
+    #[account(
+        mut,
+        token::authority = authority,
+        constraint = Some(authority_payment_account.mint) == event_account.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub authority_payment_account: Option<Account<'info, TokenAccount>>,
+}
+
+pub fn handler(ctx: Context<WithdrawEventProceedsCtx>) -> Result<()> {
+    let event_account = &mut ctx.accounts.event_account;
+    let event_vault = &mut ctx.accounts.event_vault;
+
+    // Holders can still dispute until end_ts + dispute window, keep the funds refundable until then
+    let withdrawable_at = event_account
+        .end_ts
+        .checked_add(ctx.accounts.program_config.dispute_window_secs)
+        .ok_or(ErrorCode::InvalidInput)?;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(current_time > withdrawable_at, ErrorCode::DisputeWindowActive);
+
+    // Rebates auction buyers have yet to claim stay in the vault, in the event's payment unit
+    let reserved = event_vault.outstanding_rebates(event_account, current_time);
+    let reserved_lamports = if event_account.payment_mint.is_some() { 0 } else { reserved };
+
+    // Everything above rent exemption is proceeds from primary sales
+    let vault_info = event_vault.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+    let lamports = vault_info
+        .lamports()
+        .saturating_sub(rent_exempt_minimum)
+        .saturating_sub(reserved_lamports);
+
+    if lamports > 0 {
+        event_vault.sub_lamports(lamports)?;
+        ctx.accounts.authority.add_lamports(lamports)?;
+    }
+
+    // Token priced events keep their proceeds in the vault's token account instead
+    let amount = if event_account.payment_mint.is_some() {
+        let (Some(vault_payment_account), Some(authority_payment_account)) = (
+            &ctx.accounts.vault_payment_account,
+            &ctx.accounts.authority_payment_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+
+        let amount = vault_payment_account.amount.saturating_sub(reserved);
+        if amount > 0 {
+            let event_key = event_account.key();
+            let seeds = &[
+                PROGRAM_SEED.as_bytes(),
+                VAULT_SEED.as_bytes(),
+                event_key.as_ref(),
+                &[event_vault.bump],
+            ];
+
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: vault_payment_account.to_account_info(),
+                        to: authority_payment_account.to_account_info(),
+                        authority: event_vault.to_account_info(),
+                    },
+                    &[&seeds[..]],
+                ),
+                amount,
+            )?;
+        }
+        amount
+    } else {
+        lamports
+    };
+
+    event_vault.total_withdrawn = event_vault
+        .total_withdrawn
+        .checked_add(amount)
+        .ok_or(ErrorCode::InvalidInput)?;
+    event_account.status = EventStatus::Settled;
+
+    emit!(ProceedsWithdrawn {
+        event_id: event_account.event_id,
+        event_account: event_account.key(),
+        authority: ctx.accounts.authority.key(),
+        payment_mint: event_account.payment_mint,
+        amount,
+        total_withdrawn: event_vault.total_withdrawn,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ProceedsWithdrawn {
+    pub event_id: u64,
+    pub event_account: Pubkey,
+    pub authority: Pubkey,
+    pub payment_mint: Option<Pubkey>, // Unit of amount, None for lamports
+    pub amount: u64,
+    pub total_withdrawn: u64,
+}
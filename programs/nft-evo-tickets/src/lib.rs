@@ -1,14 +1,24 @@
+#![allow(unexpected_cfgs)]
+// Anchor 0.31's generated IDL instructions call the deprecated
+// AccountInfo::realloc outside the program module
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use spl_discriminator::SplDiscriminate;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
 pub mod constants;
 pub mod error;
 pub mod state;
 pub mod instructions;
+pub mod ticket_token;
+pub mod compressed_ticket;
 
 declare_id!("6mz15gSnFGTWzjHsveE8aFpVTKjdiLkVfQKtvFf1CGdc");
 
 pub use instructions::*;
 pub use state::*;
+use compressed_ticket::CompressedTicket;
 
 use crate::instructions::initialize::__client_accounts_initialize_ctx;
 use crate::instructions::initialize_event::__client_accounts_initialize_event_ctx;
@@ -24,13 +34,59 @@ use crate::instructions::set_scanner::__client_accounts_set_scanner;
 use crate::instructions::buy_event_ticket::__client_accounts_buy_event_ticket_ctx;
 use crate::instructions::delete_event::__client_accounts_delete_event_ctx;
 use crate::instructions::update_event::__client_accounts_update_event_ctx;
+use crate::instructions::create_ticket_tier::__client_accounts_create_ticket_tier_ctx;
+use crate::instructions::update_ticket_tier::__client_accounts_update_ticket_tier_ctx;
+use crate::instructions::update_config::__client_accounts_update_config_ctx;
+use crate::instructions::transfer_admin::__client_accounts_transfer_admin_ctx;
+use crate::instructions::accept_admin::__client_accounts_accept_admin_ctx;
+use crate::instructions::set_program_paused::__client_accounts_set_program_paused_ctx;
+use crate::instructions::set_event_paused::__client_accounts_set_event_paused_ctx;
+use crate::instructions::cancel_event::__client_accounts_cancel_event_ctx;
+use crate::instructions::claim_refund::__client_accounts_claim_refund_ctx;
+use crate::instructions::withdraw_event_proceeds::__client_accounts_withdraw_event_proceeds_ctx;
+use crate::instructions::set_payment_mint::__client_accounts_set_payment_mint_ctx;
+use crate::instructions::set_ticket_mint_mode::__client_accounts_set_ticket_mint_mode_ctx;
+use crate::instructions::transfer_hook::__client_accounts_transfer_hook_ctx;
+use crate::instructions::sync_ticket_owner::__client_accounts_sync_ticket_owner_ctx;
+use crate::instructions::set_transfer_policy::__client_accounts_set_transfer_policy_ctx;
+use crate::instructions::transfer_ticket::__client_accounts_transfer_ticket_ctx;
+use crate::instructions::set_resale_policy::__client_accounts_set_resale_policy_ctx;
+use crate::instructions::set_royalty_split::__client_accounts_set_royalty_split_ctx;
+use crate::instructions::set_purchase_limit::__client_accounts_set_purchase_limit_ctx;
+use crate::instructions::create_seat_section::__client_accounts_create_seat_section_ctx;
+use crate::instructions::update_seat_section::__client_accounts_update_seat_section_ctx;
+use crate::instructions::swap_seat::__client_accounts_swap_seat_ctx;
+use crate::instructions::set_sale_schedule::__client_accounts_set_sale_schedule_ctx;
+use crate::instructions::add_to_allowlist::__client_accounts_add_to_allowlist_ctx;
+use crate::instructions::remove_from_allowlist::__client_accounts_remove_from_allowlist_ctx;
+use crate::instructions::set_allowlist_root::__client_accounts_set_allowlist_root_ctx;
+use crate::instructions::set_dutch_auction::__client_accounts_set_dutch_auction_ctx;
+use crate::instructions::claim_auction_rebate::__client_accounts_claim_auction_rebate_ctx;
+use crate::instructions::place_bid::__client_accounts_place_bid_ctx;
+use crate::instructions::settle_auction::__client_accounts_settle_auction_ctx;
+use crate::instructions::make_offer::__client_accounts_make_offer_ctx;
+use crate::instructions::cancel_offer::__client_accounts_cancel_offer_ctx;
+use crate::instructions::accept_offer::__client_accounts_accept_offer_ctx;
+use crate::instructions::set_metadata_config::__client_accounts_set_metadata_config_ctx;
+use crate::instructions::create_ticket_tree::__client_accounts_create_ticket_tree_ctx;
+use crate::instructions::buy_compressed_ticket::__client_accounts_buy_compressed_ticket_ctx;
+use crate::instructions::update_compressed_ticket::__client_accounts_update_compressed_ticket_ctx;
+use crate::instructions::list_compressed_ticket::__client_accounts_list_compressed_ticket_ctx;
+use crate::instructions::cancel_compressed_listing::__client_accounts_cancel_compressed_listing_ctx;
+use crate::instructions::buy_compressed_listing::__client_accounts_buy_compressed_listing_ctx;
 
 #[program]
 pub mod nft_evo_tickets {
     use super::*;
 
-    pub fn initialize(ctx: Context<InitializeCtx>) -> Result<()> {
-        initialize_handler(ctx)
+    pub fn initialize(
+        ctx: Context<InitializeCtx>,
+        treasury: Pubkey,
+        marketplace_fee_bps: u16,
+        max_royalty_bps: u16,
+        dispute_window_secs: i64,
+    ) -> Result<()> {
+        initialize_handler(ctx, treasury, marketplace_fee_bps, max_royalty_bps, dispute_window_secs)
     }
 
     pub fn initialize_event(
@@ -58,20 +114,25 @@ pub mod nft_evo_tickets {
     pub fn mint_ticket(
         ctx: Context<MintTicketCtx>,
         seat: Option<String>,
-        metadata_uri_override: Option<String>,
+        price_lamports: u64,
     ) -> Result<()> {
-        mint_ticket_handler(ctx, seat, metadata_uri_override)
+        mint_ticket_handler(ctx, seat, price_lamports)
     }
 
     pub fn list_ticket(
         ctx: Context<ListTicketCtx>,
         price_lamports: u64,
         expires_at: Option<i64>,
+        auction: Option<AuctionTerms>,
     ) -> Result<()> {
-        list_ticket_handler(ctx, price_lamports, expires_at)
+        list_ticket_handler(ctx, price_lamports, expires_at, auction)
     }
 
-    pub fn buy_marketplace_ticket(ctx: Context<BuyMarketplaceTicketCtx>) -> Result<()> {
+    /// Remaining accounts: one per event royalty recipient, in order. Wallets for SOL
+    /// listings, the recipients' payment_mint token accounts otherwise
+    pub fn buy_marketplace_ticket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyMarketplaceTicketCtx<'info>>,
+    ) -> Result<()> {
         buy_marketplace_ticket_handler(ctx)
     }
 
@@ -86,7 +147,7 @@ pub mod nft_evo_tickets {
     pub fn update_ticket_metadata(
         ctx: Context<UpdateTicketMetadata>,
         new_stage: TicketStage,
-        new_uri: String,
+        new_uri: Option<String>,
     ) -> Result<()> {
         update_ticket_metadata_handler(ctx, new_stage, new_uri)
     }
@@ -101,11 +162,11 @@ pub mod nft_evo_tickets {
 
     pub fn buy_event_ticket(
         ctx: Context<BuyEventTicketCtx>,
-        ticket_price_lamports: u64,
+        tier_index: u8,
         seat: Option<String>,
-        ticket_id: u64,
+        allowlist_proof: Option<AllowlistProof>,
     ) -> Result<()> {
-        buy_event_ticket_handler(ctx, ticket_price_lamports, seat, ticket_id)
+        buy_event_ticket_handler(ctx, tier_index, seat, allowlist_proof)
     }
 
     pub fn delete_event(
@@ -126,4 +187,268 @@ pub mod nft_evo_tickets {
     ) -> Result<()> {
         update_event_handler(ctx, event_id, name, start_ts, end_ts, ticket_supply, cover_image_url)
     }
+
+    pub fn create_ticket_tier(
+        ctx: Context<CreateTicketTierCtx>,
+        name: String,
+        price_lamports: u64,
+        supply: u32,
+    ) -> Result<()> {
+        create_ticket_tier_handler(ctx, name, price_lamports, supply)
+    }
+
+    pub fn update_ticket_tier(
+        ctx: Context<UpdateTicketTierCtx>,
+        tier_index: u8,
+        price_lamports: u64,
+        supply: u32,
+    ) -> Result<()> {
+        update_ticket_tier_handler(ctx, tier_index, price_lamports, supply)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfigCtx>,
+        treasury: Pubkey,
+        marketplace_fee_bps: u16,
+        max_royalty_bps: u16,
+        dispute_window_secs: i64,
+    ) -> Result<()> {
+        update_config_handler(ctx, treasury, marketplace_fee_bps, max_royalty_bps, dispute_window_secs)
+    }
+
+    pub fn transfer_admin(ctx: Context<TransferAdminCtx>, new_admin: Pubkey) -> Result<()> {
+        transfer_admin_handler(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdminCtx>) -> Result<()> {
+        accept_admin_handler(ctx)
+    }
+
+    pub fn set_program_paused(ctx: Context<SetProgramPausedCtx>, is_paused: bool) -> Result<()> {
+        set_program_paused_handler(ctx, is_paused)
+    }
+
+    pub fn set_event_paused(ctx: Context<SetEventPausedCtx>, is_paused: bool) -> Result<()> {
+        set_event_paused_handler(ctx, is_paused)
+    }
+
+    pub fn cancel_event(ctx: Context<CancelEventCtx>) -> Result<()> {
+        cancel_event_handler(ctx)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefundCtx>) -> Result<()> {
+        claim_refund_handler(ctx)
+    }
+
+    pub fn withdraw_event_proceeds(ctx: Context<WithdrawEventProceedsCtx>) -> Result<()> {
+        withdraw_event_proceeds_handler(ctx)
+    }
+
+    pub fn set_payment_mint(ctx: Context<SetPaymentMintCtx>) -> Result<()> {
+        set_payment_mint_handler(ctx)
+    }
+
+    pub fn set_ticket_mint_mode(
+        ctx: Context<SetTicketMintModeCtx>,
+        ticket_mint_mode: TicketMintMode,
+    ) -> Result<()> {
+        set_ticket_mint_mode_handler(ctx, ticket_mint_mode)
+    }
+
+    /// Token-2022 transfer hook Execute, invoked by the token program on transfers of TransferHook tickets
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHookCtx>, amount: u64) -> Result<()> {
+        transfer_hook_handler(ctx, amount)
+    }
+
+    pub fn sync_ticket_owner(ctx: Context<SyncTicketOwnerCtx>) -> Result<()> {
+        sync_ticket_owner_handler(ctx)
+    }
+
+    pub fn set_transfer_policy(
+        ctx: Context<SetTransferPolicyCtx>,
+        transfer_policy: TransferPolicy,
+        max_transfers_per_ticket: Option<u16>,
+    ) -> Result<()> {
+        set_transfer_policy_handler(ctx, transfer_policy, max_transfers_per_ticket)
+    }
+
+    pub fn transfer_ticket(ctx: Context<TransferTicketCtx>) -> Result<()> {
+        transfer_ticket_handler(ctx)
+    }
+
+    pub fn set_resale_policy(
+        ctx: Context<SetResalePolicyCtx>,
+        max_resale_markup_bps: Option<u16>,
+        resale_cooldown_secs: Option<i64>,
+        max_listings_per_wallet: Option<u16>,
+    ) -> Result<()> {
+        set_resale_policy_handler(ctx, max_resale_markup_bps, resale_cooldown_secs, max_listings_per_wallet)
+    }
+
+    pub fn set_royalty_split(
+        ctx: Context<SetRoyaltySplitCtx>,
+        royalty_bps: u16,
+        royalty_recipients: Vec<RoyaltyRecipient>,
+    ) -> Result<()> {
+        set_royalty_split_handler(ctx, royalty_bps, royalty_recipients)
+    }
+
+    pub fn set_purchase_limit(
+        ctx: Context<SetPurchaseLimitCtx>,
+        max_tickets_per_wallet: Option<u16>,
+    ) -> Result<()> {
+        set_purchase_limit_handler(ctx, max_tickets_per_wallet)
+    }
+
+    pub fn create_seat_section(
+        ctx: Context<CreateSeatSectionCtx>,
+        name: String,
+        rows: u8,
+        seats_per_row: u16,
+        price_lamports: u64,
+    ) -> Result<()> {
+        create_seat_section_handler(ctx, name, rows, seats_per_row, price_lamports)
+    }
+
+    pub fn update_seat_section(
+        ctx: Context<UpdateSeatSectionCtx>,
+        section_index: u8,
+        price_lamports: u64,
+    ) -> Result<()> {
+        update_seat_section_handler(ctx, section_index, price_lamports)
+    }
+
+    pub fn swap_seat(ctx: Context<SwapSeatCtx>, new_seat: String) -> Result<()> {
+        swap_seat_handler(ctx, new_seat)
+    }
+
+    pub fn set_sale_schedule(
+        ctx: Context<SetSaleScheduleCtx>,
+        sale_schedule: Option<SaleSchedule>,
+    ) -> Result<()> {
+        set_sale_schedule_handler(ctx, sale_schedule)
+    }
+
+    pub fn add_to_allowlist(ctx: Context<AddToAllowlistCtx>, wallet: Pubkey) -> Result<()> {
+        add_to_allowlist_handler(ctx, wallet)
+    }
+
+    pub fn remove_from_allowlist(ctx: Context<RemoveFromAllowlistCtx>) -> Result<()> {
+        remove_from_allowlist_handler(ctx)
+    }
+
+    pub fn set_allowlist_root(
+        ctx: Context<SetAllowlistRootCtx>,
+        phase: SalePhase,
+        root: [u8; 32],
+        slot_count: u32,
+    ) -> Result<()> {
+        set_allowlist_root_handler(ctx, phase, root, slot_count)
+    }
+
+    pub fn set_dutch_auction(
+        ctx: Context<SetDutchAuctionCtx>,
+        dutch_auction: Option<DutchAuction>,
+    ) -> Result<()> {
+        set_dutch_auction_handler(ctx, dutch_auction)
+    }
+
+    pub fn claim_auction_rebate(ctx: Context<ClaimAuctionRebateCtx>) -> Result<()> {
+        claim_auction_rebate_handler(ctx)
+    }
+
+    pub fn place_bid(ctx: Context<PlaceBidCtx>, amount: u64) -> Result<()> {
+        place_bid_handler(ctx, amount)
+    }
+
+    /// Remaining accounts: one wallet per event royalty recipient, in order
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleAuctionCtx<'info>>,
+    ) -> Result<()> {
+        settle_auction_handler(ctx)
+    }
+
+    /// `ticket` None makes an offer on any ticket of the event
+    pub fn make_offer(
+        ctx: Context<MakeOfferCtx>,
+        ticket: Option<Pubkey>,
+        price_lamports: u64,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        make_offer_handler(ctx, ticket, price_lamports, expires_at)
+    }
+
+    pub fn cancel_offer(ctx: Context<CancelOfferCtx>) -> Result<()> {
+        cancel_offer_handler(ctx)
+    }
+
+    /// Remaining accounts: one wallet per event royalty recipient, in order
+    pub fn accept_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptOfferCtx<'info>>,
+    ) -> Result<()> {
+        accept_offer_handler(ctx)
+    }
+
+    /// Creates or replaces the event's metadata URI templates
+    pub fn set_metadata_config(
+        ctx: Context<SetMetadataConfigCtx>,
+        templates: StageUriTemplates,
+    ) -> Result<()> {
+        set_metadata_config_handler(ctx, templates)
+    }
+
+    /// Switches the event to compressed tickets minted into `merkle_tree`
+    pub fn create_ticket_tree(
+        ctx: Context<CreateTicketTreeCtx>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        create_ticket_tree_handler(ctx, max_depth, max_buffer_size)
+    }
+
+    pub fn buy_compressed_ticket(
+        ctx: Context<BuyCompressedTicketCtx>,
+        tier_index: u8,
+        seat: Option<String>,
+        allowlist_proof: Option<AllowlistProof>,
+    ) -> Result<()> {
+        buy_compressed_ticket_handler(ctx, tier_index, seat, allowlist_proof)
+    }
+
+    /// Remaining accounts: the ticket's Merkle proof nodes
+    pub fn update_compressed_ticket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateCompressedTicketCtx<'info>>,
+        new_stage: TicketStage,
+        ticket: CompressedTicket,
+        seat: Option<String>,
+    ) -> Result<()> {
+        update_compressed_ticket_handler(ctx, new_stage, ticket, seat)
+    }
+
+    /// Remaining accounts: the ticket's Merkle proof nodes
+    pub fn list_compressed_ticket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ListCompressedTicketCtx<'info>>,
+        price_lamports: u64,
+        expires_at: Option<i64>,
+        ticket: CompressedTicket,
+    ) -> Result<()> {
+        list_compressed_ticket_handler(ctx, price_lamports, expires_at, ticket)
+    }
+
+    /// Remaining accounts: the ticket's Merkle proof nodes
+    pub fn cancel_compressed_listing<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelCompressedListingCtx<'info>>,
+        ticket: CompressedTicket,
+    ) -> Result<()> {
+        cancel_compressed_listing_handler(ctx, ticket)
+    }
+
+    /// Remaining accounts: one wallet per event royalty recipient, in order, then the ticket's Merkle proof nodes
+    pub fn buy_compressed_listing<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyCompressedListingCtx<'info>>,
+        ticket: CompressedTicket,
+    ) -> Result<()> {
+        buy_compressed_listing_handler(ctx, ticket)
+    }
 }
@@ -1,4 +1,27 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::constants::{MAX_ALLOWLIST_SLOTS, MAX_BASIS_POINTS, MAX_METADATA_URI_LEN, MAX_ROYALTY_RECIPIENTS, MAX_SEAT_LEN, NFT_MINT_SEED, PROGRAM_SEED, TICKET_SEED};
+use crate::error::ErrorCode;
+
+// ---------- ProgramConfig ----------
+// Singleton PDA: [PROGRAM_SEED, CONFIG_SEED]
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>, // Set by transfer_admin, cleared by accept_admin
+    pub treasury: Pubkey, // Platform wallet the marketplace fee is paid to
+    pub marketplace_fee_bps: u16, // Resale fee paid to the treasury without a royalty split, also NFT seller_fee_basis_points
+    pub max_royalty_bps: u16,
+    pub dispute_window_secs: i64, // Delay after end_ts before organizers can withdraw proceeds
+    /// Program-wide circuit breaker. Every instruction that moves funds or tickets
+    /// requires both this and the event's is_paused to be false, except those that
+    /// only hand users back what the program holds for them: cancel_listing,
+    /// cancel_compressed_listing, cancel_offer and claim_refund
+    pub is_paused: bool,
+    pub bump: u8,
+}
 
 // ---------- EventAccount ----------
 #[account]
@@ -11,11 +34,457 @@ pub struct EventAccount {
     pub name: String,
     pub start_ts: i64,
     pub end_ts: i64,
-    pub tickets_sold: u32,
+    pub tickets_sold: u32, // Never decreases, also numbers tickets, see next_ticket_serial
     pub ticket_supply: u32,
     pub version: u8, // Version field for tracking event format changes
     #[max_len(200)]
     pub cover_image_url: String, // IPFS or external URL for event cover photo
+    pub tier_count: u8, // Number of TicketTier PDAs created for this event
+    pub tiered_supply: u32, // Sum of supply allocated across all tiers
+    pub is_paused: bool, // Per-event circuit breaker, see set_event_paused and ProgramConfig::is_paused
+    pub status: EventStatus,
+    pub tickets_refunded: u32,
+    pub payment_mint: Option<Pubkey>, // SPL token prices are denominated in, None for SOL
+    pub ticket_mint_mode: TicketMintMode,
+    pub transfer_policy: TransferPolicy, // Applies to gifting and wallet-to-wallet transfers, not marketplace sales
+    pub max_transfers_per_ticket: Option<u16>,
+    pub max_resale_markup_bps: Option<u16>, // Listing price cap over the ticket's face value
+    pub resale_cooldown_secs: Option<i64>, // Delay after acquiring a ticket before it can be listed
+    pub max_listings_per_wallet: Option<u16>, // Simultaneous listings per seller for this event
+    pub royalty_bps: u16, // Total resale royalty, split across royalty_recipients
+    #[max_len(MAX_ROYALTY_RECIPIENTS)]
+    pub royalty_recipients: Vec<RoyaltyRecipient>, // Empty: the platform fee goes to the event vault
+    pub max_tickets_per_wallet: Option<u16>, // Primary sales per buyer, tracked by BuyerPurchases
+    pub section_count: u8, // Number of SeatSection PDAs, every ticket needs a section seat once this is set
+    pub sale_schedule: Option<SaleSchedule>, // None: tickets are on public sale until the event ends
+    pub dutch_auction: Option<DutchAuction>, // Replaces the tier and section price outside the presale
+    pub auction_clearing_price_lamports: u64, // Price of the latest auction sale, the lowest so far, in payment_mint base units when set
+    pub has_metadata_config: bool, // Set with the EventMetadataConfig PDA, ticket metadata then needs it
+    pub collection_mint: Option<Pubkey>, // Verified Metaplex collection every ticket NFT joins, see EventCollection
+    pub merkle_tree: Option<Pubkey>, // Bubblegum tree compressed tickets are minted into, see create_ticket_tree
+    pub bump: u8,
+}
+
+impl EventAccount {
+    /// Serial of the next ticket sold or minted. Serials start at 1 and are never reused
+    pub fn next_ticket_serial(&self) -> u32 {
+        self.tickets_sold.saturating_add(1)
+    }
+
+    /// Sale phase at `now`, every purchase is checked against it
+    pub fn sale_phase(&self, now: i64) -> SalePhase {
+        let Some(schedule) = &self.sale_schedule else {
+            return SalePhase::Public;
+        };
+        if now >= schedule.sales_close_ts {
+            SalePhase::Closed
+        } else if now >= schedule.public_start_ts {
+            SalePhase::Public
+        } else if schedule.allowlist_start_ts.is_some_and(|start| now >= start) {
+            SalePhase::Allowlist
+        } else if schedule.presale_start_ts.is_some_and(|start| now >= start) {
+            SalePhase::Presale
+        } else {
+            SalePhase::NotOpen
+        }
+    }
+
+    /// Final price of the Dutch auction, once it has sold out or reached its floor.
+    /// None while the price can still fall or without an auction
+    pub fn auction_clearing_price(&self, now: i64) -> Option<u64> {
+        let auction = self.dutch_auction.as_ref()?;
+        if self.tickets_sold >= self.ticket_supply {
+            Some(self.auction_clearing_price_lamports.max(auction.floor_price_lamports))
+        } else if now >= auction.end_ts {
+            Some(auction.floor_price_lamports)
+        } else {
+            None
+        }
+    }
+
+    /// NFT name of `ticket` at its current stage, and its metadata URI rendered from
+    /// the event's templates. Events with templates must pass their EventMetadataConfig
+    pub fn ticket_metadata(
+        &self,
+        ticket: &TicketAccount,
+        metadata_config: Option<&EventMetadataConfig>,
+    ) -> Result<(String, Option<String>)> {
+        let uri = self.ticket_uri(
+            ticket.stage,
+            ticket.serial,
+            ticket.seat.as_ref(),
+            &ticket.nft_mint,
+            metadata_config,
+        )?;
+        Ok((ticket.stage.get_name(&self.name, ticket.seat.as_ref()), uri))
+    }
+
+    /// Metadata URI of a ticket at `stage`, `mint` being its NFT mint or compressed asset id.
+    /// None for events without templates
+    pub fn ticket_uri(
+        &self,
+        stage: TicketStage,
+        serial: u32,
+        seat: Option<&String>,
+        mint: &Pubkey,
+        metadata_config: Option<&EventMetadataConfig>,
+    ) -> Result<Option<String>> {
+        require!(
+            metadata_config.is_some() == self.has_metadata_config,
+            ErrorCode::MetadataConfigMismatch
+        );
+        metadata_config
+            .map(|config| stage.get_http_metadata_uri(&config.templates, self.event_id, serial, seat, mint))
+            .transpose()
+    }
+
+    /// Metaplex creators of the event's tickets, the organizer unless royalties are split
+    pub fn ticket_creators(&self) -> Vec<(Pubkey, u8)> {
+        if self.royalty_recipients.is_empty() {
+            vec![(self.authority, 100)]
+        } else {
+            self.creator_shares()
+        }
+    }
+
+    /// Checks a new ticket's seat against the event's seat map. Every seat needs a
+    /// SeatReservation, and seated events only sell seats from their sections
+    pub fn check_seat(
+        &self,
+        seat: Option<&String>,
+        seat_section: Option<&SeatSection>,
+        has_reservation: bool,
+    ) -> Result<()> {
+        require!(seat.is_some() == has_reservation, ErrorCode::SeatReservationMismatch);
+        if let Some(seat_id) = seat {
+            require!(
+                !seat_id.is_empty() && seat_id.len() <= MAX_SEAT_LEN,
+                ErrorCode::InvalidSeat
+            );
+        }
+        if self.section_count > 0 {
+            let (Some(seat_id), Some(seat_section)) = (seat, seat_section) else {
+                return err!(ErrorCode::SeatSectionRequired);
+            };
+            seat_section.check_seat(seat_id)?;
+        }
+        Ok(())
+    }
+
+    /// Checks the organizer's transfer policy for a ticket that has moved `transfer_count` times
+    pub fn check_transfer_allowed(&self, transfer_count: u16, now: i64) -> Result<()> {
+        match self.transfer_policy {
+            TransferPolicy::Allowed => {}
+            TransferPolicy::Disallowed => return err!(ErrorCode::TransfersDisabled),
+            TransferPolicy::BeforeStart => {
+                require!(now < self.start_ts, ErrorCode::TransferWindowClosed)
+            }
+        }
+        if let Some(max_transfers) = self.max_transfers_per_ticket {
+            require!(transfer_count < max_transfers, ErrorCode::TransferLimitReached);
+        }
+        Ok(())
+    }
+
+    /// Royalty charged on resales, in basis points of the sale price
+    pub fn resale_royalty_bps(&self, program_config: &ProgramConfig) -> u16 {
+        if self.royalty_recipients.is_empty() {
+            program_config.marketplace_fee_bps
+        } else {
+            self.royalty_bps
+        }
+    }
+
+    /// Royalty recipients as Metaplex creator shares, which are percentages summing to 100
+    pub fn creator_shares(&self) -> Vec<(Pubkey, u8)> {
+        let mut shares: Vec<(Pubkey, u8)> = self
+            .royalty_recipients
+            .iter()
+            .map(|r| (r.address, (r.share_bps as u32 * 100 / self.royalty_bps as u32) as u8))
+            .collect();
+        // Rounding leftovers go to the first recipient
+        let total: u8 = shares.iter().map(|(_, share)| share).sum();
+        if let Some((_, first)) = shares.first_mut() {
+            *first += 100 - total;
+        }
+        shares
+    }
+
+    /// Checks `price` against the resale markup cap on what `ticket` was bought for
+    pub fn check_resale_price(&self, ticket: &TicketAccount, price: u64) -> Result<()> {
+        if let Some(markup_bps) = self.max_resale_markup_bps {
+            let max_price = ticket.price_paid_lamports as u128
+                * (MAX_BASIS_POINTS as u128 + markup_bps as u128)
+                / MAX_BASIS_POINTS as u128;
+            require!(price as u128 <= max_price, ErrorCode::ResalePriceTooHigh);
+        }
+        Ok(())
+    }
+
+    /// Checks the organizer's anti-scalping rules for listing `ticket` at `price`
+    /// by a seller who already has `active_listings` open for this event
+    pub fn check_resale_allowed(
+        &self,
+        ticket: &TicketAccount,
+        price: u64,
+        active_listings: u16,
+        now: i64,
+    ) -> Result<()> {
+        self.check_resale_price(ticket, price)?;
+        if let Some(cooldown_secs) = self.resale_cooldown_secs {
+            require!(
+                now >= ticket.acquired_at.saturating_add(cooldown_secs),
+                ErrorCode::ResaleCooldownActive
+            );
+        }
+        if let Some(max_listings) = self.max_listings_per_wallet {
+            require!(active_listings < max_listings, ErrorCode::ListingLimitReached);
+        }
+        Ok(())
+    }
+
+    /// Anti-scalping rules for listing a compressed ticket. Their leaf has no price
+    /// paid or acquisition time, so price caps and cool-downs can't be enforced
+    pub fn check_compressed_resale_allowed(&self, active_listings: u16) -> Result<()> {
+        require!(
+            self.max_resale_markup_bps.is_none() && self.resale_cooldown_secs.is_none(),
+            ErrorCode::ResalePolicyNeedsTicketAccounts
+        );
+        if let Some(max_listings) = self.max_listings_per_wallet {
+            require!(active_listings < max_listings, ErrorCode::ListingLimitReached);
+        }
+        Ok(())
+    }
+
+    /// Compressed tickets have no ticket account to rebate or count transfers on, so
+    /// events issuing them can't run a Dutch auction or restrict transfers
+    pub fn check_compressed_features(&self) -> Result<()> {
+        if self.ticket_mint_mode == TicketMintMode::Compressed {
+            require!(
+                self.dutch_auction.is_none()
+                    && self.transfer_policy == TransferPolicy::Allowed
+                    && self.max_transfers_per_ticket.is_none(),
+                ErrorCode::CompressedTicketsUnsupported
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+#[repr(u8)]
+pub enum EventStatus {
+    Active = 0,
+    Cancelled = 1, // Holders can claim refunds from the vault
+    Settled = 2,   // Proceeds have been withdrawn by the organizer
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+#[repr(u8)]
+pub enum TicketMintMode {
+    Legacy = 0,          // SPL Token mint with Metaplex metadata
+    NonTransferable = 1, // Token-2022 soulbound mint
+    TransferHook = 2,    // Token-2022 mint whose transfers are validated by this program
+    Compressed = 3,      // Bubblegum leaf in the event's merkle_tree, without a TicketAccount or mint
+}
+
+impl TicketMintMode {
+    pub fn token_program_id(&self) -> Pubkey {
+        match self {
+            TicketMintMode::Legacy => anchor_spl::token::ID,
+            _ => anchor_spl::token_2022::ID,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct RoyaltyRecipient {
+    pub address: Pubkey, // Wallet paid directly on each resale, e.g. organizer, artist, venue
+    pub share_bps: u16, // Basis points of the sale price, shares sum to royalty_bps
+}
+
+/// When tickets go on sale. Each optional window runs until the next one opens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub struct SaleSchedule {
+    pub presale_start_ts: Option<i64>, // Allowlisted buyers at presale_price_lamports
+    pub presale_price_lamports: u64, // Replaces the tier and section price during the presale, in payment_mint base units when set
+    pub allowlist_start_ts: Option<i64>, // Allowlisted buyers at the regular price
+    pub public_start_ts: i64,
+    pub sales_close_ts: i64,
+}
+
+impl SaleSchedule {
+    /// Checks the windows are in order and sales close by `event_end_ts`
+    pub fn validate(&self, event_end_ts: i64) -> Result<()> {
+        let starts = [self.presale_start_ts, self.allowlist_start_ts, Some(self.public_start_ts)];
+        let mut previous = i64::MIN;
+        for start in starts.into_iter().flatten() {
+            require!(start > previous, ErrorCode::InvalidSaleSchedule);
+            previous = start;
+        }
+        require!(
+            self.sales_close_ts > self.public_start_ts && self.sales_close_ts <= event_end_ts,
+            ErrorCode::InvalidSaleSchedule
+        );
+        Ok(())
+    }
+}
+
+/// Descending price sale, from start_price_lamports at start_ts down to floor_price_lamports at end_ts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct DutchAuction {
+    pub start_price_lamports: u64, // In payment_mint base units when the event has one
+    pub floor_price_lamports: u64, // Same unit as start_price_lamports
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub step_secs: u32, // 0: the price decays linearly, otherwise it drops once per step
+    pub rebate: bool, // Buyers can claim back what they paid above the clearing price
+}
+
+impl DutchAuction {
+    /// Checks the price falls over a window that ends by `event_end_ts`
+    pub fn validate(&self, event_end_ts: i64) -> Result<()> {
+        require!(
+            self.start_price_lamports > self.floor_price_lamports
+                && self.start_ts < self.end_ts
+                && self.end_ts <= event_end_ts
+                && (self.step_secs as i64) < self.end_ts - self.start_ts,
+            ErrorCode::InvalidDutchAuction
+        );
+        Ok(())
+    }
+
+    /// Ticket price at `now`
+    pub fn price_at(&self, now: i64) -> u64 {
+        if now <= self.start_ts {
+            return self.start_price_lamports;
+        }
+        if now >= self.end_ts {
+            return self.floor_price_lamports;
+        }
+        let mut elapsed = (now - self.start_ts) as u128;
+        if self.step_secs > 0 {
+            elapsed -= elapsed % self.step_secs as u128;
+        }
+        let duration = (self.end_ts - self.start_ts) as u128;
+        let range = (self.start_price_lamports - self.floor_price_lamports) as u128;
+        // elapsed < duration, so the drop is below range and fits in a u64
+        self.start_price_lamports - (range * elapsed / duration) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+#[repr(u8)]
+pub enum SalePhase {
+    NotOpen = 0,
+    Presale = 1,
+    Allowlist = 2,
+    Public = 3,
+    Closed = 4,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+#[repr(u8)]
+pub enum TransferPolicy {
+    Allowed = 0,
+    Disallowed = 1,
+    BeforeStart = 2, // Only until start_ts
+}
+
+// ---------- EventVault ----------
+// PDA: [PROGRAM_SEED, VAULT_SEED, event]
+// Holds primary sale proceeds until the event settles or is cancelled.
+#[account]
+#[derive(InitSpace)]
+pub struct EventVault {
+    pub event: Pubkey,
+    pub total_withdrawn: u64,
+    pub unrebated_auction_sales: u32, // Tickets bought at the Dutch auction price whose rebate is unclaimed
+    pub unrebated_auction_proceeds: u64, // What those tickets paid, in payment_mint base units when set
+    pub bump: u8,
+}
+
+impl EventVault {
+    /// Auction rebates holders can still claim, kept in the vault when proceeds are
+    /// withdrawn. Reserved down to the floor price until the clearing price is final
+    pub fn outstanding_rebates(&self, event: &EventAccount, now: i64) -> u64 {
+        let Some(auction) = event.dutch_auction.filter(|auction| auction.rebate) else {
+            return 0;
+        };
+        let clearing_price = event
+            .auction_clearing_price(now)
+            .unwrap_or(auction.floor_price_lamports);
+        self.unrebated_auction_proceeds
+            .saturating_sub(clearing_price.saturating_mul(self.unrebated_auction_sales as u64))
+    }
+}
+
+// ---------- TicketTier ----------
+// PDA: [PROGRAM_SEED, TIER_SEED, event, tier_index]
+#[account]
+#[derive(InitSpace)]
+pub struct TicketTier {
+    pub event: Pubkey,
+    pub tier_index: u8,
+    #[max_len(32)]
+    pub name: String, // e.g. "GA", "VIP", "Backstage"
+    pub price_lamports: u64, // In payment_mint base units when the event has one
+    pub supply: u32,
+    pub sold: u32,
+    pub bump: u8,
+}
+
+// ---------- SeatSection ----------
+// PDA: [PROGRAM_SEED, SECTION_SEED, event, section_index]
+// A block of numbered seats. Seat ids are "{name}-{row}-{number}", e.g. "ORCH-12-7".
+#[account]
+#[derive(InitSpace)]
+pub struct SeatSection {
+    pub event: Pubkey,
+    pub section_index: u8,
+    #[max_len(16)]
+    pub name: String,
+    pub rows: u8,
+    pub seats_per_row: u16,
+    pub price_lamports: u64, // Replaces the tier price for seats in this section, in payment_mint base units when set
+    pub bump: u8,
+}
+
+impl SeatSection {
+    /// Seat id of `number` in `row`, both counted from 1
+    pub fn seat_id(&self, row: u8, number: u16) -> String {
+        format!("{}-{}-{}", self.name, row, number)
+    }
+
+    /// Checks `seat_id` names a seat of this section, in canonical form so the
+    /// same seat can't be reserved twice under different spellings
+    pub fn check_seat(&self, seat_id: &str) -> Result<()> {
+        let mut parts = seat_id.rsplitn(3, '-');
+        let (Some(number), Some(row), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+            return err!(ErrorCode::InvalidSeat);
+        };
+        let (Ok(row), Ok(number)) = (row.parse::<u8>(), number.parse::<u16>()) else {
+            return err!(ErrorCode::InvalidSeat);
+        };
+        require!(
+            name == self.name
+                && (1..=self.rows).contains(&row)
+                && (1..=self.seats_per_row).contains(&number)
+                && self.seat_id(row, number) == seat_id,
+            ErrorCode::InvalidSeat
+        );
+        Ok(())
+    }
+}
+
+// ---------- SeatReservation ----------
+// PDA: [PROGRAM_SEED, SEAT_SEED, event, seat_id]
+// Exists while a ticket holds the seat, so creating it twice fails and a seat sells once.
+#[account]
+#[derive(InitSpace)]
+pub struct SeatReservation {
+    pub event: Pubkey,
+    pub ticket: Pubkey,
     pub bump: u8,
 }
 
@@ -26,16 +495,40 @@ pub struct TicketAccount {
     pub event: Pubkey,
     pub owner: Pubkey,
     pub nft_mint: Pubkey,
-    #[max_len(32)]
-    pub seat: Option<String>,
+    #[max_len(MAX_SEAT_LEN)]
+    pub seat: Option<String>, // Reserved through a SeatReservation when set
+    pub tier_index: Option<u8>, // None for tickets minted directly by the organizer
+    pub price_paid_lamports: u64, // Refunded if the event is cancelled and caps resale prices, in payment_mint base units when set
+    pub bought_at_auction: bool, // Paid the Dutch auction price, so owed a rebate down to the clearing price
     pub stage: TicketStage,
     pub is_listed: bool,
     pub was_scanned: bool,
-    pub listing_price: Option<u64>,
+    pub listing_price: Option<u64>, // Mirrors the listing's price_lamports, in the same unit
     pub listing_expires_at: Option<i64>,
+    pub transfer_count: u16, // Owner-to-owner transfers, capped by max_transfers_per_ticket
+    pub acquired_at: i64, // Last purchase or transfer, starts the resale cool-down
+    pub serial: u32, // Position in the event's sales, the ticket and mint PDAs derive from it
     pub bump: u8,
 }
 
+impl TicketAccount {
+    /// Ticket PDA: [PROGRAM_SEED, TICKET_SEED, event, serial]
+    pub fn pda(event: &Pubkey, serial: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PROGRAM_SEED.as_bytes(), TICKET_SEED.as_bytes(), event.as_ref(), &serial.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// NFT mint PDA of the same ticket: [PROGRAM_SEED, NFT_MINT_SEED, event, serial]
+    pub fn nft_mint_pda(event: &Pubkey, serial: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PROGRAM_SEED.as_bytes(), NFT_MINT_SEED.as_bytes(), event.as_ref(), &serial.to_le_bytes()],
+            &crate::ID,
+        )
+    }
+}
+
 // ---------- Enum ----------
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
 #[repr(u8)] //1 byte
@@ -47,58 +540,210 @@ pub enum TicketStage {
 }
 
 impl TicketStage {
-    pub fn get_http_metadata_uri(&self, event_name: &str, seat: Option<&String>) -> String {
+    pub const ALL: [TicketStage; 4] = [
+        TicketStage::Prestige,
+        TicketStage::Qr,
+        TicketStage::Scanned,
+        TicketStage::Collectible,
+    ];
+
+    /// Lowercase name used by the `{stage}` URI placeholder
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketStage::Prestige => "prestige",
+            TicketStage::Qr => "qr",
+            TicketStage::Scanned => "scanned",
+            TicketStage::Collectible => "collectible",
+        }
+    }
+
+    /// Symbol of compressed tickets at this stage. Their leaf is the only record
+    /// of a compressed ticket, so the stage travels in its metadata
+    pub fn compressed_symbol(&self) -> &'static str {
         match self {
-            TicketStage::Prestige => get_prestige_metadata_uri(event_name, seat),
-            TicketStage::Qr => get_qr_code_metadata_uri(event_name, seat),
-            TicketStage::Scanned => get_scanned_metadata_uri(event_name, seat),
-            TicketStage::Collectible => get_collectible_metadata_uri(event_name, seat),
+            TicketStage::Prestige => "TIX-PRE",
+            TicketStage::Qr => "TIX-QR",
+            TicketStage::Scanned => "TIX-SCAN",
+            TicketStage::Collectible => "TIX-COLL",
         }
     }
 
+    pub fn from_compressed_symbol(symbol: &str) -> Option<TicketStage> {
+        TicketStage::ALL
+            .into_iter()
+            .find(|stage| stage.compressed_symbol() == symbol)
+    }
+
+    /// The stage's template rendered for a ticket, placeholder values URL-encoded
+    pub fn get_http_metadata_uri(
+        &self,
+        templates: &StageUriTemplates,
+        event_id: u64,
+        serial: u32,
+        seat: Option<&String>,
+        mint: &Pubkey,
+    ) -> Result<String> {
+        render_uri_template(templates.for_stage(*self), |placeholder, uri| {
+            let value = match placeholder {
+                UriPlaceholder::EventId => event_id.to_string(),
+                UriPlaceholder::Serial => serial.to_string(),
+                UriPlaceholder::Seat => seat.cloned().unwrap_or_default(),
+                UriPlaceholder::Mint => mint.to_string(),
+                UriPlaceholder::Stage => self.as_str().to_string(),
+            };
+            push_url_encoded(uri, &value);
+        })
+    }
+
+    /// NFT name "TIX • {event} • {seat}", the same at every stage. Metaplex caps
+    /// names at 32 bytes, so the event name is shortened first and the whole name
+    /// cut to fit after that, both on a char boundary
     pub fn get_name(&self, event_name: &str, seat: Option<&String>) -> String {
-        // Metaplex has a 32-character limit for NFT names
-        // Format: "TIX • {event} • {seat}" but truncate event name if needed
-        let seat_str = seat.map_or("".to_string(), |s| s.clone());
-        let prefix = "TIX • ";
-        let separator = if !seat_str.is_empty() { " • " } else { "" };
-
-        // Calculate available space: 32 - prefix - separator - seat
-        let available_for_event = 32_usize
-            .saturating_sub(prefix.len())
-            .saturating_sub(separator.len())
-            .saturating_sub(seat_str.len());
-
-        // Truncate event name if necessary
-        let truncated_event = if event_name.len() > available_for_event {
-            &event_name[..available_for_event]
-        } else {
-            event_name
-        };
+        const MAX_NAME_LEN: usize = 32;
+        const PREFIX: &str = "TIX • ";
+        let seat_suffix = seat
+            .filter(|seat| !seat.is_empty())
+            .map_or(String::new(), |seat| format!(" • {}", seat));
 
-        match self {
-            TicketStage::Prestige => format!("{}{}{}{}", prefix, truncated_event, separator, seat_str),
-            TicketStage::Qr => format!("{}{}{}{}", prefix, truncated_event, separator, seat_str),
-            TicketStage::Scanned => format!("{}{}{}{}", prefix, truncated_event, separator, seat_str),
-            TicketStage::Collectible => format!("{}{}{}{}", prefix, truncated_event, separator, seat_str),
+        let available_for_event = MAX_NAME_LEN.saturating_sub(PREFIX.len() + seat_suffix.len());
+        let name = format!(
+            "{}{}{}",
+            PREFIX,
+            truncate_on_char_boundary(event_name, available_for_event),
+            seat_suffix
+        );
+        truncate_on_char_boundary(&name, MAX_NAME_LEN).to_string()
+    }
+}
+
+/// The longest prefix of `s` within `max_len` bytes that doesn't split a char
+fn truncate_on_char_boundary(s: &str, max_len: usize) -> &str {
+    let mut len = s.len().min(max_len);
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    &s[..len]
+}
+
+// ---------- EventMetadataConfig ----------
+// PDA: [PROGRAM_SEED, METADATA_CONFIG_SEED, event]
+// Metadata URI template of each stage, used by every mint and stage change of the event.
+#[account]
+#[derive(InitSpace)]
+pub struct EventMetadataConfig {
+    pub event: Pubkey,
+    pub templates: StageUriTemplates,
+    pub bump: u8,
+}
+
+/// Metadata URI template of each stage, e.g. `https://cdn.example/{event_id}/{serial}/{stage}.json`.
+/// Placeholders: `{event_id}`, `{serial}`, `{seat}` (empty without a seat), `{mint}` and `{stage}`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct StageUriTemplates {
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub prestige: String,
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub qr: String,
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub scanned: String,
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub collectible: String,
+}
+
+impl StageUriTemplates {
+    pub fn for_stage(&self, stage: TicketStage) -> &str {
+        match stage {
+            TicketStage::Prestige => &self.prestige,
+            TicketStage::Qr => &self.qr,
+            TicketStage::Scanned => &self.scanned,
+            TicketStage::Collectible => &self.collectible,
+        }
+    }
+
+    /// Every stage needs a template of printable ASCII with known placeholders, which
+    /// stays within Metaplex's URI limit with the longest value of each placeholder
+    pub fn validate(&self) -> Result<()> {
+        for stage in TicketStage::ALL {
+            let template = self.for_stage(stage);
+            require!(
+                !template.is_empty() && template.bytes().all(|byte| byte.is_ascii_graphic()),
+                ErrorCode::InvalidMetadataTemplate
+            );
+            render_uri_template(template, |placeholder, uri| {
+                uri.push_str(&"x".repeat(placeholder.max_len()));
+            })?;
         }
+        Ok(())
     }
 }
 
-fn get_prestige_metadata_uri(event_name: &str, seat: Option<&String>) -> String {
-    format!("https://example.com/tickets/prestige/{}/{}/metadata.json", event_name.replace(" ", "-"), seat.map_or("".to_string(), |s| s.clone()))
+/// Values a metadata URI template can reference as `{name}`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UriPlaceholder {
+    EventId,
+    Serial,
+    Seat,
+    Mint,
+    Stage,
 }
 
-fn get_qr_code_metadata_uri(event_name: &str, seat: Option<&String>) -> String {
-    format!("https://example.com/tickets/qr/{}/{}/metadata.json", event_name.replace(" ", "-"), seat.map_or("".to_string(), |s| s.clone()))
+impl UriPlaceholder {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "event_id" => Some(UriPlaceholder::EventId),
+            "serial" => Some(UriPlaceholder::Serial),
+            "seat" => Some(UriPlaceholder::Seat),
+            "mint" => Some(UriPlaceholder::Mint),
+            "stage" => Some(UriPlaceholder::Stage),
+            _ => None,
+        }
+    }
+
+    /// Longest URL-encoded value, seats may need every byte percent-encoded
+    fn max_len(self) -> usize {
+        match self {
+            UriPlaceholder::EventId => 20, // u64::MAX
+            UriPlaceholder::Serial => 10, // u32::MAX
+            UriPlaceholder::Seat => 3 * MAX_SEAT_LEN,
+            UriPlaceholder::Mint => 44, // Base58 pubkey
+            UriPlaceholder::Stage => TicketStage::Collectible.as_str().len(),
+        }
+    }
 }
 
-fn get_scanned_metadata_uri(event_name: &str, seat: Option<&String>) -> String {
-    format!("https://example.com/tickets/scanned/{}/{}/metadata.json", event_name.replace(" ", "-"), seat.map_or("".to_string(), |s| s.clone()))
+/// Copies `template`, letting `value` append each placeholder's value. Rejects unknown
+/// placeholders and unmatched braces, and URIs over Metaplex's 200-byte limit
+fn render_uri_template(template: &str, mut value: impl FnMut(UriPlaceholder, &mut String)) -> Result<String> {
+    let mut uri = String::with_capacity(MAX_METADATA_URI_LEN);
+    let mut rest = template;
+    while let Some(brace) = rest.find(['{', '}']) {
+        uri.push_str(&rest[..brace]);
+        let placeholder = &rest[brace..];
+        let close = placeholder.find('}').ok_or(ErrorCode::InvalidMetadataTemplate)?;
+        let placeholder_name = placeholder[..close].strip_prefix('{').ok_or(ErrorCode::InvalidMetadataTemplate)?;
+        value(
+            UriPlaceholder::parse(placeholder_name).ok_or(ErrorCode::InvalidMetadataTemplate)?,
+            &mut uri,
+        );
+        rest = &placeholder[close + 1..];
+    }
+    uri.push_str(rest);
+    require!(uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::MetadataUriTooLong);
+    Ok(uri)
 }
 
-fn get_collectible_metadata_uri(event_name: &str, seat: Option<&String>) -> String {
-    format!("https://example.com/tickets/collectible/{}/{}/metadata.json", event_name.replace(" ", "-"), seat.map_or("".to_string(), |s| s.clone()))
+/// Percent-encodes every byte outside RFC 3986's unreserved characters
+fn push_url_encoded(uri: &mut String, value: &str) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            uri.push(byte as char);
+        } else {
+            uri.push('%');
+            uri.push(HEX[(byte >> 4) as usize] as char);
+            uri.push(HEX[(byte & 0x0f) as usize] as char);
+        }
+    }
 }
 
 // ---------- ListingAccount ----------
@@ -107,8 +752,171 @@ fn get_collectible_metadata_uri(event_name: &str, seat: Option<&String>) -> Stri
 pub struct ListingAccount {
     pub ticket: Pubkey,
     pub seller: Pubkey,
+    pub price_lamports: u64, // In payment_mint base units when set, the reserve price of auctions
+    pub payment_mint: Option<Pubkey>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub auction: Option<ListingAuction>, // None: fixed price, sold through buy_marketplace_ticket
+    pub bump: u8,
+}
+
+/// English auction terms chosen by the seller
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct AuctionTerms {
+    pub min_increment_lamports: u64,
+    pub end_ts: i64,
+    pub extension_secs: u32, // Bids this close to end_ts push it back to now + extension_secs
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ListingAuction {
+    pub terms: AuctionTerms,
+    pub highest_bidder: Option<Pubkey>, // Their bid is held in the listing's BidEscrow
+    pub highest_bid_lamports: u64,
+}
+
+impl ListingAuction {
+    /// Lowest acceptable next bid: the reserve price, then the highest bid plus the increment
+    pub fn min_bid(&self, reserve_price_lamports: u64) -> Option<u64> {
+        match self.highest_bidder {
+            None => Some(reserve_price_lamports),
+            Some(_) => self
+                .highest_bid_lamports
+                .checked_add(self.terms.min_increment_lamports),
+        }
+    }
+}
+
+// ---------- BidEscrow ----------
+// PDA: [PROGRAM_SEED, BID_ESCROW_SEED, listing]
+// Holds the highest bid of an auction listing on top of its rent.
+#[account]
+#[derive(InitSpace)]
+pub struct BidEscrow {
+    pub listing: Pubkey,
+    pub bump: u8,
+}
+
+// ---------- OfferAccount ----------
+// PDA: [PROGRAM_SEED, OFFER_SEED, target, buyer], target is the ticket or, for offers on
+// any ticket of the event, the event. Holds the offered lamports on top of its rent.
+#[account]
+#[derive(InitSpace)]
+pub struct OfferAccount {
+    pub buyer: Pubkey,
+    pub event: Pubkey,
+    pub ticket: Option<Pubkey>, // None: any ticket of the event
     pub price_lamports: u64,
     pub created_at: i64,
     pub expires_at: Option<i64>,
     pub bump: u8,
 }
+
+// ---------- BuyerPurchases ----------
+// PDA: [PROGRAM_SEED, PURCHASES_SEED, event, buyer]
+// Primary sale tickets bought per wallet, capped by max_tickets_per_wallet.
+#[account]
+#[derive(InitSpace)]
+pub struct BuyerPurchases {
+    pub event: Pubkey,
+    pub buyer: Pubkey,
+    pub tickets_bought: u16,
+    pub bump: u8,
+}
+
+// ---------- AllowlistEntry ----------
+// PDA: [PROGRAM_SEED, ALLOWLIST_SEED, event, wallet]
+// Lets a wallet buy during the presale and allowlist windows.
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistEntry {
+    pub event: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+// ---------- MerkleAllowlist ----------
+// PDA: [PROGRAM_SEED, MERKLE_ALLOWLIST_SEED, event, phase]
+// Allowlist for one sale phase, too large to store as AllowlistEntry PDAs. Each leaf
+// grants a wallet `allocation` claim slots starting at `index`, see AllowlistProof.
+#[account]
+#[derive(InitSpace)]
+pub struct MerkleAllowlist {
+    pub event: Pubkey,
+    pub phase: SalePhase,
+    pub root: [u8; 32],
+    pub slot_count: u32, // Slots in use, claims are tracked in the AllowlistClaims bitmap
+    pub bump: u8,
+}
+
+impl MerkleAllowlist {
+    /// Leaf hash: sha256(0x00 || wallet || index || allocation), integers little-endian
+    pub fn leaf(wallet: &Pubkey, index: u32, allocation: u16) -> [u8; 32] {
+        hashv(&[&[0], wallet.as_ref(), &index.to_le_bytes(), &allocation.to_le_bytes()]).to_bytes()
+    }
+
+    /// Node hash: sha256(0x01 || lower || higher), children sorted so proofs need no directions
+    pub fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+        let (lower, higher) = if a <= b { (a, b) } else { (b, a) };
+        hashv(&[&[1], lower, higher]).to_bytes()
+    }
+
+    /// Checks `wallet` is granted the proof's slots under the current root
+    pub fn verify(&self, wallet: &Pubkey, proof: &AllowlistProof) -> Result<()> {
+        let end = proof.index as u64 + proof.allocation as u64;
+        require!(
+            proof.allocation > 0 && end <= self.slot_count as u64,
+            ErrorCode::InvalidAllowlistProof
+        );
+        let computed = proof
+            .proof
+            .iter()
+            .fold(Self::leaf(wallet, proof.index, proof.allocation), |hash, sibling| {
+                Self::node(&hash, sibling)
+            });
+        require!(computed == self.root, ErrorCode::InvalidAllowlistProof);
+        Ok(())
+    }
+}
+
+/// A buyer's Merkle allowlist leaf and the sibling hashes up to the root
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct AllowlistProof {
+    pub index: u32, // First claim slot of the leaf
+    pub allocation: u16, // Tickets the leaf allows, one slot each
+    pub proof: Vec<[u8; 32]>,
+}
+
+// ---------- AllowlistClaims ----------
+// PDA: [PROGRAM_SEED, ALLOWLIST_CLAIMS_SEED, merkle_allowlist]
+// One bit per claim slot. Kept across root rotations, so slots must keep their owner.
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistClaims {
+    pub allowlist: Pubkey,
+    pub claimed: [u8; MAX_ALLOWLIST_SLOTS as usize / 8],
+    pub bump: u8,
+}
+
+impl AllowlistClaims {
+    /// Claims the first free slot of the proof's range, returning its index
+    pub fn claim(&mut self, proof: &AllowlistProof) -> Result<u32> {
+        let slot = (proof.index..proof.index + proof.allocation as u32)
+            .find(|slot| self.claimed[*slot as usize / 8] & (1 << (slot % 8)) == 0)
+            .ok_or(ErrorCode::AllowlistAllocationUsed)?;
+        self.claimed[slot as usize / 8] |= 1 << (slot % 8);
+        Ok(slot)
+    }
+}
+
+// ---------- SellerListings ----------
+// PDA: [PROGRAM_SEED, SELLER_LISTINGS_SEED, event, seller]
+// Open listings per seller and event, capped by max_listings_per_wallet.
+#[account]
+#[derive(InitSpace)]
+pub struct SellerListings {
+    pub event: Pubkey,
+    pub seller: Pubkey,
+    pub active_listings: u16,
+    pub bump: u8,
+}
@@ -0,0 +1,620 @@
+//! Ticket NFT mint creation, transfers and metadata updates for both the
+//! legacy Token program and Token-2022, see `TicketMintMode`, and the event
+//! collections legacy tickets are verified into.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_spl::associated_token::{self, Create};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        non_transferable::NonTransferable, transfer_hook, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+};
+use anchor_spl::token_interface::{
+    self, metadata_pointer_initialize, non_transferable_mint_initialize, spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_metadata_interface::state::{Field, TokenMetadata}, token_metadata_initialize,
+    token_metadata_update_field, transfer_hook_initialize, transfer_hook_update, InitializeMint2,
+    MetadataPointerInitialize, MintTo, NonTransferableMintInitialize, TokenMetadataInitialize,
+    TokenMetadataUpdateField, TransferChecked, TransferHookInitialize, TransferHookUpdate,
+};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{
+        CreateMasterEditionV3, CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3,
+        CreateMetadataAccountV3InstructionArgs, UpdateMetadataAccountV2,
+        UpdateMetadataAccountV2InstructionArgs, VerifySizedCollectionItem,
+    },
+    types::{CollectionDetails, DataV2},
+};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+use crate::constants::{MAX_METADATA_URI_LEN, PROGRAM_SEED, TICKET_AUTHORITY_SEED};
+use crate::error::ErrorCode;
+use crate::state::TicketMintMode;
+
+const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// Accounts needed to create a ticket mint and the holder's token account.
+pub struct TicketMint<'info> {
+    pub payer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: AccountInfo<'info>, // ticket PDA, also the freeze authority
+    pub ticket_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> TicketMint<'info> {
+    /// Creates the PDA mint at `mint_seeds`. Token-2022 mints carry their own
+    /// metadata, legacy ones get Metaplex metadata from the caller.
+    pub fn create(
+        &self,
+        mode: TicketMintMode,
+        mint_seeds: &[&[u8]],
+        mint_authority_seeds: &[&[u8]],
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        // Compressed tickets are Bubblegum leaves, see compressed_ticket
+        require!(mode != TicketMintMode::Compressed, ErrorCode::TicketsAreCompressed);
+        let (space, funded_space) = if mode == TicketMintMode::Legacy {
+            (spl_token_2022::state::Mint::LEN, spl_token_2022::state::Mint::LEN)
+        } else {
+            let extension = match mode {
+                TicketMintMode::NonTransferable => ExtensionType::NonTransferable,
+                _ => ExtensionType::TransferHook,
+            };
+            let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+                ExtensionType::MetadataPointer,
+                extension,
+            ])?;
+            // Token-2022 reallocs the mint for the metadata itself, it only needs the lamports up front
+            let metadata = TokenMetadata {
+                update_authority: OptionalNonZeroPubkey::try_from(Some(self.ticket_authority.key()))?,
+                mint: self.mint.key(),
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                additional_metadata: vec![],
+            };
+            (space, space + metadata.tlv_size_of()?)
+        };
+
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                CreateAccount {
+                    from: self.payer.clone(),
+                    to: self.mint.clone(),
+                },
+                &[mint_seeds],
+            ),
+            Rent::get()?.minimum_balance(funded_space),
+            space as u64,
+            self.token_program.key,
+        )?;
+
+        // Extensions have to be initialized before the mint itself
+        match mode {
+            TicketMintMode::Legacy | TicketMintMode::Compressed => {}
+            TicketMintMode::NonTransferable => {
+                self.init_metadata_pointer()?;
+                non_transferable_mint_initialize(CpiContext::new(
+                    self.token_program.clone(),
+                    NonTransferableMintInitialize {
+                        token_program_id: self.token_program.clone(),
+                        mint: self.mint.clone(),
+                    },
+                ))?;
+            }
+            TicketMintMode::TransferHook => {
+                self.init_metadata_pointer()?;
+                transfer_hook_initialize(
+                    CpiContext::new(
+                        self.token_program.clone(),
+                        TransferHookInitialize {
+                            token_program_id: self.token_program.clone(),
+                            mint: self.mint.clone(),
+                        },
+                    ),
+                    Some(self.ticket_authority.key()),
+                    Some(crate::ID),
+                )?;
+            }
+        }
+
+        token_interface::initialize_mint2(
+            CpiContext::new(
+                self.token_program.clone(),
+                InitializeMint2 {
+                    mint: self.mint.clone(),
+                },
+            ),
+            0,
+            self.mint_authority.key,
+            Some(self.mint_authority.key),
+        )?;
+
+        if mode != TicketMintMode::Legacy {
+            token_metadata_initialize(
+                CpiContext::new_with_signer(
+                    self.token_program.clone(),
+                    TokenMetadataInitialize {
+                        program_id: self.token_program.clone(),
+                        metadata: self.mint.clone(),
+                        update_authority: self.ticket_authority.clone(),
+                        mint_authority: self.mint_authority.clone(),
+                        mint: self.mint.clone(),
+                    },
+                    &[mint_authority_seeds],
+                ),
+                name,
+                symbol,
+                uri,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn init_metadata_pointer(&self) -> Result<()> {
+        metadata_pointer_initialize(
+            CpiContext::new(
+                self.token_program.clone(),
+                MetadataPointerInitialize {
+                    token_program_id: self.token_program.clone(),
+                    mint: self.mint.clone(),
+                },
+            ),
+            Some(self.ticket_authority.key()),
+            Some(self.mint.key()),
+        )
+    }
+
+    /// Creates `owner`'s associated token account for the mint if it doesn't exist yet.
+    pub fn create_token_account(
+        &self,
+        owner: AccountInfo<'info>,
+        token_account: AccountInfo<'info>,
+        associated_token_program: AccountInfo<'info>,
+    ) -> Result<()> {
+        associated_token::create_idempotent(CpiContext::new(
+            associated_token_program,
+            Create {
+                payer: self.payer.clone(),
+                associated_token: token_account,
+                authority: owner,
+                mint: self.mint.clone(),
+                system_program: self.system_program.clone(),
+                token_program: self.token_program.clone(),
+            },
+        ))
+    }
+
+    /// Creates the transfer hook's ExtraAccountMetaList for the mint, pointing
+    /// the hook at the ticket and event accounts it keeps in sync.
+    pub fn init_extra_account_metas(
+        &self,
+        extra_account_meta_list: &AccountInfo<'info>,
+        ticket_account: &Pubkey,
+        event_account: &Pubkey,
+    ) -> Result<()> {
+        let mint_key = self.mint.key();
+        let (expected, bump) = Pubkey::find_program_address(
+            &[EXTRA_ACCOUNT_METAS_SEED, mint_key.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(extra_account_meta_list.key(), expected, ErrorCode::InvalidInput);
+
+        let metas = [
+            ExtraAccountMeta::new_with_pubkey(ticket_account, false, true)?,
+            ExtraAccountMeta::new_with_pubkey(event_account, false, false)?,
+        ];
+        let space = ExtraAccountMetaList::size_of(metas.len())?;
+
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.clone(),
+                CreateAccount {
+                    from: self.payer.clone(),
+                    to: extra_account_meta_list.clone(),
+                },
+                &[&[EXTRA_ACCOUNT_METAS_SEED, mint_key.as_ref(), &[bump]]],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut extra_account_meta_list.try_borrow_mut_data()?,
+            &metas,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Metaplex accounts of an event's collection NFT. The ticket authority PDA is
+/// its mint authority, update authority and holder, so only the program can
+/// verify tickets into it.
+pub struct EventCollection<'info> {
+    pub payer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>,
+    pub master_edition: AccountInfo<'info>,
+    pub ticket_authority: AccountInfo<'info>,
+    pub ticket_authority_bump: u8,
+    pub token_program: AccountInfo<'info>, // legacy Token program
+    pub token_metadata_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> EventCollection<'info> {
+    /// Creates the collection mint at `mint_seeds`, mints its only token to the
+    /// ticket authority's `token_account` and makes it a sized Metaplex collection.
+    pub fn create(
+        &self,
+        mint_seeds: &[&[u8]],
+        token_account: AccountInfo<'info>,
+        associated_token_program: AccountInfo<'info>,
+        mut name: String,
+    ) -> Result<()> {
+        // Metaplex has a 32 byte limit for NFT names
+        let mut name_len = name.len().min(32);
+        while !name.is_char_boundary(name_len) {
+            name_len -= 1;
+        }
+        name.truncate(name_len);
+
+        let bump = [self.ticket_authority_bump];
+        let ticket_authority_seeds: &[&[u8]] = &[
+            PROGRAM_SEED.as_bytes(),
+            TICKET_AUTHORITY_SEED.as_bytes(),
+            &bump,
+        ];
+
+        let collection_mint = TicketMint {
+            payer: self.payer.clone(),
+            mint: self.mint.clone(),
+            mint_authority: self.ticket_authority.clone(),
+            ticket_authority: self.ticket_authority.clone(),
+            token_program: self.token_program.clone(),
+            system_program: self.system_program.clone(),
+        };
+        // Legacy mints get their metadata from Metaplex below
+        collection_mint.create(
+            TicketMintMode::Legacy,
+            mint_seeds,
+            ticket_authority_seeds,
+            String::new(),
+            String::new(),
+            String::new(),
+        )?;
+        collection_mint.create_token_account(
+            self.ticket_authority.clone(),
+            token_account.clone(),
+            associated_token_program,
+        )?;
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                MintTo {
+                    mint: self.mint.clone(),
+                    to: token_account,
+                    authority: self.ticket_authority.clone(),
+                },
+                &[ticket_authority_seeds],
+            ),
+            1,
+        )?;
+
+        // The collection only groups and verifies tickets, it has no off-chain JSON
+        let metadata_ix = CreateMetadataAccountV3 {
+            metadata: self.metadata.key(),
+            mint: self.mint.key(),
+            mint_authority: self.ticket_authority.key(),
+            payer: self.payer.key(),
+            update_authority: (self.ticket_authority.key(), true),
+            system_program: self.system_program.key(),
+            rent: None,
+        }
+        .instruction(CreateMetadataAccountV3InstructionArgs {
+            data: DataV2 {
+                name,
+                symbol: "TIX".to_string(),
+                uri: String::new(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            is_mutable: true,
+            collection_details: Some(CollectionDetails::V1 { size: 0 }),
+        });
+        invoke_signed(
+            &metadata_ix,
+            &[
+                self.token_metadata_program.clone(),
+                self.metadata.clone(),
+                self.mint.clone(),
+                self.ticket_authority.clone(),
+                self.payer.clone(),
+                self.system_program.clone(),
+            ],
+            &[ticket_authority_seeds],
+        )?;
+
+        let master_edition_ix = CreateMasterEditionV3 {
+            edition: self.master_edition.key(),
+            mint: self.mint.key(),
+            update_authority: self.ticket_authority.key(),
+            mint_authority: self.ticket_authority.key(),
+            payer: self.payer.key(),
+            metadata: self.metadata.key(),
+            token_program: self.token_program.key(),
+            system_program: self.system_program.key(),
+            rent: None,
+        }
+        .instruction(CreateMasterEditionV3InstructionArgs { max_supply: Some(0) });
+        invoke_signed(
+            &master_edition_ix,
+            &[
+                self.token_metadata_program.clone(),
+                self.master_edition.clone(),
+                self.mint.clone(),
+                self.ticket_authority.clone(),
+                self.payer.clone(),
+                self.metadata.clone(),
+                self.token_program.clone(),
+                self.system_program.clone(),
+            ],
+            &[ticket_authority_seeds],
+        )?;
+
+        Ok(())
+    }
+
+    /// Verifies a ticket whose Metaplex `ticket_metadata` was created with this
+    /// collection, Metaplex checks the metadata names it.
+    pub fn verify(&self, ticket_metadata: AccountInfo<'info>) -> Result<()> {
+        let bump = [self.ticket_authority_bump];
+        let ix = VerifySizedCollectionItem {
+            metadata: ticket_metadata.key(),
+            collection_authority: self.ticket_authority.key(),
+            payer: self.payer.key(),
+            collection_mint: self.mint.key(),
+            collection: self.metadata.key(),
+            collection_master_edition_account: self.master_edition.key(),
+            collection_authority_record: None,
+        }
+        .instruction();
+        invoke_signed(
+            &ix,
+            &[
+                self.token_metadata_program.clone(),
+                ticket_metadata,
+                self.ticket_authority.clone(),
+                self.payer.clone(),
+                self.mint.clone(),
+                self.metadata.clone(),
+                self.master_edition.clone(),
+            ],
+            &[&[PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes(), &bump]],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Moves a ticket NFT between token accounts on behalf of the program.
+pub struct TicketTransfer<'info> {
+    pub token_program: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub from: AccountInfo<'info>,
+    pub to: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub ticket_authority: AccountInfo<'info>,
+    pub ticket_authority_bump: u8,
+}
+
+impl<'info> TicketTransfer<'info> {
+    /// `authority_seeds` signs for `authority` when it is a PDA, pass `&[]` for a wallet.
+    pub fn transfer(&self, authority_seeds: &[&[&[u8]]]) -> Result<()> {
+        let hooked = self.check_transferable()?;
+
+        // Token-2022 would CPI back into this program, which the runtime rejects as
+        // reentrancy, so the hook is switched off while the program moves the ticket
+        if hooked {
+            self.set_hook_program(None)?;
+        }
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TransferChecked {
+                    from: self.from.clone(),
+                    mint: self.mint.clone(),
+                    to: self.to.clone(),
+                    authority: self.authority.clone(),
+                },
+                authority_seeds,
+            ),
+            1,
+            0,
+        )?;
+
+        if hooked {
+            self.set_hook_program(Some(crate::ID))?;
+        }
+
+        Ok(())
+    }
+
+    /// Rejects soulbound mints and reports whether this program's transfer hook is active.
+    fn check_transferable(&self) -> Result<bool> {
+        if *self.mint.owner != spl_token_2022::ID {
+            return Ok(false);
+        }
+        let data = self.mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        require!(
+            mint.get_extension::<NonTransferable>().is_err(),
+            ErrorCode::TicketNonTransferable
+        );
+        Ok(transfer_hook::get_program_id(&mint) == Some(crate::ID))
+    }
+
+    fn set_hook_program(&self, program_id: Option<Pubkey>) -> Result<()> {
+        transfer_hook_update(
+            CpiContext::new_with_signer(
+                self.token_program.clone(),
+                TransferHookUpdate {
+                    token_program_id: self.token_program.clone(),
+                    mint: self.mint.clone(),
+                    authority: self.ticket_authority.clone(),
+                },
+                &[&[
+                    PROGRAM_SEED.as_bytes(),
+                    TICKET_AUTHORITY_SEED.as_bytes(),
+                    &[self.ticket_authority_bump],
+                ]],
+            ),
+            program_id,
+        )
+    }
+}
+
+/// Rewrites a ticket's metadata as the ticket authority PDA, which is the
+/// update authority of every ticket: on the mint itself for Token-2022
+/// tickets, in the Metaplex metadata account for legacy ones.
+pub struct TicketMetadataUpdate<'info> {
+    pub payer: AccountInfo<'info>, // funds the rent when Token-2022 metadata grows
+    pub mint: AccountInfo<'info>,
+    pub metadata: AccountInfo<'info>, // Metaplex metadata PDA, unused for Token-2022 mints
+    pub ticket_authority: AccountInfo<'info>,
+    pub ticket_authority_bump: u8,
+    pub token_program: AccountInfo<'info>,
+    pub token_metadata_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> TicketMetadataUpdate<'info> {
+    /// Renames the NFT and points it at `uri`, keeping the current URI when None.
+    pub fn update(&self, name: String, uri: Option<String>) -> Result<()> {
+        if let Some(uri) = &uri {
+            require!(uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::MetadataUriTooLong);
+        }
+        let bump = [self.ticket_authority_bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            PROGRAM_SEED.as_bytes(),
+            TICKET_AUTHORITY_SEED.as_bytes(),
+            &bump,
+        ]];
+
+        if *self.mint.owner == spl_token_2022::ID {
+            self.fund_token_metadata(&name, uri.as_deref())?;
+            let fields = [(Field::Name, Some(name)), (Field::Uri, uri)];
+            for (field, value) in fields {
+                let Some(value) = value else { continue };
+                token_metadata_update_field(
+                    CpiContext::new_with_signer(
+                        self.token_program.clone(),
+                        TokenMetadataUpdateField {
+                            program_id: self.token_program.clone(),
+                            metadata: self.mint.clone(),
+                            update_authority: self.ticket_authority.clone(),
+                        },
+                        signer_seeds,
+                    ),
+                    field,
+                    value,
+                )?;
+            }
+            return Ok(());
+        }
+
+        require_keys_eq!(
+            self.metadata.key(),
+            Metadata::find_pda(self.mint.key).0,
+            ErrorCode::InvalidInput
+        );
+        let metadata = Metadata::safe_deserialize(&self.metadata.try_borrow_data()?)
+            .map_err(|_| ErrorCode::InvalidInput)?;
+        require_keys_eq!(
+            metadata.update_authority,
+            self.ticket_authority.key(),
+            ErrorCode::MetadataAuthorityMismatch
+        );
+
+        // Metaplex replaces the whole data, everything but the name and URI is carried over
+        let ix = UpdateMetadataAccountV2 {
+            metadata: self.metadata.key(),
+            update_authority: self.ticket_authority.key(),
+        }
+        .instruction(UpdateMetadataAccountV2InstructionArgs {
+            data: Some(DataV2 {
+                name,
+                symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+                uri: uri.unwrap_or_else(|| metadata.uri.trim_end_matches('\0').to_string()),
+                seller_fee_basis_points: metadata.seller_fee_basis_points,
+                creators: metadata.creators,
+                collection: metadata.collection,
+                uses: metadata.uses,
+            }),
+            new_update_authority: None,
+            primary_sale_happened: None,
+            is_mutable: None,
+        });
+
+        invoke_signed(
+            &ix,
+            &[
+                self.token_metadata_program.clone(),
+                self.metadata.clone(),
+                self.ticket_authority.clone(),
+            ],
+            signer_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    /// Token-2022 reallocs the mint for longer metadata but leaves the rent to the caller.
+    fn fund_token_metadata(&self, name: &str, uri: Option<&str>) -> Result<()> {
+        let new_len = {
+            let data = self.mint.try_borrow_data()?;
+            let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+            let mut metadata = mint.get_variable_len_extension::<TokenMetadata>()?;
+            let old_size = metadata.tlv_size_of()?;
+            metadata.name = name.to_string();
+            if let Some(uri) = uri {
+                metadata.uri = uri.to_string();
+            }
+            data.len() - old_size + metadata.tlv_size_of()?
+        };
+
+        let shortfall = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(self.mint.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.clone(),
+                    system_program::Transfer {
+                        from: self.payer.clone(),
+                        to: self.mint.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+
+        Ok(())
+    }
+}
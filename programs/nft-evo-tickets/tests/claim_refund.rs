@@ -0,0 +1,254 @@
+//! Refunds of cancelled events and deleting the event once everyone is refunded.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, infos, seat_reservation_account, token_balance, Fixture, TestAccount,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    claim_refund_handler, delete_event_handler, ClaimRefundCtx, DeleteEventCtx, EventAccount,
+    EventStatus, TicketAccount,
+};
+
+const SOL: u64 = 1_000_000_000;
+
+/// Positions in `refund_accounts` that tests swap out.
+const REFUND_HOLDER: usize = 0;
+const REFUND_VAULT: usize = 2;
+const REFUND_TICKET: usize = 3;
+const REFUND_MINT: usize = 4;
+const REFUND_HOLDER_NFT_ACCOUNT: usize = 5;
+const REFUND_VAULT_PAYMENT: usize = 8;
+const REFUND_HOLDER_PAYMENT: usize = 9;
+const REFUND_SEAT_RESERVATION: usize = 10;
+
+/// Positions in `delete_accounts` that tests swap out.
+const DELETE_AUTHORITY: usize = 0;
+const DELETE_VAULT_PAYMENT: usize = 4;
+
+/// The cancelled event, priced in `payment_mint` when given.
+fn cancelled_event(fixture: &Fixture, payment_mint: Option<Pubkey>) -> EventAccount {
+    EventAccount {
+        status: EventStatus::Cancelled,
+        payment_mint,
+        ..fixture.event_state()
+    }
+}
+
+/// Accounts for `claim_refund` of `ticket` bought for 1 SOL, or 1 SOL worth of
+/// `payment_mint` base units when the event is token priced.
+fn refund_accounts(fixture: &Fixture, event: &EventAccount, ticket: &TicketAccount) -> Vec<TestAccount> {
+    let mut vault = fixture.vault_account();
+    let (vault_payment, holder_payment) = match event.payment_mint {
+        Some(payment_mint) => (
+            TestAccount::token(payment_mint, vault.key, 5 * SOL),
+            TestAccount::token(payment_mint, fixture.holder, 0),
+        ),
+        None => {
+            vault.lamports += 5 * SOL;
+            (
+                TestAccount::program(nft_evo_tickets::ID),
+                TestAccount::program(nft_evo_tickets::ID),
+            )
+        }
+    };
+    vec![
+        TestAccount::signer(fixture.holder),
+        fixture.event_account(event),
+        vault,
+        fixture.ticket_account(ticket),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.holder, 1),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(spl_token::ID),
+        vault_payment,
+        holder_payment,
+        TestAccount::program(nft_evo_tickets::ID),
+    ]
+}
+
+/// The fixture's ticket without a seat.
+fn unseated_ticket(fixture: &Fixture) -> TicketAccount {
+    TicketAccount {
+        seat: None,
+        ..fixture.ticket_state(false)
+    }
+}
+
+/// Claims the refund, returning the event's refund count afterwards.
+fn try_refund(accounts: &mut [TestAccount]) -> Result<u32> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = ClaimRefundCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    claim_refund_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps))?;
+    Ok(ctx_accounts.event_account.tickets_refunded)
+}
+
+/// Closes the ticket account as the refund transaction does on exit.
+fn close_ticket(accounts: &mut [TestAccount]) {
+    accounts[REFUND_HOLDER].lamports += accounts[REFUND_TICKET].lamports;
+    accounts[REFUND_TICKET].lamports = 0;
+    accounts[REFUND_TICKET].owner = System::id();
+    accounts[REFUND_TICKET].data.clear();
+}
+
+#[test]
+fn refund_pays_back_lamports_once() {
+    let fixture = Fixture::new();
+    let mut accounts = refund_accounts(
+        &fixture,
+        &cancelled_event(&fixture, None),
+        &unseated_ticket(&fixture),
+    );
+    let holder_lamports = accounts[REFUND_HOLDER].lamports;
+    let vault_lamports = accounts[REFUND_VAULT].lamports;
+    let nft_account_rent = accounts[REFUND_HOLDER_NFT_ACCOUNT].lamports;
+
+    assert_eq!(try_refund(&mut accounts), Ok(1));
+    assert_eq!(
+        accounts[REFUND_HOLDER].lamports - holder_lamports,
+        SOL + nft_account_rent
+    );
+    assert_eq!(vault_lamports - accounts[REFUND_VAULT].lamports, SOL);
+    // The NFT is burned and its token account closed
+    assert_eq!(accounts[REFUND_HOLDER_NFT_ACCOUNT].owner, System::id());
+    assert_eq!(
+        spl_token::state::Mint::unpack(&accounts[REFUND_MINT].data)
+            .unwrap()
+            .supply,
+        0
+    );
+
+    close_ticket(&mut accounts);
+    match try_refund(&mut accounts) {
+        Err(Error::AnchorError(e)) => assert_eq!(
+            e.error_code_number,
+            u32::from(anchor_lang::error::ErrorCode::AccountNotInitialized)
+        ),
+        other => panic!("expected the closed ticket to be rejected, got {other:?}"),
+    }
+}
+
+#[test]
+fn refund_pays_back_tokens_from_vault() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        tickets_refunded: 3,
+        ..cancelled_event(&fixture, Some(Pubkey::new_unique()))
+    };
+    let ticket = unseated_ticket(&fixture);
+    let mut accounts = refund_accounts(&fixture, &event, &ticket);
+    let vault_lamports = accounts[REFUND_VAULT].lamports;
+
+    assert_eq!(try_refund(&mut accounts), Ok(4));
+    assert_eq!(accounts[REFUND_VAULT].lamports, vault_lamports);
+    assert_eq!(token_balance(&accounts[REFUND_VAULT_PAYMENT]), 4 * SOL);
+    assert_eq!(token_balance(&accounts[REFUND_HOLDER_PAYMENT]), SOL);
+
+    let mut missing_payment_accounts = refund_accounts(&fixture, &event, &ticket);
+    missing_payment_accounts[REFUND_VAULT_PAYMENT] = TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_refund(&mut missing_payment_accounts), ErrorCode::MissingPaymentAccounts);
+}
+
+#[test]
+fn refund_requires_seat_reservation() {
+    let fixture = Fixture::new();
+    let event = cancelled_event(&fixture, None);
+    let ticket = fixture.ticket_state(false);
+    let seat = ticket.seat.clone().unwrap();
+
+    let mut accounts = refund_accounts(&fixture, &event, &ticket);
+    assert_error(try_refund(&mut accounts), ErrorCode::SeatReservationMismatch);
+
+    let mut accounts = refund_accounts(&fixture, &event, &ticket);
+    accounts[REFUND_SEAT_RESERVATION] =
+        seat_reservation_account(fixture.event, &seat, Pubkey::new_unique());
+    assert_error(try_refund(&mut accounts), ErrorCode::SeatReservationMismatch);
+
+    let mut accounts = refund_accounts(&fixture, &event, &ticket);
+    accounts[REFUND_SEAT_RESERVATION] = seat_reservation_account(fixture.event, &seat, fixture.ticket);
+    assert_eq!(try_refund(&mut accounts), Ok(1));
+}
+
+/// Accounts for `delete_event` of a refunded event, with the vault's token
+/// account holding `vault_balance` of `payment_mint`.
+fn delete_accounts(
+    fixture: &Fixture,
+    payment_mint: Option<Pubkey>,
+    vault_balance: u64,
+) -> Vec<TestAccount> {
+    let event = EventAccount {
+        tickets_refunded: 1,
+        ..cancelled_event(fixture, payment_mint)
+    };
+    let vault = fixture.vault_account();
+    let (token_program, vault_payment) = match payment_mint {
+        Some(payment_mint) => (
+            TestAccount::program(spl_token::ID),
+            TestAccount::token(payment_mint, vault.key, vault_balance),
+        ),
+        None => (
+            TestAccount::program(nft_evo_tickets::ID),
+            TestAccount::program(nft_evo_tickets::ID),
+        ),
+    };
+    vec![
+        TestAccount::signer(fixture.authority),
+        fixture.event_account(&event),
+        vault,
+        token_program,
+        vault_payment,
+    ]
+}
+
+fn try_delete(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = DeleteEventCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &1u64.to_le_bytes(),
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    delete_event_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps), 1)
+}
+
+#[test]
+fn delete_closes_empty_vault_token_account() {
+    let fixture = Fixture::new();
+    let payment_mint = Pubkey::new_unique();
+    let mut accounts = delete_accounts(&fixture, None, 0);
+    assert_eq!(try_delete(&mut accounts), Ok(()));
+
+    let mut accounts = delete_accounts(&fixture, Some(payment_mint), 0);
+    let authority_lamports = accounts[DELETE_AUTHORITY].lamports;
+    let token_account_rent = accounts[DELETE_VAULT_PAYMENT].lamports;
+    assert_eq!(try_delete(&mut accounts), Ok(()));
+    assert_eq!(accounts[DELETE_VAULT_PAYMENT].owner, System::id());
+    assert_eq!(
+        accounts[DELETE_AUTHORITY].lamports - authority_lamports,
+        token_account_rent
+    );
+
+    let mut accounts = delete_accounts(&fixture, Some(payment_mint), 1);
+    assert_error(try_delete(&mut accounts), ErrorCode::VaultNotEmpty);
+
+    let mut accounts = delete_accounts(&fixture, Some(payment_mint), 0);
+    accounts[DELETE_VAULT_PAYMENT] = TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_delete(&mut accounts), ErrorCode::MissingPaymentAccounts);
+}
@@ -0,0 +1,863 @@
+//! Shared fixtures for the account validation tests.
+//!
+//! These tests drive `Accounts::try_accounts` directly against hand-built
+//! `AccountInfo`s, so every `#[account(...)]` constraint is exercised exactly
+//! as it would be on-chain without needing an SBF build or a validator.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::{IsInitialized, Pack};
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::error::TokenError;
+use anchor_spl::token::spl_token::instruction::TokenInstruction;
+
+use nft_evo_tickets::constants::{
+    CONFIG_SEED, EVENT_SEED, LISTING_SEED, METADATA_CONFIG_SEED, PROGRAM_SEED, PURCHASES_SEED,
+    SEAT_SEED, SELLER_LISTINGS_SEED, TICKET_AUTHORITY_SEED, TIER_SEED, VAULT_SEED,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    buy_event_ticket_handler, AllowlistClaims, AllowlistProof, BuyEventTicketCtx, BuyerPurchases, EventAccount, EventStatus,
+    EventMetadataConfig, EventVault, ProgramConfig, SeatReservation, SellerListings,
+    StageUriTemplates, TicketAccount, TicketMintMode, TicketStage,
+    TicketTier, TransferPolicy,
+};
+
+/// Unix timestamp reported by the stubbed Clock sysvar.
+pub const NOW: i64 = 1_700_000_000;
+
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+    pub is_writable: bool,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    pub fn signer(key: Pubkey) -> Self {
+        Self {
+            key,
+            owner: System::id(),
+            lamports: 10_000_000_000,
+            data: vec![],
+            is_signer: true,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    pub fn wallet(key: Pubkey) -> Self {
+        Self {
+            is_signer: false,
+            ..Self::signer(key)
+        }
+    }
+
+    /// An address that does not exist yet, sized so `init` can allocate into it.
+    pub fn uninitialized(key: Pubkey, space: usize) -> Self {
+        Self {
+            key,
+            owner: System::id(),
+            lamports: 0,
+            data: vec![0; space],
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    pub fn program(id: Pubkey) -> Self {
+        Self {
+            key: id,
+            owner: anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+            lamports: 1,
+            data: vec![],
+            is_signer: false,
+            is_writable: false,
+            executable: true,
+        }
+    }
+
+    pub fn rent_sysvar() -> Self {
+        let rent = Rent::default();
+        let mut data = Vec::with_capacity(17);
+        data.extend_from_slice(&rent.lamports_per_byte_year.to_le_bytes());
+        data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+        data.push(rent.burn_percent);
+        Self {
+            key: sysvar::rent::ID,
+            owner: sysvar::ID,
+            lamports: 1,
+            data,
+            is_signer: false,
+            is_writable: false,
+            executable: false,
+        }
+    }
+
+    pub fn anchor<T: AccountSerialize>(key: Pubkey, account: &T, space: usize) -> Self {
+        let mut data = Vec::with_capacity(space);
+        account.try_serialize(&mut data).unwrap();
+        data.resize(space.max(data.len()), 0);
+        Self {
+            key,
+            owner: nft_evo_tickets::ID,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    pub fn mint(key: Pubkey, authority: Pubkey) -> Self {
+        let mint = spl_token::state::Mint {
+            mint_authority: COption::Some(authority),
+            supply: 1,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::Some(authority),
+        };
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        Self {
+            key,
+            owner: spl_token::ID,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    /// The associated token account of `authority` for `mint`.
+    pub fn token(mint: Pubkey, authority: Pubkey, amount: u64) -> Self {
+        let account = spl_token::state::Account {
+            mint,
+            owner: authority,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+        Self {
+            key: anchor_spl::associated_token::get_associated_token_address(&authority, &mint),
+            owner: spl_token::ID,
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            is_signer: false,
+            is_writable: true,
+            executable: false,
+        }
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+pub fn infos(accounts: &mut [TestAccount]) -> Vec<AccountInfo<'_>> {
+    accounts.iter_mut().map(TestAccount::info).collect()
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &nft_evo_tickets::ID).0
+}
+
+pub fn bump(seeds: &[&[u8]]) -> u8 {
+    Pubkey::find_program_address(seeds, &nft_evo_tickets::ID).1
+}
+
+pub fn event_pda(event_id: u64) -> Pubkey {
+    pda(&[PROGRAM_SEED.as_bytes(), EVENT_SEED.as_bytes(), &event_id.to_le_bytes()])
+}
+
+/// The platform treasury of the test program config.
+pub fn treasury() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+/// The unpaused program config: 5% marketplace fee, royalties capped at 10%.
+pub fn config_account() -> TestAccount {
+    program_config(false)
+}
+
+/// `config_account` with the program paused.
+pub fn paused_config_account() -> TestAccount {
+    program_config(true)
+}
+
+fn program_config(is_paused: bool) -> TestAccount {
+    let seeds: &[&[u8]] = &[PROGRAM_SEED.as_bytes(), CONFIG_SEED.as_bytes()];
+    let config = ProgramConfig {
+        admin: Pubkey::new_unique(),
+        pending_admin: None,
+        treasury: treasury(),
+        marketplace_fee_bps: 500,
+        max_royalty_bps: 1000,
+        dispute_window_secs: 0,
+        is_paused,
+        bump: bump(seeds),
+    };
+    TestAccount::anchor(pda(seeds), &config, 8 + ProgramConfig::INIT_SPACE)
+}
+
+/// The event's vault before any withdrawal or auction sale.
+pub fn vault_state(event: Pubkey) -> EventVault {
+    let seeds: &[&[u8]] = &[PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event.as_ref()];
+    EventVault {
+        event,
+        total_withdrawn: 0,
+        unrebated_auction_sales: 0,
+        unrebated_auction_proceeds: 0,
+        bump: bump(seeds),
+    }
+}
+
+/// `vault`, stored at its event's vault address and holding nothing but its rent.
+pub fn vault_state_account(vault: &EventVault) -> TestAccount {
+    let seeds: &[&[u8]] = &[PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), vault.event.as_ref()];
+    TestAccount::anchor(pda(seeds), vault, 8 + EventVault::INIT_SPACE)
+}
+
+/// The event's vault, holding nothing but its rent.
+pub fn vault_account(event: Pubkey) -> TestAccount {
+    vault_state_account(&vault_state(event))
+}
+
+/// One template for every stage, what `buy_accounts` gives events on sale.
+pub fn stage_templates() -> StageUriTemplates {
+    let template = "https://cdn.test/{event_id}/{serial}/{stage}.json".to_string();
+    StageUriTemplates {
+        prestige: template.clone(),
+        qr: template.clone(),
+        scanned: template.clone(),
+        collectible: template,
+    }
+}
+
+/// The event's `EventMetadataConfig` holding `templates`.
+pub fn metadata_config_account(event: Pubkey, templates: StageUriTemplates) -> TestAccount {
+    let seeds: &[&[u8]] = &[PROGRAM_SEED.as_bytes(), METADATA_CONFIG_SEED.as_bytes(), event.as_ref()];
+    let config = EventMetadataConfig {
+        event,
+        templates,
+        bump: bump(seeds),
+    };
+    TestAccount::anchor(pda(seeds), &config, 8 + EventMetadataConfig::INIT_SPACE)
+}
+
+pub fn seat_reservation_key(event: Pubkey, seat: &str) -> Pubkey {
+    pda(&[PROGRAM_SEED.as_bytes(), SEAT_SEED.as_bytes(), event.as_ref(), seat.as_bytes()])
+}
+
+/// `seat` of `event`, reserved for `ticket`.
+pub fn seat_reservation_account(event: Pubkey, seat: &str, ticket: Pubkey) -> TestAccount {
+    let reservation = SeatReservation {
+        event,
+        ticket,
+        bump: bump(&[PROGRAM_SEED.as_bytes(), SEAT_SEED.as_bytes(), event.as_ref(), seat.as_bytes()]),
+    };
+    TestAccount::anchor(
+        seat_reservation_key(event, seat),
+        &reservation,
+        8 + SeatReservation::INIT_SPACE,
+    )
+}
+
+/// `seat` of `event`, not reserved yet.
+pub fn free_seat_account(event: Pubkey, seat: &str) -> TestAccount {
+    TestAccount::uninitialized(seat_reservation_key(event, seat), 8 + SeatReservation::INIT_SPACE)
+}
+
+pub fn event_account(key: Pubkey, event_id: u64, authority: Pubkey) -> TestAccount {
+    TestAccount::anchor(key, &event_state(event_id, authority), 8 + EventAccount::INIT_SPACE)
+}
+
+/// An active event that has already ended, with no transfer restrictions.
+pub fn event_state(event_id: u64, authority: Pubkey) -> EventAccount {
+    EventAccount {
+        authority,
+        scanner: authority,
+        event_id,
+        name: "Test Event".to_string(),
+        start_ts: 0,
+        end_ts: 1,
+        tickets_sold: 1,
+        ticket_supply: 100,
+        version: 2,
+        cover_image_url: String::new(),
+        tier_count: 1,
+        tiered_supply: 100,
+        is_paused: false,
+        status: EventStatus::Active,
+        tickets_refunded: 0,
+        payment_mint: None,
+        ticket_mint_mode: TicketMintMode::Legacy,
+        transfer_policy: TransferPolicy::Allowed,
+        max_transfers_per_ticket: None,
+        max_resale_markup_bps: None,
+        resale_cooldown_secs: None,
+        max_listings_per_wallet: None,
+        royalty_bps: 0,
+        royalty_recipients: vec![],
+        max_tickets_per_wallet: None,
+        section_count: 0,
+        sale_schedule: None,
+        dutch_auction: None,
+        auction_clearing_price_lamports: 0,
+        has_metadata_config: false,
+        collection_mint: None,
+        merkle_tree: None,
+        bump: bump(&[PROGRAM_SEED.as_bytes(), EVENT_SEED.as_bytes(), &event_id.to_le_bytes()]),
+    }
+}
+
+/// A QR-stage ticket for `nft_mint`, as recorded by the program.
+pub fn ticket_account(
+    key: Pubkey,
+    event: Pubkey,
+    owner: Pubkey,
+    nft_mint: Pubkey,
+    is_listed: bool,
+) -> TestAccount {
+    let ticket = ticket_state(event, owner, nft_mint, is_listed);
+    TestAccount::anchor(key, &ticket, 8 + TicketAccount::INIT_SPACE)
+}
+
+/// A QR-stage ticket bought for 1 SOL well before `NOW`.
+pub fn ticket_state(
+    event: Pubkey,
+    owner: Pubkey,
+    nft_mint: Pubkey,
+    is_listed: bool,
+) -> TicketAccount {
+    TicketAccount {
+        event,
+        owner,
+        nft_mint,
+        seat: Some("A1".to_string()),
+        tier_index: Some(0),
+        price_paid_lamports: 1_000_000_000,
+        bought_at_auction: false,
+        stage: TicketStage::Qr,
+        is_listed,
+        was_scanned: false,
+        listing_price: None,
+        listing_expires_at: None,
+        transfer_count: 0,
+        acquired_at: 0,
+        serial: 1,
+        bump: 255,
+    }
+}
+
+/// The program-wide PDA that owns transfer hooks and Token-2022 metadata.
+pub fn ticket_authority() -> TestAccount {
+    TestAccount::wallet(pda(&[PROGRAM_SEED.as_bytes(), TICKET_AUTHORITY_SEED.as_bytes()]))
+}
+
+/// The parties and addresses a test works with: event 1 run by `authority` and
+/// checked in by `scanner`, and `ticket` with its NFT `mint`, held by `holder` and
+/// wanted by `buyer`. Every instruction's account list is built from these by the test file exercising it.
+pub struct Fixture {
+    pub authority: Pubkey,
+    pub scanner: Pubkey,
+    pub holder: Pubkey,
+    pub buyer: Pubkey,
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Fixture {
+    /// Installs the syscall stubs and picks fresh addresses.
+    pub fn new() -> Self {
+        install_stubs();
+        Self {
+            authority: Pubkey::new_unique(),
+            scanner: Pubkey::new_unique(),
+            holder: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            event: event_pda(1),
+            ticket: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+        }
+    }
+
+    /// `event_state` of the fixture's event, with its own scanner.
+    pub fn event_state(&self) -> EventAccount {
+        EventAccount {
+            scanner: self.scanner,
+            ..event_state(1, self.authority)
+        }
+    }
+
+    /// `event`, stored at the fixture's event address.
+    pub fn event_account(&self, event: &EventAccount) -> TestAccount {
+        TestAccount::anchor(self.event, event, 8 + EventAccount::INIT_SPACE)
+    }
+
+    pub fn vault_account(&self) -> TestAccount {
+        vault_account(self.event)
+    }
+
+    /// `ticket_state` of the fixture's ticket, held by `holder`.
+    pub fn ticket_state(&self, is_listed: bool) -> TicketAccount {
+        ticket_state(self.event, self.holder, self.mint, is_listed)
+    }
+
+    /// `ticket`, stored at the fixture's ticket address.
+    pub fn ticket_account(&self, ticket: &TicketAccount) -> TestAccount {
+        TestAccount::anchor(self.ticket, ticket, 8 + TicketAccount::INIT_SPACE)
+    }
+
+    /// The ticket's NFT mint, with the ticket account as its authority.
+    pub fn nft_mint(&self) -> TestAccount {
+        TestAccount::mint(self.mint, self.ticket)
+    }
+
+    /// `owner`'s associated token account for the ticket NFT.
+    pub fn nft_account(&self, owner: Pubkey, amount: u64) -> TestAccount {
+        TestAccount::token(self.mint, owner, amount)
+    }
+
+    /// The ticket's listing PDA, which also escrows the NFT while listed.
+    pub fn listing(&self) -> Pubkey {
+        pda(&[PROGRAM_SEED.as_bytes(), LISTING_SEED.as_bytes(), self.ticket.as_ref()])
+    }
+
+    /// `holder`'s open listing count for the event, or the counter their first
+    /// listing creates when `active_listings` is None.
+    pub fn seller_listings_account(&self, active_listings: Option<u16>) -> TestAccount {
+        let seeds: &[&[u8]] = &[
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            self.event.as_ref(),
+            self.holder.as_ref(),
+        ];
+        match active_listings {
+            Some(active_listings) => {
+                let seller_listings = SellerListings {
+                    event: self.event,
+                    seller: self.holder,
+                    active_listings,
+                    bump: bump(seeds),
+                };
+                TestAccount::anchor(pda(seeds), &seller_listings, 8 + SellerListings::INIT_SPACE)
+            }
+            None => TestAccount::uninitialized(pda(seeds), 8 + SellerListings::INIT_SPACE),
+        }
+    }
+}
+
+/// Positions in `buy_accounts` that tests swap out.
+pub const BUY_EVENT: usize = 2;
+pub const BUY_VAULT: usize = 4;
+pub const BUY_PURCHASES: usize = 5;
+pub const BUY_TICKET: usize = 6;
+pub const BUY_METADATA: usize = 9;
+pub const BUY_SEAT_SECTION: usize = 21;
+pub const BUY_SEAT_RESERVATION: usize = 22;
+pub const BUY_ALLOWLIST_ENTRY: usize = 23;
+pub const BUY_MERKLE_ALLOWLIST: usize = 24;
+pub const BUY_ALLOWLIST_CLAIMS: usize = 25;
+pub const BUY_METADATA_CONFIG: usize = 26;
+pub const BUY_COLLECTION_MINT: usize = 27;
+pub const BUY_COLLECTION_METADATA: usize = 28;
+pub const BUY_COLLECTION_MASTER_EDITION: usize = 29;
+
+/// Accounts for `buy_event_ticket` of tier 0 (1 SOL, 10 tickets) by `buyer` in a
+/// SOL-priced legacy event, as the event's next serial. Tickets only sell once the
+/// event has its metadata config, so it gets `stage_templates()`. Other optional
+/// accounts are left out.
+pub fn buy_accounts(buyer: Pubkey, event: &EventAccount) -> Vec<TestAccount> {
+    let event_key = event_pda(event.event_id);
+    let event = &EventAccount {
+        has_metadata_config: true,
+        ..event.clone()
+    };
+    let tier_seeds: &[&[u8]] = &[
+        PROGRAM_SEED.as_bytes(),
+        TIER_SEED.as_bytes(),
+        event_key.as_ref(),
+        &[0],
+    ];
+    let tier = TicketTier {
+        event: event_key,
+        tier_index: 0,
+        name: "GA".to_string(),
+        price_lamports: 1_000_000_000,
+        supply: 10,
+        sold: 0,
+        bump: bump(tier_seeds),
+    };
+    let purchases = pda(&[
+        PROGRAM_SEED.as_bytes(),
+        PURCHASES_SEED.as_bytes(),
+        event_key.as_ref(),
+        buyer.as_ref(),
+    ]);
+    let (ticket, _) = TicketAccount::pda(&event_key, event.next_ticket_serial());
+    let (mint, _) = TicketAccount::nft_mint_pda(&event_key, event.next_ticket_serial());
+    let none = || TestAccount::program(nft_evo_tickets::ID);
+
+    vec![
+        TestAccount::signer(buyer),
+        config_account(),
+        TestAccount::anchor(event_key, event, 8 + EventAccount::INIT_SPACE),
+        TestAccount::anchor(pda(tier_seeds), &tier, 8 + TicketTier::INIT_SPACE),
+        vault_account(event_key),
+        TestAccount::uninitialized(purchases, 8 + BuyerPurchases::INIT_SPACE),
+        TestAccount::uninitialized(ticket, 8 + TicketAccount::INIT_SPACE),
+        TestAccount::uninitialized(mint, spl_token::state::Mint::LEN),
+        ticket_authority(),
+        TestAccount::wallet(Pubkey::new_unique()), // metadata
+        TestAccount::wallet(Pubkey::new_unique()), // master edition
+        TestAccount::uninitialized(
+            get_associated_token_address(&buyer, &mint),
+            spl_token::state::Account::LEN,
+        ),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+        TestAccount::program(mpl_token_metadata::ID),
+        TestAccount::rent_sysvar(),
+        none(), // buyer payment account
+        none(), // vault payment account
+        none(), // extra account metas
+        none(), // seat section
+        none(), // seat reservation
+        none(), // allowlist entry
+        none(), // merkle allowlist
+        none(), // allowlist claims
+        metadata_config_account(event_key, stage_templates()),
+        none(), // collection mint
+        none(), // collection metadata
+        none(), // collection master edition
+    ]
+}
+
+/// State left behind by a successful `buy_event_ticket`.
+pub struct Bought {
+    pub ticket: TicketAccount,
+    pub tickets_bought: u16,
+    pub seat_reservation: Option<SeatReservation>,
+    pub allowlist_claims: Option<AllowlistClaims>,
+}
+
+/// Runs account validation and the `buy_event_ticket` handler for tier 0.
+pub fn try_buy(accounts: &mut [TestAccount], seat: Option<&str>) -> Result<Bought> {
+    try_buy_with_proof(accounts, seat, None)
+}
+
+/// `try_buy`, presenting a Merkle allowlist proof.
+pub fn try_buy_with_proof(
+    accounts: &mut [TestAccount],
+    seat: Option<&str>,
+    allowlist_proof: Option<AllowlistProof>,
+) -> Result<Bought> {
+    let seat = seat.map(str::to_string);
+    let mut ix_data = vec![0]; // tier_index: 0
+    seat.serialize(&mut ix_data).unwrap();
+    allowlist_proof.serialize(&mut ix_data).unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = BuyEventTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut std::collections::BTreeSet::new(),
+    )?;
+    buy_event_ticket_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        0,
+        seat,
+        allowlist_proof,
+    )?;
+    Ok(Bought {
+        ticket: (*ctx_accounts.ticket_account).clone(),
+        tickets_bought: ctx_accounts.buyer_purchases.tickets_bought,
+        seat_reservation: ctx_accounts.seat_reservation.as_deref().cloned(),
+        allowlist_claims: ctx_accounts.allowlist_claims.as_deref().map(|claims| (**claims).clone()),
+    })
+}
+
+pub fn assert_error<T>(result: Result<T>, expected: ErrorCode) {
+    match result {
+        Ok(_) => panic!("expected {expected:?}, but the accounts were accepted"),
+        Err(Error::AnchorError(e)) => assert_eq!(
+            e.error_code_number,
+            u32::from(expected),
+            "expected {expected:?}, got {}",
+            e.error_name
+        ),
+        Err(e) => panic!("expected {expected:?}, got {e:?}"),
+    }
+}
+
+const CREATE_ACCOUNT: u8 = 0;
+const ASSIGN: u8 = 1;
+const TRANSFER: u8 = 2;
+const ALLOCATE: u8 = 8;
+const ACCOUNT_ALREADY_IN_USE: u32 = 0;
+
+thread_local! {
+    static INVOKED: RefCell<Vec<Instruction>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes the CPIs made on this test's thread so far, in order.
+pub fn take_invoked() -> Vec<Instruction> {
+    INVOKED.with(|invoked| invoked.take())
+}
+
+/// Off-chain syscalls: a fixed Rent/Clock, a System program that can create
+/// accounts and move lamports, and the SPL Token and Associated Token programs
+/// so handlers run end to end and tests can check the balances they leave.
+/// Like the real ones they refuse to reuse accounts that are already in use and
+/// to overdraw a token account, but they don't check authorities or signers.
+/// Every CPI is recorded for `take_invoked`, other programs' CPIs are no-ops.
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        INVOKED.with(|invoked| invoked.borrow_mut().push(instruction.clone()));
+        if instruction.program_id == spl_token::ID {
+            return invoke_token(instruction, account_infos);
+        }
+        if instruction.program_id == anchor_spl::associated_token::ID {
+            return invoke_associated_token(instruction, account_infos);
+        }
+
+        // Only these System instructions are emulated
+        let data = &instruction.data;
+        if instruction.program_id != System::id()
+            || !matches!(data.first(), Some(&CREATE_ACCOUNT | &ASSIGN | &TRANSFER | &ALLOCATE))
+        {
+            return Ok(());
+        }
+        let find = |key: &Pubkey| account_infos.iter().find(|a| a.key == key).unwrap();
+        if data[0] == ASSIGN || data[0] == ALLOCATE {
+            let account = find(&instruction.accounts[0].pubkey);
+            if account.owner != &System::id() {
+                return Err(ProgramError::Custom(ACCOUNT_ALREADY_IN_USE));
+            }
+            if data[0] == ASSIGN {
+                account.assign(&Pubkey::try_from(&data[4..36]).unwrap());
+            }
+            return Ok(());
+        }
+        let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+
+        let from = find(&instruction.accounts[0].pubkey);
+        let to = find(&instruction.accounts[1].pubkey);
+        **from.try_borrow_mut_lamports()? -= lamports;
+        **to.try_borrow_mut_lamports()? += lamports;
+        if data[0] == CREATE_ACCOUNT {
+            to.assign(&Pubkey::try_from(&data[20..52]).unwrap());
+        }
+        Ok(())
+    }
+}
+
+fn find<'a, 'info>(account_infos: &'a [AccountInfo<'info>], key: &Pubkey) -> &'a AccountInfo<'info> {
+    account_infos.iter().find(|a| a.key == key).unwrap()
+}
+
+fn unpack<T: Pack + IsInitialized>(info: &AccountInfo) -> std::result::Result<T, ProgramError> {
+    if info.owner != &spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    T::unpack(&info.try_borrow_data()?)
+}
+
+fn pack<T: Pack>(state: T, info: &AccountInfo) -> ProgramResult {
+    T::pack(state, &mut info.try_borrow_mut_data()?)
+}
+
+/// Adds `amount` to the token account's balance, or takes it when negative.
+fn credit(info: &AccountInfo, mint: &Pubkey, amount: i128) -> ProgramResult {
+    let mut account: spl_token::state::Account = unpack(info)?;
+    if &account.mint != mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    account.amount = u64::try_from(i128::from(account.amount) + amount)
+        .map_err(|_| ProgramError::from(TokenError::InsufficientFunds))?;
+    pack(account, info)
+}
+
+fn change_supply(info: &AccountInfo, amount: i128) -> ProgramResult {
+    let mut mint: spl_token::state::Mint = unpack(info)?;
+    mint.supply = u64::try_from(i128::from(mint.supply) + amount).unwrap();
+    pack(mint, info)
+}
+
+fn init_token_account(info: &AccountInfo, mint: &Pubkey, owner: &Pubkey) -> ProgramResult {
+    if info.owner != &spl_token::ID || info.data_len() != spl_token::state::Account::LEN {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if spl_token::state::Account::unpack_unchecked(&info.try_borrow_data()?)?.is_initialized() {
+        return Err(TokenError::AlreadyInUse.into());
+    }
+    let account = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    pack(account, info)
+}
+
+fn invoke_token(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    let account = |index: usize| find(account_infos, &instruction.accounts[index].pubkey);
+    match TokenInstruction::unpack(&instruction.data)? {
+        TokenInstruction::InitializeMint {
+            decimals,
+            mint_authority,
+            freeze_authority,
+        }
+        | TokenInstruction::InitializeMint2 {
+            decimals,
+            mint_authority,
+            freeze_authority,
+        } => {
+            let info = account(0);
+            if info.owner != &spl_token::ID || info.data_len() != spl_token::state::Mint::LEN {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let mint = spl_token::state::Mint {
+                mint_authority: COption::Some(mint_authority),
+                supply: 0,
+                decimals,
+                is_initialized: true,
+                freeze_authority,
+            };
+            pack(mint, info)
+        }
+        TokenInstruction::InitializeAccount => {
+            init_token_account(account(0), account(1).key, account(2).key)
+        }
+        TokenInstruction::InitializeAccount2 { owner }
+        | TokenInstruction::InitializeAccount3 { owner } => {
+            init_token_account(account(0), account(1).key, &owner)
+        }
+        TokenInstruction::Transfer { amount } => {
+            let mint = unpack::<spl_token::state::Account>(account(0))?.mint;
+            credit(account(0), &mint, -i128::from(amount))?;
+            credit(account(1), &mint, i128::from(amount))
+        }
+        TokenInstruction::TransferChecked { amount, .. } => {
+            credit(account(0), account(1).key, -i128::from(amount))?;
+            credit(account(2), account(1).key, i128::from(amount))
+        }
+        TokenInstruction::MintTo { amount } | TokenInstruction::MintToChecked { amount, .. } => {
+            change_supply(account(0), i128::from(amount))?;
+            credit(account(1), account(0).key, i128::from(amount))
+        }
+        TokenInstruction::Burn { amount } | TokenInstruction::BurnChecked { amount, .. } => {
+            credit(account(0), account(1).key, -i128::from(amount))?;
+            change_supply(account(1), -i128::from(amount))
+        }
+        TokenInstruction::CloseAccount => {
+            let (info, destination) = (account(0), account(1));
+            if unpack::<spl_token::state::Account>(info)?.amount != 0 {
+                return Err(TokenError::NonNativeHasBalance.into());
+            }
+            **destination.try_borrow_mut_lamports()? += info.lamports();
+            **info.try_borrow_mut_lamports()? = 0;
+            info.assign(&System::id());
+            info.try_borrow_mut_data()?.fill(0);
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+const CREATE_IDEMPOTENT: u8 = 1;
+
+/// Creates the associated token account of `instruction.accounts[2]` for the mint
+/// at `instruction.accounts[3]`, paid for by `instruction.accounts[0]`.
+fn invoke_associated_token(instruction: &Instruction, account_infos: &[AccountInfo]) -> ProgramResult {
+    let account = |index: usize| find(account_infos, &instruction.accounts[index].pubkey);
+    let (payer, info, wallet, mint) = (account(0), account(1), account(2), account(3));
+    if account(5).key != &spl_token::ID {
+        return Ok(());
+    }
+    if info.owner == &spl_token::ID && instruction.data.first() == Some(&CREATE_IDEMPOTENT) {
+        return Ok(());
+    }
+    if info.owner != &System::id() {
+        return Err(ProgramError::Custom(ACCOUNT_ALREADY_IN_USE));
+    }
+    let rent = Rent::default()
+        .minimum_balance(spl_token::state::Account::LEN)
+        .saturating_sub(info.lamports());
+    **payer.try_borrow_mut_lamports()? -= rent;
+    **info.try_borrow_mut_lamports()? += rent;
+    info.assign(&spl_token::ID);
+    init_token_account(info, mint.key, wallet.key)
+}
+
+/// The balance of the SPL token account `account`.
+pub fn token_balance(account: &TestAccount) -> u64 {
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub fn install_stubs() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        set_syscall_stubs(Box::new(Stubs));
+    });
+}
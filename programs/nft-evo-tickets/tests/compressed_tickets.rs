@@ -0,0 +1,926 @@
+//! Compressed tickets: Bubblegum leaves in the event's Merkle tree, sold,
+//! scanned and resold from their leaf instead of a TicketAccount and mint.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, buy_accounts, bump, config_account, event_pda, free_seat_account, infos,
+    metadata_config_account, pda, seat_reservation_account, stage_templates, take_invoked,
+    ticket_authority, treasury, try_buy, Fixture, TestAccount, BUY_TICKET, BUY_VAULT,
+};
+use nft_evo_tickets::compressed_ticket::{
+    asset_id, tree_config, CompressedCreator, CompressedMetadata, CompressedTicket,
+    ACCOUNT_COMPRESSION_PROGRAM_ID, BUBBLEGUM_PROGRAM_ID, NOOP_PROGRAM_ID,
+};
+use nft_evo_tickets::constants::{LISTING_SEED, PROGRAM_SEED, SELLER_LISTINGS_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    buy_compressed_listing_handler, buy_compressed_ticket_handler, cancel_compressed_listing_handler,
+    cancel_event_handler, create_ticket_tree_handler, list_compressed_ticket_handler,
+    set_dutch_auction_handler, set_ticket_mint_mode_handler, set_transfer_policy_handler,
+    update_compressed_ticket_handler, AllowlistProof, BuyCompressedListingCtx, BuyCompressedTicketCtx,
+    CancelCompressedListingCtx, CancelEventCtx, CreateTicketTreeCtx, DutchAuction, EventAccount,
+    ListCompressedTicketCtx, ListingAccount, SeatReservation, SellerListings, SetDutchAuctionCtx,
+    SetTicketMintModeCtx, SetTransferPolicyCtx, StageUriTemplates, TicketMintMode, TicketStage,
+    TransferPolicy, UpdateCompressedTicketCtx,
+};
+
+const CREATE_TREE: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+const MINT_V1: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+const TRANSFER: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+const UPDATE_METADATA: [u8; 8] = [170, 182, 43, 239, 97, 78, 225, 186];
+const PRICE: u64 = 2_000_000_000;
+
+/// Positions in the account lists below that tests swap out or check.
+const CREATE_TREE_TREE: usize = 2;
+const BUY_COMPRESSED_TREE: usize = 6;
+const BUY_COMPRESSED_SEAT_RESERVATION: usize = 17;
+const BUY_COMPRESSED_METADATA_CONFIG: usize = 21;
+const UPDATE_SEAT_RESERVATION: usize = 15;
+const UPDATE_METADATA_CONFIG: usize = 16;
+const UPDATE_PROOF: usize = 17;
+const BUY_LISTING_TREASURY: usize = 5;
+const BUY_LISTING_SELLER: usize = 6;
+const BUY_LISTING_PROOF: usize = 18;
+
+/// Bubblegum `transfer` data: root, data hash, creator hash, nonce and index.
+type TransferArgs = ([u8; 32], [u8; 32], [u8; 32], u64, u32);
+
+/// Bubblegum `update_metadata` data, update args as (name, symbol, uri,
+/// creators, seller fee, primary sale happened, is mutable).
+type UpdateMetadataArgs = (
+    [u8; 32],
+    u64,
+    u32,
+    CompressedMetadata,
+    (
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<Vec<CompressedCreator>>,
+        Option<u16>,
+        Option<bool>,
+        Option<bool>,
+    ),
+);
+
+fn bubblegum(invoked: &[Instruction]) -> Vec<&Instruction> {
+    invoked
+        .iter()
+        .filter(|ix| ix.program_id == BUBBLEGUM_PROGRAM_ID)
+        .collect()
+}
+
+fn compressed_event(fixture: &Fixture, tree: Pubkey) -> EventAccount {
+    EventAccount {
+        ticket_mint_mode: TicketMintMode::Compressed,
+        merkle_tree: Some(tree),
+        ..fixture.event_state()
+    }
+}
+
+/// The event's first ticket, held by `owner` at `stage`.
+fn leaf(event: &EventAccount, owner: Pubkey, stage: TicketStage) -> CompressedTicket {
+    CompressedTicket {
+        root: [7; 32],
+        nonce: 0,
+        owner,
+        delegate: owner,
+        metadata: CompressedMetadata::new(
+            "TIX • Test Event".to_string(),
+            stage,
+            String::new(),
+            500,
+            event.ticket_creators(),
+        ),
+    }
+}
+
+fn proof() -> Vec<TestAccount> {
+    (0..3).map(|_| TestAccount::wallet(Pubkey::new_unique())).collect()
+}
+
+fn listing_key(tree: &Pubkey, nonce: u64) -> Pubkey {
+    pda(&[PROGRAM_SEED.as_bytes(), LISTING_SEED.as_bytes(), asset_id(tree, nonce).as_ref()])
+}
+
+fn seller_listings_key(seller: &Pubkey) -> Pubkey {
+    pda(&[
+        PROGRAM_SEED.as_bytes(),
+        SELLER_LISTINGS_SEED.as_bytes(),
+        event_pda(1).as_ref(),
+        seller.as_ref(),
+    ])
+}
+
+/// Accounts for `create_ticket_tree` of event 1 with a freshly allocated tree.
+fn create_tree_accounts(event: &EventAccount, tree: Pubkey) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(event.authority),
+        TestAccount::anchor(event_pda(1), event, 8 + EventAccount::INIT_SPACE),
+        TestAccount {
+            owner: ACCOUNT_COMPRESSION_PROGRAM_ID,
+            ..TestAccount::wallet(tree)
+        },
+        TestAccount::wallet(tree_config(&tree)),
+        ticket_authority(),
+        TestAccount::program(BUBBLEGUM_PROGRAM_ID),
+        TestAccount::program(NOOP_PROGRAM_ID),
+        TestAccount::program(ACCOUNT_COMPRESSION_PROGRAM_ID),
+        TestAccount::program(System::id()),
+    ]
+}
+
+/// Runs `create_ticket_tree`, returning the event and the CPIs made.
+fn try_create_tree(accounts: &mut [TestAccount], max_depth: u32) -> Result<(EventAccount, Vec<Instruction>)> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = CreateTicketTreeCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    create_ticket_tree_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps), max_depth, 64)?;
+    Ok(((*ctx_accounts.event_account).clone(), take_invoked()))
+}
+
+/// Accounts for `buy_compressed_ticket` of tier 0 by `buyer`. Optional accounts are left out.
+fn buy_compressed_accounts(buyer: Pubkey, event: &EventAccount) -> Vec<TestAccount> {
+    let tree = event.merkle_tree.unwrap();
+    let none = || TestAccount::program(nft_evo_tickets::ID);
+    let mut accounts: Vec<TestAccount> = buy_accounts(buyer, event).into_iter().take(BUY_TICKET).collect();
+    accounts.extend([
+        TestAccount::wallet(tree),
+        TestAccount::wallet(tree_config(&tree)),
+        ticket_authority(),
+        TestAccount::program(BUBBLEGUM_PROGRAM_ID),
+        TestAccount::program(NOOP_PROGRAM_ID),
+        TestAccount::program(ACCOUNT_COMPRESSION_PROGRAM_ID),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(System::id()),
+    ]);
+    accounts.extend((0..8).map(|_| none()));
+    accounts[BUY_COMPRESSED_METADATA_CONFIG] =
+        metadata_config_account(event_pda(event.event_id), stage_templates());
+    accounts
+}
+
+/// Runs `buy_compressed_ticket` for tier 0, returning the event, the seat
+/// reservation and the CPIs made.
+fn try_buy_compressed(
+    accounts: &mut [TestAccount],
+    seat: Option<&str>,
+) -> Result<(EventAccount, Option<SeatReservation>, Vec<Instruction>)> {
+    let seat = seat.map(str::to_string);
+    let allowlist_proof: Option<AllowlistProof> = None;
+    let mut ix_data = vec![0]; // tier_index: 0
+    seat.serialize(&mut ix_data).unwrap();
+    allowlist_proof.serialize(&mut ix_data).unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = BuyCompressedTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    buy_compressed_ticket_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        0,
+        seat,
+        allowlist_proof,
+    )?;
+    Ok((
+        (*ctx_accounts.event_account).clone(),
+        ctx_accounts.seat_reservation.as_deref().cloned(),
+        take_invoked(),
+    ))
+}
+
+/// Accounts for `update_compressed_ticket` of `ticket` signed by `signer`, followed by its proof.
+fn update_accounts(signer: Pubkey, event: &EventAccount, ticket: &CompressedTicket) -> Vec<TestAccount> {
+    let tree = event.merkle_tree.unwrap();
+    let none = || TestAccount::program(nft_evo_tickets::ID);
+    let mut accounts = vec![
+        TestAccount::signer(signer),
+        config_account(),
+        TestAccount::anchor(event_pda(1), event, 8 + EventAccount::INIT_SPACE),
+        TestAccount::wallet(event.authority),
+        TestAccount::wallet(event.scanner),
+        TestAccount::wallet(tree),
+        TestAccount::wallet(tree_config(&tree)),
+        TestAccount::wallet(ticket.owner),
+        TestAccount::wallet(ticket.delegate),
+        ticket_authority(),
+        TestAccount::program(BUBBLEGUM_PROGRAM_ID),
+        TestAccount::program(NOOP_PROGRAM_ID),
+        TestAccount::program(ACCOUNT_COMPRESSION_PROGRAM_ID),
+        TestAccount::program(mpl_token_metadata::ID),
+        TestAccount::program(System::id()),
+        none(), // seat reservation
+        none(), // metadata config
+    ];
+    accounts.extend(proof());
+    accounts
+}
+
+/// Runs `update_compressed_ticket`, returning the CPIs made.
+fn try_update(
+    accounts: &mut [TestAccount],
+    new_stage: TicketStage,
+    ticket: &CompressedTicket,
+    seat: Option<&str>,
+) -> Result<Vec<Instruction>> {
+    let seat = seat.map(str::to_string);
+    let ix_data = (new_stage, ticket.clone(), seat.clone()).try_to_vec().unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = UpdateCompressedTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    update_compressed_ticket_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, remaining, bumps),
+        new_stage,
+        ticket.clone(),
+        seat,
+    )?;
+    Ok(take_invoked())
+}
+
+/// Accounts for `list_compressed_ticket` of `ticket` by its owner, followed by its proof.
+fn list_accounts(event: &EventAccount, ticket: &CompressedTicket) -> Vec<TestAccount> {
+    let tree = event.merkle_tree.unwrap();
+    let mut accounts = vec![
+        TestAccount::signer(ticket.owner),
+        config_account(),
+        TestAccount::anchor(event_pda(1), event, 8 + EventAccount::INIT_SPACE),
+        TestAccount::wallet(tree),
+        TestAccount::uninitialized(listing_key(&tree, ticket.nonce), 8 + ListingAccount::INIT_SPACE),
+        TestAccount::uninitialized(seller_listings_key(&ticket.owner), 8 + SellerListings::INIT_SPACE),
+        TestAccount::wallet(tree_config(&tree)),
+        TestAccount::wallet(ticket.delegate),
+        ticket_authority(),
+        TestAccount::program(BUBBLEGUM_PROGRAM_ID),
+        TestAccount::program(NOOP_PROGRAM_ID),
+        TestAccount::program(ACCOUNT_COMPRESSION_PROGRAM_ID),
+        TestAccount::program(System::id()),
+    ];
+    accounts.extend(proof());
+    accounts
+}
+
+/// Runs `list_compressed_ticket` at `PRICE`, returning the listing and the CPIs made.
+fn try_list(accounts: &mut [TestAccount], ticket: &CompressedTicket) -> Result<(ListingAccount, Vec<Instruction>)> {
+    let expires_at: Option<i64> = None;
+    let ix_data = (PRICE, expires_at, ticket.clone()).try_to_vec().unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = ListCompressedTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    list_compressed_ticket_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, remaining, bumps),
+        PRICE,
+        expires_at,
+        ticket.clone(),
+    )?;
+    Ok(((*ctx_accounts.listing_account).clone(), take_invoked()))
+}
+
+/// `seller`'s listing of the event's first ticket at `PRICE`, holding its leaf.
+fn listing_account(tree: &Pubkey, seller: Pubkey) -> TestAccount {
+    let asset = asset_id(tree, 0);
+    let listing = ListingAccount {
+        ticket: asset,
+        seller,
+        price_lamports: PRICE,
+        payment_mint: None,
+        created_at: 0,
+        expires_at: None,
+        auction: None,
+        bump: bump(&[PROGRAM_SEED.as_bytes(), LISTING_SEED.as_bytes(), asset.as_ref()]),
+    };
+    TestAccount::anchor(listing_key(tree, 0), &listing, 8 + ListingAccount::INIT_SPACE)
+}
+
+fn seller_listings_account(seller: Pubkey) -> TestAccount {
+    let seller_listings = SellerListings {
+        event: event_pda(1),
+        seller,
+        active_listings: 1,
+        bump: bump(&[
+            PROGRAM_SEED.as_bytes(),
+            SELLER_LISTINGS_SEED.as_bytes(),
+            event_pda(1).as_ref(),
+            seller.as_ref(),
+        ]),
+    };
+    TestAccount::anchor(seller_listings_key(&seller), &seller_listings, 8 + SellerListings::INIT_SPACE)
+}
+
+#[test]
+fn ticket_tree_switches_the_event_to_compressed_tickets() {
+    let fixture = Fixture::new();
+    let tree = Pubkey::new_unique();
+    let event = EventAccount {
+        tickets_sold: 0,
+        ..fixture.event_state()
+    };
+    let mut accounts = create_tree_accounts(&event, tree);
+    let (updated, invoked) = try_create_tree(&mut accounts, 14).unwrap();
+    assert_eq!(updated.merkle_tree, Some(tree));
+    assert!(updated.ticket_mint_mode == TicketMintMode::Compressed);
+
+    let cpis = bubblegum(&invoked);
+    assert_eq!(cpis.len(), 1);
+    assert_eq!(cpis[0].data[..8], CREATE_TREE);
+    let (max_depth, max_buffer_size, public): (u32, u32, Option<bool>) =
+        AnchorDeserialize::try_from_slice(&cpis[0].data[8..]).unwrap();
+    assert_eq!((max_depth, max_buffer_size, public), (14, 64, Some(false)));
+    assert_eq!(cpis[0].accounts[0].pubkey, tree_config(&tree));
+    assert_eq!(cpis[0].accounts[1].pubkey, tree);
+    // The ticket authority is the tree creator, so only the program can mint into it
+    let creator = &cpis[0].accounts[3];
+    assert!(creator.pubkey == ticket_authority().key && creator.is_signer);
+
+    // 2^6 leaves can't hold the event's 100 tickets
+    let mut accounts = create_tree_accounts(&event, tree);
+    assert_error(try_create_tree(&mut accounts, 6), ErrorCode::TicketTreeTooSmall);
+
+    let mut accounts = create_tree_accounts(&fixture.event_state(), tree);
+    assert_error(try_create_tree(&mut accounts, 14), ErrorCode::TicketsAlreadySold);
+
+    let collection_event = EventAccount {
+        collection_mint: Some(Pubkey::new_unique()),
+        ..event.clone()
+    };
+    let mut accounts = create_tree_accounts(&collection_event, tree);
+    assert_error(try_create_tree(&mut accounts, 14), ErrorCode::CollectionRequiresLegacyMints);
+
+    let mut accounts = create_tree_accounts(&event, tree);
+    accounts[CREATE_TREE_TREE].owner = System::id();
+    assert!(try_create_tree(&mut accounts, 14).is_err());
+}
+
+/// The event's authority and the event, what every organizer setting takes.
+fn organizer_accounts(event: &EventAccount) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(event.authority),
+        TestAccount::anchor(event_pda(1), event, 8 + EventAccount::INIT_SPACE),
+    ]
+}
+
+fn try_set_transfer_policy(event: &EventAccount, transfer_policy: TransferPolicy) -> Result<()> {
+    let mut accounts = organizer_accounts(event);
+    let infos = infos(&mut accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SetTransferPolicyCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    set_transfer_policy_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        transfer_policy,
+        None,
+    )
+}
+
+fn try_set_dutch_auction(event: &EventAccount, dutch_auction: DutchAuction) -> Result<()> {
+    let mut accounts = organizer_accounts(event);
+    let infos = infos(&mut accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SetDutchAuctionCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    set_dutch_auction_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        Some(dutch_auction),
+    )
+}
+
+fn try_cancel(event: &EventAccount) -> Result<()> {
+    let mut accounts = organizer_accounts(event);
+    let infos = infos(&mut accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = CancelEventCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    cancel_event_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps))
+}
+
+#[test]
+fn compressed_events_cannot_be_refunded_rebated_or_transfer_limited() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        tickets_sold: 0,
+        end_ts: 1_000,
+        ..compressed_event(&fixture, Pubkey::new_unique())
+    };
+    let auction = DutchAuction {
+        start_price_lamports: 2 * PRICE,
+        floor_price_lamports: PRICE,
+        start_ts: 0,
+        end_ts: 1_000,
+        step_secs: 0,
+        rebate: true,
+    };
+
+    // Refunds and rebates are paid against ticket accounts, which leaves don't have
+    assert_error(try_cancel(&event), ErrorCode::CompressedTicketsUnsupported);
+    assert_error(try_set_dutch_auction(&event, auction), ErrorCode::CompressedTicketsUnsupported);
+    assert_error(
+        try_set_transfer_policy(&event, TransferPolicy::Disallowed),
+        ErrorCode::CompressedTicketsUnsupported,
+    );
+    assert!(try_set_transfer_policy(&event, TransferPolicy::Allowed).is_ok());
+
+    // Nor can an event with them switch to compressed tickets
+    let legacy = EventAccount {
+        ticket_mint_mode: TicketMintMode::Legacy,
+        merkle_tree: None,
+        ..event.clone()
+    };
+    for restricted in [
+        EventAccount {
+            dutch_auction: Some(auction),
+            ..legacy.clone()
+        },
+        EventAccount {
+            transfer_policy: TransferPolicy::BeforeStart,
+            ..legacy.clone()
+        },
+    ] {
+        let mut accounts = create_tree_accounts(&restricted, Pubkey::new_unique());
+        assert_error(try_create_tree(&mut accounts, 14), ErrorCode::CompressedTicketsUnsupported);
+    }
+}
+
+#[test]
+fn compressed_mode_needs_a_tree_and_its_own_sale_instruction() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        tickets_sold: 0,
+        ..fixture.event_state()
+    };
+    let mut accounts = vec![
+        TestAccount::signer(event.authority),
+        TestAccount::anchor(event_pda(1), &event, 8 + EventAccount::INIT_SPACE),
+    ];
+    let infos = infos(&mut accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mode = TicketMintMode::Compressed;
+    let mut ctx_accounts = SetTicketMintModeCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &mode.try_to_vec().unwrap(),
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )
+    .unwrap();
+    assert_error(
+        set_ticket_mint_mode_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps), mode),
+        ErrorCode::TicketTreeMismatch,
+    );
+
+    let event = compressed_event(&fixture, Pubkey::new_unique());
+    let mut accounts = buy_accounts(fixture.buyer, &event);
+    assert_error(try_buy(&mut accounts, None), ErrorCode::TicketsAreCompressed);
+
+    let legacy = EventAccount {
+        ticket_mint_mode: TicketMintMode::Legacy,
+        ..event.clone()
+    };
+    let mut accounts = buy_compressed_accounts(fixture.buyer, &legacy);
+    assert_error(try_buy_compressed(&mut accounts, None), ErrorCode::TicketsNotCompressed);
+
+    let mut accounts = buy_compressed_accounts(fixture.buyer, &event);
+    accounts[BUY_COMPRESSED_TREE] = TestAccount::wallet(Pubkey::new_unique());
+    assert_error(try_buy_compressed(&mut accounts, None), ErrorCode::TicketTreeMismatch);
+}
+
+#[test]
+fn purchases_mint_a_leaf_instead_of_a_ticket_account() {
+    let fixture = Fixture::new();
+    let buyer = fixture.buyer;
+    let tree = Pubkey::new_unique();
+    let event = compressed_event(&fixture, tree);
+    let mut accounts = buy_compressed_accounts(buyer, &event);
+    let vault_lamports = accounts[BUY_VAULT].lamports;
+    let (updated, _, invoked) = try_buy_compressed(&mut accounts, None).unwrap();
+    assert_eq!(updated.tickets_sold, 2);
+    assert_eq!(accounts[BUY_VAULT].lamports, vault_lamports + 1_000_000_000);
+
+    // Only the leaf is created, no mint, token account or Metaplex accounts
+    assert!(invoked
+        .iter()
+        .all(|ix| ix.program_id == BUBBLEGUM_PROGRAM_ID || ix.program_id == System::id()));
+    let cpis = bubblegum(&invoked);
+    assert_eq!(cpis.len(), 1);
+    let mint = cpis[0];
+    assert_eq!(mint.data[..8], MINT_V1);
+    assert_eq!(mint.accounts[1].pubkey, buyer);
+    assert_eq!(mint.accounts[2].pubkey, buyer);
+    assert_eq!(mint.accounts[3].pubkey, tree);
+    assert!(mint.accounts[5].pubkey == ticket_authority().key && mint.accounts[5].is_signer);
+
+    // The event has started, so the leaf is minted at the QR stage
+    let metadata = CompressedMetadata::try_from_slice(&mint.data[8..]).unwrap();
+    assert_eq!(metadata.name, "TIX • Test Event");
+    assert_eq!(metadata.symbol, "TIX-QR");
+    assert_eq!(metadata.seller_fee_basis_points, 500);
+    assert_eq!(
+        metadata.creators,
+        vec![CompressedCreator {
+            address: event.authority,
+            verified: false,
+            share: 100,
+        }]
+    );
+
+    let upcoming = EventAccount {
+        start_ts: common::NOW + 60,
+        end_ts: common::NOW + 120,
+        ..event
+    };
+    let mut accounts = buy_compressed_accounts(buyer, &upcoming);
+    let (_, _, invoked) = try_buy_compressed(&mut accounts, None).unwrap();
+    let metadata = CompressedMetadata::try_from_slice(&bubblegum(&invoked)[0].data[8..]).unwrap();
+    assert_eq!(metadata.symbol, "TIX-PRE");
+}
+
+#[test]
+fn leaves_of_long_named_events_fit_the_name_limit() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        name: "Coachella Valley Music and Arts Festival".to_string(),
+        ..compressed_event(&fixture, Pubkey::new_unique())
+    };
+    let mut accounts = buy_compressed_accounts(fixture.buyer, &event);
+    accounts[BUY_COMPRESSED_SEAT_RESERVATION] = free_seat_account(fixture.event, "ORCH-12-7");
+    let (_, _, invoked) = try_buy_compressed(&mut accounts, Some("ORCH-12-7")).unwrap();
+    // The leaf is named as it will be at every later stage
+    let metadata = CompressedMetadata::try_from_slice(&bubblegum(&invoked)[0].data[8..]).unwrap();
+    assert_eq!(metadata.name, "TIX • Coachella  • ORCH-12-7");
+    assert!(metadata.name.len() <= 32);
+}
+
+#[test]
+fn seats_are_reserved_for_the_ticket_asset_id() {
+    let fixture = Fixture::new();
+    let tree = Pubkey::new_unique();
+    let event = compressed_event(&fixture, tree);
+    let mut accounts = buy_compressed_accounts(fixture.buyer, &event);
+    accounts[BUY_COMPRESSED_SEAT_RESERVATION] = free_seat_account(fixture.event, "A1");
+    let (_, seat_reservation, invoked) = try_buy_compressed(&mut accounts, Some("A1")).unwrap();
+
+    // The event's second ticket is the tree's second leaf
+    let seat_reservation = seat_reservation.unwrap();
+    assert_eq!(seat_reservation.ticket, asset_id(&tree, 1));
+    let metadata = CompressedMetadata::try_from_slice(&bubblegum(&invoked)[0].data[8..]).unwrap();
+    assert_eq!(metadata.name, "TIX • Test Event • A1");
+
+    // Scanning the seated ticket needs its reservation
+    let ticket = CompressedTicket {
+        nonce: 1,
+        ..leaf(&event, fixture.buyer, TicketStage::Qr)
+    };
+    let mut accounts = update_accounts(event.scanner, &event, &ticket);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Scanned, &ticket, Some("A1")),
+        ErrorCode::SeatReservationMismatch,
+    );
+    accounts[UPDATE_SEAT_RESERVATION] =
+        seat_reservation_account(fixture.event, "A1", seat_reservation.ticket);
+    try_update(&mut accounts, TicketStage::Scanned, &ticket, Some("A1")).unwrap();
+
+    let other = CompressedTicket {
+        nonce: 2,
+        ..ticket
+    };
+    let mut accounts = update_accounts(event.scanner, &event, &other);
+    accounts[UPDATE_SEAT_RESERVATION] =
+        seat_reservation_account(fixture.event, "A1", seat_reservation.ticket);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Scanned, &other, Some("A1")),
+        ErrorCode::SeatReservationMismatch,
+    );
+}
+
+#[test]
+fn scanning_updates_the_leaf_stage() {
+    let fixture = Fixture::new();
+    let holder = fixture.holder;
+    let event = compressed_event(&fixture, Pubkey::new_unique());
+    let ticket = leaf(&event, holder, TicketStage::Qr);
+    let mut accounts = update_accounts(event.scanner, &event, &ticket);
+    let invoked = try_update(&mut accounts, TicketStage::Scanned, &ticket, None).unwrap();
+
+    let cpis = bubblegum(&invoked);
+    assert_eq!(cpis.len(), 1);
+    let update = cpis[0];
+    assert_eq!(update.data[..8], UPDATE_METADATA);
+    assert!(update.accounts[1].pubkey == ticket_authority().key && update.accounts[1].is_signer);
+    assert_eq!(update.accounts[5].pubkey, holder);
+    // The proof nodes follow Bubblegum's accounts
+    let proof: Vec<Pubkey> = accounts[UPDATE_PROOF..].iter().map(|node| node.key).collect();
+    let nodes: Vec<Pubkey> = update.accounts[13..].iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(nodes, proof);
+
+    let (root, nonce, index, current, (_, symbol, uri, ..)): UpdateMetadataArgs =
+        AnchorDeserialize::try_from_slice(&update.data[8..]).unwrap();
+    assert_eq!((root, nonce, index), (ticket.root, 0, 0));
+    assert_eq!(current, ticket.metadata);
+    assert_eq!(symbol.as_deref(), Some("TIX-SCAN"));
+    assert_eq!(uri, None);
+
+    // Only the scanner scans, and only QR tickets
+    let mut accounts = update_accounts(holder, &event, &ticket);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Scanned, &ticket, None),
+        ErrorCode::Unauthorized,
+    );
+    let scanned = leaf(&event, holder, TicketStage::Scanned);
+    let mut accounts = update_accounts(event.scanner, &event, &scanned);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Scanned, &scanned, None),
+        ErrorCode::InvalidTicketStage,
+    );
+
+    let mut unknown = ticket.clone();
+    unknown.metadata.symbol = "TIX".to_string();
+    let mut accounts = update_accounts(event.scanner, &event, &unknown);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Scanned, &unknown, None),
+        ErrorCode::InvalidCompressedTicket,
+    );
+}
+
+#[test]
+fn scanned_leaves_become_collectibles_after_the_event() {
+    let fixture = Fixture::new();
+    let holder = fixture.holder;
+    let event = EventAccount {
+        has_metadata_config: true,
+        ..compressed_event(&fixture, Pubkey::new_unique())
+    };
+    let template = "https://cdn.test/{event_id}/{serial}/{stage}.json".to_string();
+    let templates = StageUriTemplates {
+        prestige: template.clone(),
+        qr: template.clone(),
+        scanned: template.clone(),
+        collectible: template,
+    };
+
+    let ticket = leaf(&event, holder, TicketStage::Scanned);
+    let mut accounts = update_accounts(holder, &event, &ticket);
+    accounts[UPDATE_METADATA_CONFIG] = metadata_config_account(fixture.event, templates);
+    let invoked = try_update(&mut accounts, TicketStage::Collectible, &ticket, None).unwrap();
+    let (.., (_, symbol, uri, ..)): UpdateMetadataArgs =
+        AnchorDeserialize::try_from_slice(&bubblegum(&invoked)[0].data[8..]).unwrap();
+    assert_eq!(symbol.as_deref(), Some("TIX-COLL"));
+    assert_eq!(uri.as_deref(), Some("https://cdn.test/1/1/collectible.json"));
+
+    // Tickets of an event with templates are updated with them
+    let mut accounts = update_accounts(holder, &event, &ticket);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Collectible, &ticket, None),
+        ErrorCode::MetadataConfigMismatch,
+    );
+
+    let unscanned = leaf(&event, holder, TicketStage::Qr);
+    let mut accounts = update_accounts(holder, &event, &unscanned);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Collectible, &unscanned, None),
+        ErrorCode::TicketNotScanned,
+    );
+
+    let ongoing = EventAccount {
+        end_ts: common::NOW + 60,
+        ..event
+    };
+    let mut accounts = update_accounts(holder, &ongoing, &ticket);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Collectible, &ticket, None),
+        ErrorCode::EventNotOver,
+    );
+}
+
+#[test]
+fn listing_escrows_the_leaf_with_the_listing() {
+    let fixture = Fixture::new();
+    let seller = fixture.holder;
+    let tree = Pubkey::new_unique();
+    let event = compressed_event(&fixture, tree);
+    let ticket = leaf(&event, seller, TicketStage::Qr);
+    let mut accounts = list_accounts(&event, &ticket);
+    let (listing, invoked) = try_list(&mut accounts, &ticket).unwrap();
+    assert_eq!(listing.ticket, asset_id(&tree, 0));
+    assert_eq!(listing.seller, seller);
+    assert_eq!(listing.price_lamports, PRICE);
+
+    let cpis = bubblegum(&invoked);
+    assert_eq!(cpis.len(), 1);
+    let transfer = cpis[0];
+    assert_eq!(transfer.data[..8], TRANSFER);
+    assert!(transfer.accounts[1].pubkey == seller && transfer.accounts[1].is_signer);
+    assert_eq!(transfer.accounts[3].pubkey, listing_key(&tree, 0));
+    assert_eq!(transfer.accounts.len(), 8 + 3);
+    let args: TransferArgs = AnchorDeserialize::try_from_slice(&transfer.data[8..]).unwrap();
+    assert_eq!(
+        args,
+        (
+            ticket.root,
+            ticket.metadata.data_hash().unwrap(),
+            ticket.metadata.creator_hash(),
+            0,
+            0
+        )
+    );
+
+    let scanned = leaf(&event, seller, TicketStage::Scanned);
+    let mut accounts = list_accounts(&event, &scanned);
+    assert_error(try_list(&mut accounts, &scanned), ErrorCode::CannotListInCurrentStage);
+
+    // Leaves carry no face value or acquisition time to enforce a resale policy against
+    let capped = EventAccount {
+        max_resale_markup_bps: Some(1_000),
+        ..event.clone()
+    };
+    let mut accounts = list_accounts(&capped, &ticket);
+    assert_error(try_list(&mut accounts, &ticket), ErrorCode::ResalePolicyNeedsTicketAccounts);
+    let cooling_down = EventAccount {
+        resale_cooldown_secs: Some(60),
+        ..event
+    };
+    let mut accounts = list_accounts(&cooling_down, &ticket);
+    assert_error(try_list(&mut accounts, &ticket), ErrorCode::ResalePolicyNeedsTicketAccounts);
+}
+
+#[test]
+fn sold_listings_pay_the_seller_and_move_the_leaf_to_the_buyer() {
+    let fixture = Fixture::new();
+    let (seller, buyer) = (fixture.holder, fixture.buyer);
+    let tree = Pubkey::new_unique();
+    let event = compressed_event(&fixture, tree);
+    let listing = listing_key(&tree, 0);
+    let ticket = leaf(&event, listing, TicketStage::Qr);
+    let none = || TestAccount::program(nft_evo_tickets::ID);
+    let mut accounts = vec![
+        TestAccount::signer(buyer),
+        config_account(),
+        fixture.event_account(&event),
+        TestAccount::wallet(tree),
+        listing_account(&tree, seller),
+        TestAccount::wallet(treasury()),
+        TestAccount::wallet(seller),
+        seller_listings_account(seller),
+        TestAccount::wallet(tree_config(&tree)),
+        ticket_authority(),
+        TestAccount::program(BUBBLEGUM_PROGRAM_ID),
+        TestAccount::program(NOOP_PROGRAM_ID),
+        TestAccount::program(ACCOUNT_COMPRESSION_PROGRAM_ID),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(System::id()),
+        none(), // buyer payment account
+        none(), // seller payment account
+        none(), // treasury payment account
+    ];
+    accounts.extend(proof());
+    let (seller_lamports, treasury_lamports) = (
+        accounts[BUY_LISTING_SELLER].lamports,
+        accounts[BUY_LISTING_TREASURY].lamports,
+    );
+
+    {
+        let ix_data = ticket.try_to_vec().unwrap();
+        let infos = infos(&mut accounts);
+        let mut remaining = &infos[..];
+        let mut bumps = Default::default();
+        take_invoked();
+        let mut ctx_accounts = BuyCompressedListingCtx::try_accounts(
+            &nft_evo_tickets::ID,
+            &mut remaining,
+            &ix_data,
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        buy_compressed_listing_handler(
+            Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, remaining, bumps),
+            ticket,
+        )
+        .unwrap();
+        assert_eq!(ctx_accounts.seller_listings.active_listings, 0);
+    }
+
+    // 5% marketplace fee to the platform treasury, the rest to the seller
+    assert_eq!(accounts[BUY_LISTING_SELLER].lamports, seller_lamports + PRICE / 20 * 19);
+    assert_eq!(accounts[BUY_LISTING_TREASURY].lamports, treasury_lamports + PRICE / 20);
+
+    let invoked = take_invoked();
+    let transfer = bubblegum(&invoked)[0];
+    assert_eq!(transfer.data[..8], TRANSFER);
+    // The listing PDA owns the leaf and signs for it
+    assert!(transfer.accounts[1].pubkey == listing && transfer.accounts[1].is_signer);
+    assert_eq!(transfer.accounts[2].pubkey, listing);
+    assert_eq!(transfer.accounts[3].pubkey, buyer);
+    let proof: Vec<Pubkey> = accounts[BUY_LISTING_PROOF..].iter().map(|node| node.key).collect();
+    let nodes: Vec<Pubkey> = transfer.accounts[8..].iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(nodes, proof);
+}
+
+#[test]
+fn cancelled_listings_return_the_leaf_to_the_seller() {
+    let fixture = Fixture::new();
+    let seller = fixture.holder;
+    let tree = Pubkey::new_unique();
+    let event = compressed_event(&fixture, tree);
+    let listing = listing_key(&tree, 0);
+    let ticket = leaf(&event, listing, TicketStage::Qr);
+    let cancel_accounts = |signer: Pubkey| {
+        let mut accounts = vec![
+            TestAccount::signer(signer),
+            fixture.event_account(&event),
+            TestAccount::wallet(tree),
+            listing_account(&tree, seller),
+            seller_listings_account(signer),
+            TestAccount::wallet(tree_config(&tree)),
+            ticket_authority(),
+            TestAccount::program(BUBBLEGUM_PROGRAM_ID),
+            TestAccount::program(NOOP_PROGRAM_ID),
+            TestAccount::program(ACCOUNT_COMPRESSION_PROGRAM_ID),
+            TestAccount::program(System::id()),
+        ];
+        accounts.extend(proof());
+        accounts
+    };
+    let try_cancel = |accounts: &mut [TestAccount]| -> Result<Vec<Instruction>> {
+        let ix_data = ticket.try_to_vec().unwrap();
+        let infos = infos(accounts);
+        let mut remaining = &infos[..];
+        let mut bumps = Default::default();
+        take_invoked();
+        let mut ctx_accounts = CancelCompressedListingCtx::try_accounts(
+            &nft_evo_tickets::ID,
+            &mut remaining,
+            &ix_data,
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        cancel_compressed_listing_handler(
+            Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, remaining, bumps),
+            ticket.clone(),
+        )?;
+        assert_eq!(ctx_accounts.seller_listings.active_listings, 0);
+        Ok(take_invoked())
+    };
+
+    let mut accounts = cancel_accounts(seller);
+    let invoked = try_cancel(&mut accounts).unwrap();
+    let transfer = bubblegum(&invoked)[0];
+    assert_eq!(transfer.data[..8], TRANSFER);
+    assert!(transfer.accounts[1].pubkey == listing && transfer.accounts[1].is_signer);
+    assert_eq!(transfer.accounts[3].pubkey, seller);
+
+    let mut accounts = cancel_accounts(Pubkey::new_unique());
+    assert_error(try_cancel(&mut accounts), ErrorCode::Unauthorized);
+}
@@ -0,0 +1,246 @@
+//! Dutch auction pricing in `buy_event_ticket` and clearing price rebates.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, buy_accounts, config_account, event_state, infos, try_buy, vault_state,
+    vault_state_account, Fixture, TestAccount, NOW,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    claim_auction_rebate_handler, ClaimAuctionRebateCtx, DutchAuction, EventAccount, EventStatus,
+    EventVault, TicketAccount,
+};
+
+const SOL: u64 = 1_000_000_000;
+const HOUR: i64 = 3600;
+
+/// Positions in `rebate_accounts` that tests swap out or read back.
+const REBATE_HOLDER: usize = 0;
+const REBATE_TICKET: usize = 4;
+
+/// From 5 SOL down to 1 SOL over four hours starting at `start_ts`.
+fn auction(start_ts: i64, step_secs: u32) -> DutchAuction {
+    DutchAuction {
+        start_price_lamports: 5 * SOL,
+        floor_price_lamports: SOL,
+        start_ts,
+        end_ts: start_ts + 4 * HOUR,
+        step_secs,
+        rebate: true,
+    }
+}
+
+fn event(fixture: &Fixture, dutch_auction: DutchAuction) -> EventAccount {
+    EventAccount {
+        dutch_auction: Some(dutch_auction),
+        ..fixture.event_state()
+    }
+}
+
+#[test]
+fn price_decays_linearly_to_floor() {
+    let auction = auction(NOW, 0);
+    assert_eq!(auction.price_at(NOW - 1), 5 * SOL);
+    assert_eq!(auction.price_at(NOW), 5 * SOL);
+    assert_eq!(auction.price_at(NOW + 30 * 60), 4_500_000_000);
+    assert_eq!(auction.price_at(NOW + 2 * HOUR), 3 * SOL);
+    assert_eq!(auction.price_at(NOW + 4 * HOUR), SOL);
+    assert_eq!(auction.price_at(NOW + 5 * HOUR), SOL);
+}
+
+#[test]
+fn price_decays_in_steps() {
+    let auction = auction(NOW, HOUR as u32);
+    assert_eq!(auction.price_at(NOW + 30 * 60), 5 * SOL);
+    assert_eq!(auction.price_at(NOW + HOUR), 4 * SOL);
+    assert_eq!(auction.price_at(NOW + 2 * HOUR - 1), 4 * SOL);
+    assert_eq!(auction.price_at(NOW + 3 * HOUR + 59 * 60), 2 * SOL);
+    assert_eq!(auction.price_at(NOW + 4 * HOUR), SOL);
+}
+
+#[test]
+fn auction_must_fall_within_event() {
+    let valid = auction(NOW, 0);
+    assert!(valid.validate(NOW + 4 * HOUR).is_ok());
+    assert_error(valid.validate(NOW + 4 * HOUR - 1), ErrorCode::InvalidDutchAuction);
+
+    let rising = DutchAuction {
+        floor_price_lamports: 5 * SOL,
+        ..valid
+    };
+    assert_error(rising.validate(NOW + 4 * HOUR), ErrorCode::InvalidDutchAuction);
+
+    let step_longer_than_auction = DutchAuction {
+        step_secs: 4 * HOUR as u32,
+        ..valid
+    };
+    assert_error(
+        step_longer_than_auction.validate(NOW + 4 * HOUR),
+        ErrorCode::InvalidDutchAuction,
+    );
+}
+
+#[test]
+fn buy_charges_current_auction_price() {
+    let fixture = Fixture::new();
+    let event = event(&fixture, auction(NOW - 2 * HOUR, 0));
+    let mut accounts = buy_accounts(fixture.buyer, &event);
+    let bought = try_buy(&mut accounts, None).unwrap();
+    assert_eq!(bought.ticket.price_paid_lamports, 3 * SOL);
+    assert!(bought.ticket.bought_at_auction);
+}
+
+#[test]
+fn clearing_price_is_final_once_sold_out_or_ended() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        auction_clearing_price_lamports: 3 * SOL,
+        ..event(&fixture, auction(NOW - 2 * HOUR, 0))
+    };
+    assert_eq!(event.auction_clearing_price(NOW), None);
+    assert_eq!(event.auction_clearing_price(NOW + 2 * HOUR), Some(SOL));
+
+    let sold_out = EventAccount {
+        tickets_sold: event.ticket_supply,
+        ..event
+    };
+    assert_eq!(sold_out.auction_clearing_price(NOW), Some(3 * SOL));
+    assert_eq!(event_state(1, Pubkey::new_unique()).auction_clearing_price(NOW), None);
+}
+
+/// Accounts for `claim_auction_rebate` of the fixture's ticket bought at the
+/// auction for `price_paid`, with 10 SOL of proceeds in the vault.
+fn rebate_accounts(fixture: &Fixture, event: &EventAccount, price_paid: u64) -> Vec<TestAccount> {
+    let mut vault = vault_state_account(&EventVault {
+        unrebated_auction_sales: 1,
+        unrebated_auction_proceeds: price_paid,
+        ..vault_state(fixture.event)
+    });
+    vault.lamports += 10 * SOL;
+    let ticket = TicketAccount {
+        price_paid_lamports: price_paid,
+        bought_at_auction: true,
+        ..fixture.ticket_state(false)
+    };
+    vec![
+        TestAccount::signer(fixture.holder),
+        config_account(),
+        fixture.event_account(event),
+        vault,
+        fixture.ticket_account(&ticket),
+        fixture.nft_account(fixture.holder, 1),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(nft_evo_tickets::ID),
+        TestAccount::program(nft_evo_tickets::ID),
+    ]
+}
+
+/// Claims the rebate, returning the ticket, the vault and what reached the holder.
+fn try_claim_rebate(accounts: &mut [TestAccount]) -> Result<(TicketAccount, EventVault, u64)> {
+    let holder_lamports = accounts[REBATE_HOLDER].lamports;
+    let (ticket, vault) = {
+        let infos = infos(accounts);
+        let mut remaining = &infos[..];
+        let mut bumps = Default::default();
+        let mut ctx_accounts = ClaimAuctionRebateCtx::try_accounts(
+            &nft_evo_tickets::ID,
+            &mut remaining,
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        claim_auction_rebate_handler(Context::new(
+            &nft_evo_tickets::ID,
+            &mut ctx_accounts,
+            &[],
+            bumps,
+        ))?;
+        (
+            (*ctx_accounts.ticket_account).clone(),
+            (*ctx_accounts.event_vault).clone(),
+        )
+    };
+    Ok((ticket, vault, accounts[REBATE_HOLDER].lamports - holder_lamports))
+}
+
+#[test]
+fn early_buyer_claims_rebate_down_to_clearing_price() {
+    let fixture = Fixture::new();
+    let ended = event(&fixture, auction(NOW - 4 * HOUR, 0));
+    let (ticket, vault, rebate) =
+        try_claim_rebate(&mut rebate_accounts(&fixture, &ended, 4 * SOL)).unwrap();
+    assert_eq!(rebate, 3 * SOL);
+    assert_eq!(ticket.price_paid_lamports, SOL);
+    assert!(!ticket.bought_at_auction);
+    assert_eq!(vault.unrebated_auction_sales, 0);
+    assert_eq!(vault.unrebated_auction_proceeds, 0);
+
+    assert_error(
+        try_claim_rebate(&mut rebate_accounts(&fixture, &ended, SOL)),
+        ErrorCode::NoRebateDue,
+    );
+}
+
+#[test]
+fn rebate_is_only_owed_on_auction_purchases() {
+    let fixture = Fixture::new();
+    let ended = event(&fixture, auction(NOW - 4 * HOUR, 0));
+    let mut accounts = rebate_accounts(&fixture, &ended, 4 * SOL);
+    accounts[REBATE_TICKET] = fixture.ticket_account(&TicketAccount {
+        price_paid_lamports: 4 * SOL,
+        ..fixture.ticket_state(false)
+    });
+    assert_error(try_claim_rebate(&mut accounts), ErrorCode::NoRebateDue);
+}
+
+#[test]
+fn rebate_waits_for_final_price() {
+    let fixture = Fixture::new();
+    let running = event(&fixture, auction(NOW - HOUR, 0));
+    assert_error(
+        try_claim_rebate(&mut rebate_accounts(&fixture, &running, 4 * SOL)),
+        ErrorCode::AuctionNotFinal,
+    );
+
+    let no_rebate = event(&fixture, DutchAuction {
+        rebate: false,
+        ..auction(NOW - 4 * HOUR, 0)
+    });
+    assert_error(
+        try_claim_rebate(&mut rebate_accounts(&fixture, &no_rebate, 4 * SOL)),
+        ErrorCode::NoRebateDue,
+    );
+
+    let paused = EventAccount {
+        is_paused: true,
+        ..event(&fixture, auction(NOW - 4 * HOUR, 0))
+    };
+    assert_error(
+        try_claim_rebate(&mut rebate_accounts(&fixture, &paused, 4 * SOL)),
+        ErrorCode::EventPaused,
+    );
+}
+
+#[test]
+fn rebate_outlasts_settlement_but_not_cancellation() {
+    let fixture = Fixture::new();
+    let settled = EventAccount {
+        status: EventStatus::Settled,
+        ..event(&fixture, auction(NOW - 4 * HOUR, 0))
+    };
+    assert!(try_claim_rebate(&mut rebate_accounts(&fixture, &settled, 4 * SOL)).is_ok());
+
+    let cancelled = EventAccount {
+        status: EventStatus::Cancelled,
+        ..settled
+    };
+    assert_error(
+        try_claim_rebate(&mut rebate_accounts(&fixture, &cancelled, 4 * SOL)),
+        ErrorCode::EventCancelled,
+    );
+}
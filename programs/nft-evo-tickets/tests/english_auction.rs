@@ -0,0 +1,296 @@
+//! English auction listings: bidding, anti-sniping and settlement.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, bump, config_account, infos, paused_config_account, pda, ticket_authority,
+    token_balance, treasury, Fixture, TestAccount, NOW,
+};
+use nft_evo_tickets::constants::{BID_ESCROW_SEED, LISTING_SEED, PROGRAM_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    place_bid_handler, settle_auction_handler, AuctionTerms, BidEscrow, EventAccount,
+    ListingAccount, ListingAuction, PlaceBidCtx, SettleAuctionCtx,
+};
+
+const SOL: u64 = 1_000_000_000;
+const RESERVE: u64 = SOL;
+const INCREMENT: u64 = 100_000_000;
+const EXTENSION_SECS: u32 = 300;
+
+/// Positions in `bid_accounts` and `settle_accounts` that tests swap out or check.
+const BID_LISTING: usize = 4;
+const BID_ESCROW: usize = 5;
+const BID_PREVIOUS_BIDDER: usize = 6;
+const SETTLE_CONFIG: usize = 1;
+const SETTLE_BID_ESCROW: usize = 4;
+const SETTLE_EVENT: usize = 5;
+const SETTLE_TREASURY: usize = 6;
+const SETTLE_SELLER: usize = 7;
+const SETTLE_ESCROW_NFT_ACCOUNT: usize = 11;
+const SETTLE_RECIPIENT_NFT_ACCOUNT: usize = 12;
+
+/// `holder`'s auction listing of the ticket ending at `end_ts`, with `highest` as its leading bid.
+fn listing_account(
+    fixture: &Fixture,
+    reserve: u64,
+    end_ts: i64,
+    highest: Option<(Pubkey, u64)>,
+) -> TestAccount {
+    let listing = ListingAccount {
+        ticket: fixture.ticket,
+        seller: fixture.holder,
+        price_lamports: reserve,
+        payment_mint: None,
+        created_at: 0,
+        expires_at: None,
+        auction: Some(ListingAuction {
+            terms: AuctionTerms {
+                min_increment_lamports: INCREMENT,
+                end_ts,
+                extension_secs: EXTENSION_SECS,
+            },
+            highest_bidder: highest.map(|(bidder, _)| bidder),
+            highest_bid_lamports: highest.map_or(0, |(_, amount)| amount),
+        }),
+        bump: bump(&[PROGRAM_SEED.as_bytes(), LISTING_SEED.as_bytes(), fixture.ticket.as_ref()]),
+    };
+    TestAccount::anchor(fixture.listing(), &listing, 8 + ListingAccount::INIT_SPACE)
+}
+
+/// The listing's bid escrow, holding `held` on top of its rent.
+fn bid_escrow_account(fixture: &Fixture, held: u64) -> TestAccount {
+    let listing = fixture.listing();
+    let seeds: &[&[u8]] = &[PROGRAM_SEED.as_bytes(), BID_ESCROW_SEED.as_bytes(), listing.as_ref()];
+    let escrow = BidEscrow {
+        listing,
+        bump: bump(seeds),
+    };
+    let mut account = TestAccount::anchor(pda(seeds), &escrow, 8 + BidEscrow::INIT_SPACE);
+    account.lamports += held;
+    account
+}
+
+/// Accounts for `place_bid` by `bidder`, topping `highest`.
+fn bid_accounts(
+    fixture: &Fixture,
+    bidder: Pubkey,
+    end_ts: i64,
+    highest: Option<(Pubkey, u64)>,
+) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(bidder),
+        config_account(),
+        fixture.ticket_account(&fixture.ticket_state(true)),
+        fixture.event_account(&fixture.event_state()),
+        listing_account(fixture, RESERVE, end_ts, highest),
+        bid_escrow_account(fixture, highest.map_or(0, |(_, amount)| amount)),
+        match highest {
+            Some((previous, _)) => TestAccount::wallet(previous),
+            None => TestAccount::program(nft_evo_tickets::ID),
+        },
+        TestAccount::program(System::id()),
+    ]
+}
+
+/// Accounts for `settle_auction` of a listing won by `highest`, sending the ticket to `recipient`.
+fn settle_accounts(
+    fixture: &Fixture,
+    end_ts: i64,
+    highest: Option<(Pubkey, u64)>,
+    recipient: Pubkey,
+) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(Pubkey::new_unique()),
+        config_account(),
+        fixture.ticket_account(&fixture.ticket_state(true)),
+        listing_account(fixture, RESERVE, end_ts, highest),
+        bid_escrow_account(fixture, highest.map_or(0, |(_, amount)| amount)),
+        fixture.event_account(&fixture.event_state()),
+        TestAccount::wallet(treasury()),
+        TestAccount::wallet(fixture.holder),
+        fixture.seller_listings_account(Some(1)),
+        TestAccount::wallet(recipient),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.listing(), 1),
+        fixture.nft_account(recipient, 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+    ]
+}
+
+/// Runs the bid, returning the listing's auction afterwards.
+fn try_bid(accounts: &mut [TestAccount], amount: u64) -> Result<ListingAuction> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = PlaceBidCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &amount.to_le_bytes(),
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    place_bid_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        amount,
+    )?;
+    Ok(ctx_accounts.listing_account.auction.unwrap())
+}
+
+/// Runs the settlement, returning the ticket's owner afterwards.
+fn try_settle(accounts: &mut [TestAccount]) -> Result<Pubkey> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SettleAuctionCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    settle_auction_handler(Context::new(
+        &nft_evo_tickets::ID,
+        &mut ctx_accounts,
+        remaining,
+        bumps,
+    ))?;
+    Ok(ctx_accounts.ticket_account.owner)
+}
+
+#[test]
+fn first_bid_must_meet_reserve() {
+    let fixture = Fixture::new();
+    let bidder = Pubkey::new_unique();
+    let mut accounts = bid_accounts(&fixture, bidder, NOW + 3600, None);
+    assert_error(try_bid(&mut accounts, RESERVE - 1), ErrorCode::BidTooLow);
+
+    let mut accounts = bid_accounts(&fixture, bidder, NOW + 3600, None);
+    let state = try_bid(&mut accounts, RESERVE).unwrap();
+    assert_eq!(state.highest_bidder, Some(bidder));
+    assert_eq!(accounts[BID_ESCROW].lamports - bid_escrow_account(&fixture, 0).lamports, RESERVE);
+}
+
+#[test]
+fn bids_cover_the_rent_exempt_minimum() {
+    let fixture = Fixture::new();
+    let rent_exempt = Rent::default().minimum_balance(0);
+    let bidder = Pubkey::new_unique();
+    let mut accounts = bid_accounts(&fixture, bidder, NOW + 3600, None);
+    accounts[BID_LISTING] = listing_account(&fixture, 1, NOW + 3600, None);
+    assert_error(try_bid(&mut accounts, rent_exempt - 1), ErrorCode::BidTooLow);
+
+    let mut accounts = bid_accounts(&fixture, bidder, NOW + 3600, None);
+    accounts[BID_LISTING] = listing_account(&fixture, 1, NOW + 3600, None);
+    assert_eq!(try_bid(&mut accounts, rent_exempt).unwrap().highest_bid_lamports, rent_exempt);
+}
+
+#[test]
+fn outbid_bidder_is_refunded() {
+    let fixture = Fixture::new();
+    let previous = Pubkey::new_unique();
+    let highest = Some((previous, RESERVE));
+    let mut accounts = bid_accounts(&fixture, Pubkey::new_unique(), NOW + 3600, highest);
+    assert_error(try_bid(&mut accounts, RESERVE + INCREMENT - 1), ErrorCode::BidTooLow);
+
+    let mut accounts = bid_accounts(&fixture, Pubkey::new_unique(), NOW + 3600, highest);
+    let previous_lamports = accounts[BID_PREVIOUS_BIDDER].lamports;
+    try_bid(&mut accounts, RESERVE + INCREMENT).unwrap();
+    assert_eq!(accounts[BID_PREVIOUS_BIDDER].lamports - previous_lamports, RESERVE);
+    assert_eq!(
+        accounts[BID_ESCROW].lamports - bid_escrow_account(&fixture, 0).lamports,
+        RESERVE + INCREMENT
+    );
+}
+
+#[test]
+fn refund_must_go_to_highest_bidder() {
+    let fixture = Fixture::new();
+    let highest = Some((Pubkey::new_unique(), RESERVE));
+    let mut accounts = bid_accounts(&fixture, Pubkey::new_unique(), NOW + 3600, highest);
+    accounts[BID_PREVIOUS_BIDDER] = TestAccount::wallet(Pubkey::new_unique());
+    assert_error(try_bid(&mut accounts, 2 * SOL), ErrorCode::BidderMismatch);
+}
+
+#[test]
+fn late_bid_extends_auction() {
+    let fixture = Fixture::new();
+    let mut accounts = bid_accounts(&fixture, Pubkey::new_unique(), NOW + 60, None);
+    let state = try_bid(&mut accounts, RESERVE).unwrap();
+    assert_eq!(state.terms.end_ts, NOW + EXTENSION_SECS as i64);
+
+    let mut accounts = bid_accounts(&fixture, Pubkey::new_unique(), NOW + 3600, None);
+    assert_eq!(try_bid(&mut accounts, RESERVE).unwrap().terms.end_ts, NOW + 3600);
+
+    let mut accounts = bid_accounts(&fixture, Pubkey::new_unique(), NOW, None);
+    assert_error(try_bid(&mut accounts, RESERVE), ErrorCode::AuctionEnded);
+}
+
+#[test]
+fn seller_cannot_bid() {
+    let fixture = Fixture::new();
+    let mut accounts = bid_accounts(&fixture, fixture.holder, NOW + 3600, None);
+    assert_error(try_bid(&mut accounts, RESERVE), ErrorCode::Unauthorized);
+}
+
+#[test]
+fn settle_pays_seller_and_fee_to_winner_bid() {
+    let fixture = Fixture::new();
+    let winner = fixture.buyer;
+    let mut accounts = settle_accounts(&fixture, NOW, Some((winner, 2 * SOL)), winner);
+    let (seller_before, treasury_before, escrow_rent) = (
+        accounts[SETTLE_SELLER].lamports,
+        accounts[SETTLE_TREASURY].lamports,
+        accounts[SETTLE_ESCROW_NFT_ACCOUNT].lamports,
+    );
+    assert_eq!(try_settle(&mut accounts), Ok(winner));
+    // 5% marketplace fee to the platform treasury, the closed escrow's rent back to the seller
+    assert_eq!(accounts[SETTLE_TREASURY].lamports - treasury_before, SOL / 10);
+    assert_eq!(accounts[SETTLE_SELLER].lamports - seller_before, 2 * SOL - SOL / 10 + escrow_rent);
+    assert_eq!(accounts[SETTLE_ESCROW_NFT_ACCOUNT].lamports, 0);
+    assert_eq!(accounts[SETTLE_BID_ESCROW].lamports, bid_escrow_account(&fixture, 0).lamports);
+    assert_eq!(token_balance(&accounts[SETTLE_RECIPIENT_NFT_ACCOUNT]), 1);
+}
+
+#[test]
+fn settle_waits_for_end_and_winner() {
+    let fixture = Fixture::new();
+    let winner = fixture.buyer;
+    let mut accounts = settle_accounts(&fixture, NOW + 1, Some((winner, 2 * SOL)), winner);
+    assert_error(try_settle(&mut accounts), ErrorCode::AuctionNotEnded);
+
+    let mut accounts = settle_accounts(&fixture, NOW, Some((winner, 2 * SOL)), Pubkey::new_unique());
+    assert_error(try_settle(&mut accounts), ErrorCode::BidderMismatch);
+}
+
+#[test]
+fn settle_waits_for_unpause() {
+    let fixture = Fixture::new();
+    let winner = fixture.buyer;
+    let mut accounts = settle_accounts(&fixture, NOW, Some((winner, 2 * SOL)), winner);
+    accounts[SETTLE_CONFIG] = paused_config_account();
+    assert_error(try_settle(&mut accounts), ErrorCode::ProgramPaused);
+
+    let paused = EventAccount {
+        is_paused: true,
+        ..fixture.event_state()
+    };
+    let mut accounts = settle_accounts(&fixture, NOW, Some((winner, 2 * SOL)), winner);
+    accounts[SETTLE_EVENT] = fixture.event_account(&paused);
+    assert_error(try_settle(&mut accounts), ErrorCode::EventPaused);
+}
+
+#[test]
+fn settle_without_bids_returns_ticket_to_seller() {
+    let fixture = Fixture::new();
+    let mut accounts = settle_accounts(&fixture, NOW, None, fixture.holder);
+    assert_eq!(try_settle(&mut accounts), Ok(fixture.holder));
+    assert_eq!(token_balance(&accounts[SETTLE_RECIPIENT_NFT_ACCOUNT]), 1);
+}
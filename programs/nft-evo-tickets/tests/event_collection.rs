@@ -0,0 +1,280 @@
+//! Event collection NFTs, and the verified collection of every ticket sold.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+
+use common::{
+    assert_error, buy_accounts, event_pda, infos, pda, take_invoked, ticket_authority,
+    token_balance, try_buy, Fixture, TestAccount, BUY_COLLECTION_MASTER_EDITION,
+    BUY_COLLECTION_METADATA, BUY_COLLECTION_MINT, BUY_EVENT, BUY_METADATA,
+};
+use nft_evo_tickets::constants::{COLLECTION_SEED, PROGRAM_SEED, VAULT_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    create_event_handler, set_ticket_mint_mode_handler, CreateEventCtx, EventAccount,
+    EventVault, SetTicketMintModeCtx, TicketMintMode,
+};
+
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+const CREATE_MASTER_EDITION_V3: u8 = 17;
+const VERIFY_SIZED_COLLECTION_ITEM: u8 = 30;
+const MINT_TO: u8 = 7;
+const EVENT_NAME: &str = "A Festival Name Longer Than Metaplex Allows";
+
+/// Positions in `create_event_accounts` that tests swap out or check.
+const CREATE_COLLECTION_MINT: usize = 4;
+const CREATE_COLLECTION_METADATA: usize = 5;
+const CREATE_COLLECTION_TOKEN_ACCOUNT: usize = 7;
+
+/// `CreateMetadataAccountV3` data up to the collection details, creators as
+/// (address, verified, share), collections as (verified, key) and uses as (method, remaining, total).
+type CreateMetadataArgs = (
+    String,
+    String,
+    String,
+    u16,
+    Option<Vec<(Pubkey, bool, u8)>>,
+    Option<(bool, Pubkey)>,
+    Option<(u8, u64, u64)>,
+    bool,
+    Option<(u8, u64)>,
+);
+
+fn collection_mint(event: &Pubkey) -> Pubkey {
+    pda(&[PROGRAM_SEED.as_bytes(), COLLECTION_SEED.as_bytes(), event.as_ref()])
+}
+
+fn metaplex(invoked: &[Instruction]) -> Vec<&Instruction> {
+    invoked
+        .iter()
+        .filter(|ix| ix.program_id == mpl_token_metadata::ID)
+        .collect()
+}
+
+/// Accounts for `create_event` of event 1 by `organizer`, with the collection accounts.
+fn create_event_accounts(organizer: Pubkey) -> Vec<TestAccount> {
+    let event = event_pda(1);
+    let mint = collection_mint(&event);
+    vec![
+        TestAccount::signer(organizer),
+        TestAccount::uninitialized(event, 8 + EventAccount::INIT_SPACE),
+        TestAccount::uninitialized(
+            pda(&[PROGRAM_SEED.as_bytes(), VAULT_SEED.as_bytes(), event.as_ref()]),
+            8 + EventVault::INIT_SPACE,
+        ),
+        TestAccount::program(System::id()),
+        TestAccount::uninitialized(mint, spl_token::state::Mint::LEN),
+        TestAccount::wallet(Metadata::find_pda(&mint).0),
+        TestAccount::wallet(MasterEdition::find_pda(&mint).0),
+        TestAccount::uninitialized(
+            get_associated_token_address(&ticket_authority().key, &mint),
+            spl_token::state::Account::LEN,
+        ),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(mpl_token_metadata::ID),
+    ]
+}
+
+/// Runs `create_event`, returning the new event and the CPIs made.
+fn try_create_event(accounts: &mut [TestAccount]) -> Result<(EventAccount, Vec<Instruction>)> {
+    let args = (1u64, EVENT_NAME.to_string(), 0i64, 100i64, 10u32, String::new());
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = CreateEventCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &args.try_to_vec().unwrap(),
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    let (event_id, name, start_ts, end_ts, ticket_supply, cover_image_url) = args;
+    create_event_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        event_id,
+        name,
+        start_ts,
+        end_ts,
+        ticket_supply,
+        cover_image_url,
+    )?;
+    Ok(((*ctx_accounts.event_account).clone(), take_invoked()))
+}
+
+/// Buy accounts of an event with a collection, with its collection accounts.
+fn collection_buy_accounts(buyer: Pubkey, event: &EventAccount) -> Vec<TestAccount> {
+    let mint = event.collection_mint.unwrap();
+    let mut accounts = buy_accounts(buyer, event);
+    accounts[BUY_COLLECTION_MINT] = TestAccount::wallet(mint);
+    accounts[BUY_COLLECTION_METADATA] = TestAccount::wallet(Metadata::find_pda(&mint).0);
+    accounts[BUY_COLLECTION_MASTER_EDITION] = TestAccount::wallet(MasterEdition::find_pda(&mint).0);
+    accounts
+}
+
+fn collection_event(fixture: &Fixture) -> EventAccount {
+    EventAccount {
+        collection_mint: Some(collection_mint(&fixture.event)),
+        ..fixture.event_state()
+    }
+}
+
+#[test]
+fn create_event_mints_a_sized_collection_held_by_the_ticket_authority() {
+    let fixture = Fixture::new();
+    let mut accounts = create_event_accounts(fixture.authority);
+    let (event, invoked) = try_create_event(&mut accounts).unwrap();
+    let mint = collection_mint(&fixture.event);
+    assert_eq!(event.collection_mint, Some(mint));
+
+    let mint_to = invoked
+        .iter()
+        .find(|ix| ix.program_id == spl_token::ID && ix.data[0] == MINT_TO)
+        .expect("no MintTo CPI");
+    assert!(mint_to.accounts[2].pubkey == ticket_authority().key && mint_to.accounts[2].is_signer);
+    assert_eq!(token_balance(&accounts[CREATE_COLLECTION_TOKEN_ACCOUNT]), 1);
+
+    let metaplex = metaplex(&invoked);
+    assert_eq!(metaplex.len(), 2);
+    assert_eq!(metaplex[0].data[0], CREATE_METADATA_ACCOUNT_V3);
+    assert_eq!(metaplex[0].accounts[4].pubkey, ticket_authority().key);
+    let (name, symbol, _, royalty_bps, creators, collection, _, is_mutable, collection_details): CreateMetadataArgs =
+        AnchorDeserialize::deserialize(&mut &metaplex[0].data[1..]).unwrap();
+    assert_eq!((name.as_str(), symbol.as_str()), (&EVENT_NAME[..32], "TIX"));
+    assert_eq!((royalty_bps, creators, collection, is_mutable), (0, None, None, true));
+    // Sized collections count their verified tickets
+    assert_eq!(collection_details, Some((0, 0)));
+
+    assert_eq!(metaplex[1].data[0], CREATE_MASTER_EDITION_V3);
+    assert_eq!(metaplex[1].accounts[0].pubkey, MasterEdition::find_pda(&mint).0);
+    assert!(metaplex.iter().all(|ix| ix.accounts.iter().any(|meta| meta.pubkey == ticket_authority().key && meta.is_signer)));
+}
+
+#[test]
+fn create_event_without_collection_accounts_has_no_collection() {
+    let fixture = Fixture::new();
+    let mut accounts = create_event_accounts(fixture.authority);
+    for account in &mut accounts[CREATE_COLLECTION_MINT..] {
+        *account = TestAccount::program(nft_evo_tickets::ID);
+    }
+    let (event, invoked) = try_create_event(&mut accounts).unwrap();
+    assert_eq!(event.collection_mint, None);
+    assert!(metaplex(&invoked).is_empty());
+}
+
+#[test]
+fn create_event_checks_the_collection_accounts() {
+    let fixture = Fixture::new();
+    let mut accounts = create_event_accounts(fixture.authority);
+    accounts[CREATE_COLLECTION_METADATA] = TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_create_event(&mut accounts), ErrorCode::MissingCollectionAccounts);
+
+    let mut accounts = create_event_accounts(fixture.authority);
+    accounts[CREATE_COLLECTION_MINT] = TestAccount::uninitialized(Pubkey::new_unique(), 0);
+    let result = try_create_event(&mut accounts);
+    assert!(matches!(result, Err(Error::AnchorError(e)) if e.error_name == "ConstraintSeeds"));
+}
+
+#[test]
+fn purchased_tickets_are_verified_into_the_event_collection() {
+    let fixture = Fixture::new();
+    let event = collection_event(&fixture);
+    let mint = event.collection_mint.unwrap();
+    let mut accounts = collection_buy_accounts(fixture.buyer, &event);
+    take_invoked();
+    try_buy(&mut accounts, None).unwrap();
+    let invoked = take_invoked();
+    let metaplex = metaplex(&invoked);
+    assert_eq!(metaplex.len(), 3);
+
+    let (.., collection, _, _, _): CreateMetadataArgs =
+        AnchorDeserialize::deserialize(&mut &metaplex[0].data[1..]).unwrap();
+    assert_eq!(collection, Some((false, mint)));
+
+    // Verified last, once the ticket's metadata and edition exist
+    let verify = metaplex[2];
+    assert_eq!(verify.data, vec![VERIFY_SIZED_COLLECTION_ITEM]);
+    let keys: Vec<_> = verify.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys[..6],
+        [
+            accounts[BUY_METADATA].key,
+            ticket_authority().key,
+            fixture.buyer,
+            mint,
+            Metadata::find_pda(&mint).0,
+            MasterEdition::find_pda(&mint).0,
+        ]
+    );
+    assert!(verify.accounts[1].is_signer);
+}
+
+#[test]
+fn purchases_need_the_events_collection() {
+    let fixture = Fixture::new();
+    let buyer = fixture.buyer;
+    let event = collection_event(&fixture);
+    let mut accounts = buy_accounts(buyer, &event);
+    assert_error(try_buy(&mut accounts, None), ErrorCode::MissingCollectionAccounts);
+
+    let mut accounts = collection_buy_accounts(buyer, &event);
+    accounts[BUY_COLLECTION_MINT] = TestAccount::wallet(collection_mint(&event_pda(2)));
+    assert_error(try_buy(&mut accounts, None), ErrorCode::CollectionMismatch);
+
+    // Events without a collection don't take one either
+    let mut accounts = collection_buy_accounts(buyer, &event);
+    accounts[BUY_EVENT] = fixture.event_account(&fixture.event_state());
+    assert_error(try_buy(&mut accounts, None), ErrorCode::CollectionMismatch);
+
+    // Without a collection tickets are minted as before
+    let mut accounts = buy_accounts(buyer, &fixture.event_state());
+    take_invoked();
+    try_buy(&mut accounts, None).unwrap();
+    assert_eq!(metaplex(&take_invoked()).len(), 2);
+}
+
+#[test]
+fn collection_events_keep_legacy_ticket_mints() {
+    let fixture = Fixture::new();
+    let try_set = |event: &EventAccount, mode: TicketMintMode| -> Result<TicketMintMode> {
+        let mut accounts = vec![
+            TestAccount::signer(event.authority),
+            fixture.event_account(event),
+        ];
+        let infos = infos(&mut accounts);
+        let mut remaining = &infos[..];
+        let mut bumps = Default::default();
+        let mut ctx_accounts = SetTicketMintModeCtx::try_accounts(
+            &nft_evo_tickets::ID,
+            &mut remaining,
+            &mode.try_to_vec().unwrap(),
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        set_ticket_mint_mode_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps), mode)?;
+        Ok(ctx_accounts.event_account.ticket_mint_mode)
+    };
+    let event = EventAccount {
+        tickets_sold: 0,
+        ..collection_event(&fixture)
+    };
+    for mode in [TicketMintMode::NonTransferable, TicketMintMode::TransferHook] {
+        assert_error(try_set(&event, mode), ErrorCode::CollectionRequiresLegacyMints);
+    }
+    assert!(try_set(&event, TicketMintMode::Legacy).unwrap() == TicketMintMode::Legacy);
+
+    let without_collection = EventAccount {
+        collection_mint: None,
+        ..event
+    };
+    assert!(try_set(&without_collection, TicketMintMode::TransferHook).unwrap() == TicketMintMode::TransferHook);
+}
@@ -0,0 +1,378 @@
+//! Account substitution attacks against the marketplace instructions.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, bump, config_account, event_account, event_pda, infos, ticket_authority,
+    token_balance, treasury, Fixture, TestAccount,
+};
+use nft_evo_tickets::constants::{LISTING_SEED, PROGRAM_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    buy_marketplace_ticket_handler, cancel_listing_handler, BuyMarketplaceTicketCtx,
+    CancelListingCtx, EventAccount, ListTicketCtx, ListingAccount, RoyaltyRecipient, TicketAccount,
+};
+
+const PRICE: u64 = 1_000_000_000;
+
+/// Positions in `buy_accounts`, `list_accounts` and `cancel_accounts` that tests swap out.
+const BUY_LISTING: usize = 3;
+const BUY_EVENT: usize = 4;
+const BUY_TREASURY: usize = 5;
+const BUY_SELLER: usize = 6;
+const BUY_MINT: usize = 8;
+const BUY_ESCROW_NFT_ACCOUNT: usize = 9;
+const BUY_BUYER_NFT_ACCOUNT: usize = 10;
+/// The first of the buyer, seller and treasury payment token accounts.
+const BUY_PAYMENT_ACCOUNTS: usize = 17;
+const LIST_TICKET: usize = 2;
+const LIST_EVENT: usize = 3;
+const LIST_MINT: usize = 6;
+const LIST_SELLER_NFT_ACCOUNT: usize = 7;
+const LIST_ESCROW_NFT_ACCOUNT: usize = 8;
+const CANCEL_MINT: usize = 4;
+const CANCEL_ESCROW_NFT_ACCOUNT: usize = 5;
+const CANCEL_SELLER_NFT_ACCOUNT: usize = 6;
+
+/// `holder`'s listing of the ticket at `PRICE`, in `payment_mint` when given.
+fn listing_account(fixture: &Fixture, payment_mint: Option<Pubkey>) -> TestAccount {
+    let listing = ListingAccount {
+        ticket: fixture.ticket,
+        seller: fixture.holder,
+        price_lamports: PRICE,
+        payment_mint,
+        created_at: 0,
+        expires_at: None,
+        auction: None,
+        bump: bump(&[PROGRAM_SEED.as_bytes(), LISTING_SEED.as_bytes(), fixture.ticket.as_ref()]),
+    };
+    TestAccount::anchor(fixture.listing(), &listing, 8 + ListingAccount::INIT_SPACE)
+}
+
+/// The event with an organizer royalty split of `(recipient, share_bps)`.
+fn royalty_event_account(fixture: &Fixture, recipients: &[(Pubkey, u16)]) -> TestAccount {
+    let event = EventAccount {
+        royalty_bps: recipients.iter().map(|(_, share_bps)| share_bps).sum(),
+        royalty_recipients: recipients
+            .iter()
+            .map(|&(address, share_bps)| RoyaltyRecipient { address, share_bps })
+            .collect(),
+        ..fixture.event_state()
+    };
+    fixture.event_account(&event)
+}
+
+/// Accounts for `buy_marketplace_ticket` of `holder`'s listing by `buyer`, in context order.
+fn buy_accounts(fixture: &Fixture) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(fixture.buyer),
+        config_account(),
+        fixture.ticket_account(&fixture.ticket_state(true)),
+        listing_account(fixture, None),
+        fixture.event_account(&fixture.event_state()),
+        TestAccount::wallet(treasury()),
+        TestAccount::wallet(fixture.holder),
+        fixture.seller_listings_account(Some(1)),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.listing(), 1),
+        fixture.nft_account(fixture.buyer, 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(System::id()),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::rent_sysvar(),
+        // SOL listing: no payment token accounts
+        TestAccount::program(nft_evo_tickets::ID),
+        TestAccount::program(nft_evo_tickets::ID),
+        TestAccount::program(nft_evo_tickets::ID),
+    ]
+}
+
+/// `buy_accounts` for a listing priced in `payment_mint`.
+fn token_buy_accounts(fixture: &Fixture, payment_mint: Pubkey) -> Vec<TestAccount> {
+    let mut accounts = buy_accounts(fixture);
+    accounts[BUY_LISTING] = listing_account(fixture, Some(payment_mint));
+    accounts.truncate(BUY_PAYMENT_ACCOUNTS);
+    accounts.push(TestAccount::token(payment_mint, fixture.buyer, PRICE));
+    accounts.push(TestAccount::token(payment_mint, fixture.holder, 0));
+    accounts.push(TestAccount::token(payment_mint, treasury(), 0));
+    accounts
+}
+
+/// Accounts for `list_ticket` by `holder`, in context order.
+fn list_accounts(fixture: &Fixture) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(fixture.holder),
+        config_account(),
+        fixture.ticket_account(&fixture.ticket_state(false)),
+        fixture.event_account(&fixture.event_state()),
+        TestAccount::uninitialized(fixture.listing(), 8 + ListingAccount::INIT_SPACE),
+        fixture.seller_listings_account(None),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.holder, 1),
+        fixture.nft_account(fixture.listing(), 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+        TestAccount::rent_sysvar(),
+        TestAccount::program(nft_evo_tickets::ID), // bid escrow
+    ]
+}
+
+/// Accounts for `cancel_listing` by `holder`, in context order.
+fn cancel_accounts(fixture: &Fixture) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(fixture.holder),
+        fixture.ticket_account(&fixture.ticket_state(true)),
+        listing_account(fixture, None),
+        fixture.seller_listings_account(Some(1)),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.listing(), 1),
+        fixture.nft_account(fixture.holder, 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(System::id()),
+        TestAccount::program(nft_evo_tickets::ID), // bid escrow
+    ]
+}
+
+fn try_buy(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    BuyMarketplaceTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut Default::default(),
+        &mut BTreeSet::new(),
+    )
+    .map(|_| ())
+}
+
+/// Runs the full purchase, anything after the context accounts is passed as remaining accounts.
+fn try_buy_paying(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = BuyMarketplaceTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    buy_marketplace_ticket_handler(Context::new(
+        &nft_evo_tickets::ID,
+        &mut ctx_accounts,
+        remaining,
+        bumps,
+    ))
+}
+
+fn try_list(accounts: &mut [TestAccount]) -> Result<()> {
+    let mut ix_data = PRICE.to_le_bytes().to_vec();
+    ix_data.push(0); // expires_at: None
+    ix_data.push(0); // auction: None
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    ListTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut Default::default(),
+        &mut BTreeSet::new(),
+    )
+    .map(|_| ())
+}
+
+fn try_cancel(accounts: &mut [TestAccount]) -> Result<()> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = CancelListingCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    cancel_listing_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, remaining, bumps))
+}
+
+#[test]
+fn buy_accepts_honest_accounts() {
+    let fixture = Fixture::new();
+    try_buy(&mut buy_accounts(&fixture)).unwrap();
+}
+
+#[test]
+fn buy_rejects_buyer_posing_as_seller() {
+    let fixture = Fixture::new();
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_SELLER] = TestAccount::wallet(fixture.buyer);
+    assert_error(try_buy(&mut accounts), ErrorCode::SellerMismatch);
+}
+
+#[test]
+fn buy_rejects_ticket_of_another_event() {
+    let fixture = Fixture::new();
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_EVENT] = event_account(event_pda(2), 2, fixture.buyer);
+    assert_error(try_buy(&mut accounts), ErrorCode::EventMismatch);
+}
+
+#[test]
+fn buy_pays_fee_to_the_platform_treasury() {
+    let fixture = Fixture::new();
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_TREASURY] = TestAccount::wallet(fixture.buyer);
+    assert_error(try_buy(&mut accounts), ErrorCode::TreasuryMismatch);
+
+    let mut accounts = buy_accounts(&fixture);
+    let before: Vec<u64> = accounts.iter().map(|a| a.lamports).collect();
+    try_buy_paying(&mut accounts).unwrap();
+    let gained = |i: usize| accounts[i].lamports - before[i];
+    assert_eq!(gained(BUY_SELLER), PRICE / 20 * 19 + before[BUY_ESCROW_NFT_ACCOUNT]);
+    assert_eq!(gained(BUY_TREASURY), PRICE / 20);
+    assert_eq!(accounts[BUY_ESCROW_NFT_ACCOUNT].lamports, 0);
+}
+
+#[test]
+fn buy_rejects_substituted_mint() {
+    let fixture = Fixture::new();
+    let other_mint = Pubkey::new_unique();
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_MINT] = TestAccount::mint(other_mint, fixture.ticket);
+    accounts[BUY_ESCROW_NFT_ACCOUNT] = TestAccount::token(other_mint, fixture.listing(), 1);
+    accounts[BUY_BUYER_NFT_ACCOUNT] = TestAccount::token(other_mint, fixture.buyer, 0);
+    assert_error(try_buy(&mut accounts), ErrorCode::MintMismatch);
+}
+
+#[test]
+fn buy_accepts_token_payment_accounts() {
+    let fixture = Fixture::new();
+    try_buy(&mut token_buy_accounts(&fixture, Pubkey::new_unique())).unwrap();
+}
+
+#[test]
+fn buy_rejects_payment_in_another_token() {
+    let fixture = Fixture::new();
+    let usdc = Pubkey::new_unique();
+    let mut accounts = token_buy_accounts(&fixture, usdc);
+    accounts[BUY_PAYMENT_ACCOUNTS] = TestAccount::token(Pubkey::new_unique(), fixture.buyer, PRICE);
+    assert_error(try_buy(&mut accounts), ErrorCode::PaymentMintMismatch);
+}
+
+#[test]
+fn buy_pays_royalty_split_to_recipients() {
+    let fixture = Fixture::new();
+    let (artist, venue) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_EVENT] = royalty_event_account(&fixture, &[(artist, 600), (venue, 400)]);
+    accounts.push(TestAccount::wallet(artist));
+    accounts.push(TestAccount::wallet(venue));
+    let before: Vec<u64> = accounts.iter().map(|a| a.lamports).collect();
+
+    try_buy_paying(&mut accounts).unwrap();
+
+    let gained = |i: usize| accounts[i].lamports - before[i];
+    assert_eq!(gained(BUY_SELLER), PRICE / 10 * 9 + before[BUY_ESCROW_NFT_ACCOUNT]);
+    assert_eq!(gained(BUY_TREASURY), 0);
+    assert_eq!(gained(accounts.len() - 2), PRICE / 100 * 6);
+    assert_eq!(gained(accounts.len() - 1), PRICE / 100 * 4);
+}
+
+#[test]
+fn buy_rejects_substituted_royalty_recipient() {
+    let fixture = Fixture::new();
+    let (artist, venue) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_EVENT] = royalty_event_account(&fixture, &[(artist, 600), (venue, 400)]);
+    accounts.push(TestAccount::wallet(fixture.buyer));
+    accounts.push(TestAccount::wallet(venue));
+    assert_error(try_buy_paying(&mut accounts), ErrorCode::RoyaltyRecipientMismatch);
+}
+
+#[test]
+fn buy_rejects_missing_royalty_recipient() {
+    let fixture = Fixture::new();
+    let (artist, venue) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = buy_accounts(&fixture);
+    accounts[BUY_EVENT] = royalty_event_account(&fixture, &[(artist, 600), (venue, 400)]);
+    accounts.push(TestAccount::wallet(artist));
+    assert_error(try_buy_paying(&mut accounts), ErrorCode::RoyaltyRecipientMismatch);
+}
+
+#[test]
+fn list_accepts_honest_accounts() {
+    let fixture = Fixture::new();
+    try_list(&mut list_accounts(&fixture)).unwrap();
+}
+
+#[test]
+fn list_rejects_substituted_event() {
+    let fixture = Fixture::new();
+    let mut accounts = list_accounts(&fixture);
+    accounts[LIST_EVENT] = event_account(event_pda(2), 2, fixture.authority);
+    assert_error(try_list(&mut accounts), ErrorCode::EventMismatch);
+}
+
+#[test]
+fn list_rejects_substituted_mint() {
+    let fixture = Fixture::new();
+    let other_mint = Pubkey::new_unique();
+    let mut accounts = list_accounts(&fixture);
+    accounts[LIST_MINT] = TestAccount::mint(other_mint, fixture.ticket);
+    accounts[LIST_SELLER_NFT_ACCOUNT] = TestAccount::token(other_mint, fixture.holder, 1);
+    accounts[LIST_ESCROW_NFT_ACCOUNT] = TestAccount::token(other_mint, fixture.listing(), 0);
+    assert_error(try_list(&mut accounts), ErrorCode::MintMismatch);
+}
+
+#[test]
+fn list_accepts_holder_with_stale_ticket_owner() {
+    let fixture = Fixture::new();
+    let mut accounts = list_accounts(&fixture);
+    let stale = TicketAccount {
+        owner: fixture.buyer,
+        ..fixture.ticket_state(false)
+    };
+    accounts[LIST_TICKET] = fixture.ticket_account(&stale);
+    try_list(&mut accounts).unwrap();
+}
+
+#[test]
+fn list_rejects_seller_without_the_token() {
+    let fixture = Fixture::new();
+    let mut accounts = list_accounts(&fixture);
+    accounts[LIST_SELLER_NFT_ACCOUNT] = fixture.nft_account(fixture.holder, 0);
+    assert_error(try_list(&mut accounts), ErrorCode::NotTicketHolder);
+}
+
+#[test]
+fn cancel_accepts_honest_accounts() {
+    let fixture = Fixture::new();
+    let mut accounts = cancel_accounts(&fixture);
+    let (seller_before, escrow_rent) = (accounts[0].lamports, accounts[CANCEL_ESCROW_NFT_ACCOUNT].lamports);
+    try_cancel(&mut accounts).unwrap();
+    assert_eq!(token_balance(&accounts[CANCEL_SELLER_NFT_ACCOUNT]), 1);
+    // The escrow is closed, its rent goes back to the seller
+    assert_eq!(accounts[CANCEL_ESCROW_NFT_ACCOUNT].lamports, 0);
+    assert_eq!(accounts[0].lamports - seller_before, escrow_rent);
+}
+
+#[test]
+fn cancel_rejects_substituted_mint() {
+    let fixture = Fixture::new();
+    let other_mint = Pubkey::new_unique();
+    let mut accounts = cancel_accounts(&fixture);
+    accounts[CANCEL_MINT] = TestAccount::mint(other_mint, fixture.ticket);
+    accounts[CANCEL_ESCROW_NFT_ACCOUNT] = TestAccount::token(other_mint, fixture.listing(), 1);
+    accounts[CANCEL_SELLER_NFT_ACCOUNT] = TestAccount::token(other_mint, fixture.holder, 0);
+    assert_error(try_cancel(&mut accounts), ErrorCode::MintMismatch);
+}
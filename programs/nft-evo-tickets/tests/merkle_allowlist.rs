@@ -0,0 +1,251 @@
+//! Merkle allowlist proofs on presale purchases and root rotation.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use common::{
+    assert_error, bump, buy_accounts, infos, pda, try_buy_with_proof, Bought, Fixture, TestAccount,
+    BUY_ALLOWLIST_CLAIMS, BUY_MERKLE_ALLOWLIST, NOW,
+};
+use nft_evo_tickets::constants::{ALLOWLIST_CLAIMS_SEED, MERKLE_ALLOWLIST_SEED, PROGRAM_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    set_allowlist_root_handler, AllowlistClaims, AllowlistProof, EventAccount, MerkleAllowlist,
+    SalePhase, SaleSchedule, SetAllowlistRootCtx,
+};
+
+const HOUR: i64 = 3600;
+
+/// Hashes of the allowlist's four leaves: `fan` holds slots 0..2, three other
+/// wallets one slot each after it.
+fn leaf_hashes(fan: Pubkey) -> Vec<[u8; 32]> {
+    [(fan, 0, 2), ([2; 32].into(), 2, 1), ([3; 32].into(), 3, 1), ([4; 32].into(), 4, 1)]
+        .iter()
+        .map(|(wallet, index, allocation)| MerkleAllowlist::leaf(wallet, *index, *allocation))
+        .collect()
+}
+
+fn root(fan: Pubkey) -> [u8; 32] {
+    let hashes = leaf_hashes(fan);
+    MerkleAllowlist::node(
+        &MerkleAllowlist::node(&hashes[0], &hashes[1]),
+        &MerkleAllowlist::node(&hashes[2], &hashes[3]),
+    )
+}
+
+/// Proof for `fan`'s leaf.
+fn fan_proof(fan: Pubkey) -> AllowlistProof {
+    let hashes = leaf_hashes(fan);
+    AllowlistProof {
+        index: 0,
+        allocation: 2,
+        proof: vec![hashes[1], MerkleAllowlist::node(&hashes[2], &hashes[3])],
+    }
+}
+
+/// An event in its presale window.
+fn event(fixture: &Fixture) -> EventAccount {
+    EventAccount {
+        sale_schedule: Some(SaleSchedule {
+            presale_start_ts: Some(NOW),
+            presale_price_lamports: 500_000_000,
+            allowlist_start_ts: Some(NOW + HOUR),
+            public_start_ts: NOW + 2 * HOUR,
+            sales_close_ts: NOW + 3 * HOUR,
+        }),
+        ..fixture.event_state()
+    }
+}
+
+fn allowlist_seeds(event: &Pubkey, phase: SalePhase) -> Vec<Vec<u8>> {
+    vec![
+        PROGRAM_SEED.as_bytes().to_vec(),
+        MERKLE_ALLOWLIST_SEED.as_bytes().to_vec(),
+        event.to_bytes().to_vec(),
+        vec![phase as u8],
+    ]
+}
+
+/// The event's Merkle allowlist for `phase` and its claims bitmap.
+fn allowlist_accounts(
+    fixture: &Fixture,
+    phase: SalePhase,
+    root: [u8; 32],
+    claimed: &[u32],
+) -> (TestAccount, TestAccount) {
+    let event = fixture.event;
+    let seeds = allowlist_seeds(&event, phase);
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    let allowlist_key = pda(&seeds);
+    let allowlist = MerkleAllowlist {
+        event,
+        phase,
+        root,
+        slot_count: 5,
+        bump: bump(&seeds),
+    };
+    let claims_seeds: &[&[u8]] = &[
+        PROGRAM_SEED.as_bytes(),
+        ALLOWLIST_CLAIMS_SEED.as_bytes(),
+        allowlist_key.as_ref(),
+    ];
+    let mut claims = AllowlistClaims {
+        allowlist: allowlist_key,
+        claimed: [0; 1024],
+        bump: bump(claims_seeds),
+    };
+    for slot in claimed {
+        claims.claimed[*slot as usize / 8] |= 1 << (slot % 8);
+    }
+    (
+        TestAccount::anchor(allowlist_key, &allowlist, 8 + MerkleAllowlist::INIT_SPACE),
+        TestAccount::anchor(pda(claims_seeds), &claims, 8 + AllowlistClaims::INIT_SPACE),
+    )
+}
+
+/// Buys as `buyer` against the presale allowlist of `fixture.buyer`'s tree, with
+/// slots in `claimed` already used.
+fn try_buy_presale(
+    fixture: &Fixture,
+    buyer: Pubkey,
+    phase: SalePhase,
+    claimed: &[u32],
+    proof: Option<AllowlistProof>,
+) -> Result<Bought> {
+    let mut accounts = buy_accounts(buyer, &event(fixture));
+    let (allowlist, claims) = allowlist_accounts(fixture, phase, root(fixture.buyer), claimed);
+    accounts[BUY_MERKLE_ALLOWLIST] = allowlist;
+    accounts[BUY_ALLOWLIST_CLAIMS] = claims;
+    try_buy_with_proof(&mut accounts, None, proof)
+}
+
+fn is_claimed(claims: &AllowlistClaims, slot: u32) -> bool {
+    claims.claimed[slot as usize / 8] & (1 << (slot % 8)) != 0
+}
+
+#[test]
+fn proof_buys_presale_ticket_and_claims_slot() {
+    let fixture = Fixture::new();
+    let bought = try_buy_presale(&fixture, fixture.buyer, SalePhase::Presale, &[], Some(fan_proof(fixture.buyer)))
+        .unwrap();
+    assert_eq!(bought.ticket.price_paid_lamports, 500_000_000);
+    let claims = bought.allowlist_claims.unwrap();
+    assert!(is_claimed(&claims, 0));
+    assert!(!is_claimed(&claims, 1));
+}
+
+#[test]
+fn allocation_limits_purchases() {
+    let fixture = Fixture::new();
+    let bought = try_buy_presale(&fixture, fixture.buyer, SalePhase::Presale, &[0], Some(fan_proof(fixture.buyer)))
+        .unwrap();
+    assert!(is_claimed(&bought.allowlist_claims.unwrap(), 1));
+
+    assert_error(
+        try_buy_presale(&fixture, fixture.buyer, SalePhase::Presale, &[0, 1], Some(fan_proof(fixture.buyer))),
+        ErrorCode::AllowlistAllocationUsed,
+    );
+}
+
+#[test]
+fn proof_must_match_buyer_and_leaf() {
+    let fixture = Fixture::new();
+    assert_error(
+        try_buy_presale(
+            &fixture,
+            Pubkey::new_unique(),
+            SalePhase::Presale,
+            &[],
+            Some(fan_proof(fixture.buyer)),
+        ),
+        ErrorCode::InvalidAllowlistProof,
+    );
+
+    let inflated = AllowlistProof {
+        allocation: 3,
+        ..fan_proof(fixture.buyer)
+    };
+    assert_error(
+        try_buy_presale(&fixture, fixture.buyer, SalePhase::Presale, &[], Some(inflated)),
+        ErrorCode::InvalidAllowlistProof,
+    );
+}
+
+#[test]
+fn proof_is_required_for_current_phase() {
+    let fixture = Fixture::new();
+    assert_error(
+        try_buy_presale(&fixture, fixture.buyer, SalePhase::Presale, &[], None),
+        ErrorCode::NotOnAllowlist,
+    );
+    assert_error(
+        try_buy_presale(&fixture, fixture.buyer, SalePhase::Allowlist, &[], Some(fan_proof(fixture.buyer))),
+        ErrorCode::NotOnAllowlist,
+    );
+}
+
+/// Runs `set_allowlist_root` against existing allowlist accounts, returning them after.
+fn try_set_root(
+    fixture: &Fixture,
+    signer: Pubkey,
+    phase: SalePhase,
+    root: [u8; 32],
+    claimed: &[u32],
+) -> Result<(MerkleAllowlist, AllowlistClaims)> {
+    let (allowlist, claims) = allowlist_accounts(fixture, phase, [0; 32], claimed);
+    let mut accounts = vec![
+        TestAccount::signer(signer),
+        fixture.event_account(&event(fixture)),
+        allowlist,
+        claims,
+        TestAccount::program(System::id()),
+    ];
+    let mut ix_data = vec![phase as u8];
+    root.serialize(&mut ix_data).unwrap();
+    5u32.serialize(&mut ix_data).unwrap();
+    let infos = infos(&mut accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SetAllowlistRootCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    set_allowlist_root_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        phase,
+        root,
+        5,
+    )?;
+    Ok((
+        (*ctx_accounts.merkle_allowlist).clone(),
+        (**ctx_accounts.allowlist_claims).clone(),
+    ))
+}
+
+#[test]
+fn rotating_root_keeps_claims() {
+    let fixture = Fixture::new();
+    let root = root(fixture.buyer);
+    let (allowlist, claims) =
+        try_set_root(&fixture, fixture.authority, SalePhase::Presale, root, &[0]).unwrap();
+    assert_eq!(allowlist.root, root);
+    assert!(is_claimed(&claims, 0));
+}
+
+#[test]
+fn only_authority_sets_root_for_early_phases() {
+    let fixture = Fixture::new();
+    assert_error(
+        try_set_root(&fixture, Pubkey::new_unique(), SalePhase::Presale, [1; 32], &[]),
+        ErrorCode::Unauthorized,
+    );
+    assert_error(
+        try_set_root(&fixture, fixture.authority, SalePhase::Public, [1; 32], &[]),
+        ErrorCode::InvalidInput,
+    );
+}
@@ -0,0 +1,235 @@
+//! Offers on unlisted tickets, for one ticket or any ticket of an event.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, bump, config_account, infos, pda, ticket_authority, token_balance, treasury,
+    Fixture, TestAccount, NOW,
+};
+use nft_evo_tickets::constants::{OFFER_SEED, PROGRAM_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    accept_offer_handler, cancel_offer_handler, make_offer_handler, AcceptOfferCtx,
+    CancelOfferCtx, EventAccount, MakeOfferCtx, OfferAccount,
+};
+
+const PRICE: u64 = 2_000_000_000;
+
+/// Positions in `make_accounts` and `accept_accounts` that tests swap out or check.
+const MAKE_TICKET: usize = 3;
+const MAKE_OFFER: usize = 4;
+const ACCEPT_SELLER: usize = 0;
+const ACCEPT_TREASURY: usize = 6;
+const ACCEPT_SELLER_NFT_ACCOUNT: usize = 8;
+const ACCEPT_BUYER_NFT_ACCOUNT: usize = 9;
+
+fn offer_seeds(fixture: &Fixture, ticket: Option<Pubkey>) -> Vec<Vec<u8>> {
+    vec![
+        PROGRAM_SEED.as_bytes().to_vec(),
+        OFFER_SEED.as_bytes().to_vec(),
+        ticket.unwrap_or(fixture.event).to_bytes().to_vec(),
+        fixture.buyer.to_bytes().to_vec(),
+    ]
+}
+
+fn offer_key(fixture: &Fixture, ticket: Option<Pubkey>) -> Pubkey {
+    pda(&offer_seeds(fixture, ticket).iter().map(Vec::as_slice).collect::<Vec<_>>())
+}
+
+/// An open offer of PRICE by `buyer` for `ticket`, or any ticket when None, escrowing the price.
+fn offer_account(fixture: &Fixture, ticket: Option<Pubkey>, expires_at: Option<i64>) -> TestAccount {
+    let seeds = offer_seeds(fixture, ticket);
+    let offer = OfferAccount {
+        buyer: fixture.buyer,
+        event: fixture.event,
+        ticket,
+        price_lamports: PRICE,
+        created_at: 0,
+        expires_at,
+        bump: bump(&seeds.iter().map(Vec::as_slice).collect::<Vec<_>>()),
+    };
+    let mut account = TestAccount::anchor(offer_key(fixture, ticket), &offer, 8 + OfferAccount::INIT_SPACE);
+    account.lamports += PRICE;
+    account
+}
+
+/// Accounts for `make_offer` by `buyer`, naming the ticket for ticket offers.
+fn make_accounts(fixture: &Fixture, event: &EventAccount, ticket: Option<Pubkey>) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(fixture.buyer),
+        config_account(),
+        fixture.event_account(event),
+        match ticket {
+            Some(_) => fixture.ticket_account(&fixture.ticket_state(false)),
+            None => TestAccount::program(nft_evo_tickets::ID),
+        },
+        TestAccount::uninitialized(offer_key(fixture, ticket), 8 + OfferAccount::INIT_SPACE),
+        TestAccount::program(System::id()),
+    ]
+}
+
+/// Accounts for `accept_offer` of `offer` by `holder`.
+fn accept_accounts(fixture: &Fixture, offer: TestAccount) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(fixture.holder),
+        config_account(),
+        fixture.ticket_account(&fixture.ticket_state(false)),
+        fixture.event_account(&fixture.event_state()),
+        offer,
+        TestAccount::wallet(fixture.buyer),
+        TestAccount::wallet(treasury()),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.holder, 1),
+        fixture.nft_account(fixture.buyer, 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+    ]
+}
+
+/// Runs `make_offer`, returning the recorded offer.
+fn try_make(
+    accounts: &mut [TestAccount],
+    ticket: Option<Pubkey>,
+    expires_at: Option<i64>,
+) -> Result<OfferAccount> {
+    let ix_data = ticket.try_to_vec().unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = MakeOfferCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    make_offer_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        ticket,
+        PRICE,
+        expires_at,
+    )?;
+    Ok((*ctx_accounts.offer_account).clone())
+}
+
+/// Runs `accept_offer`, returning the ticket's owner afterwards.
+fn try_accept(accounts: &mut [TestAccount]) -> Result<Pubkey> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = AcceptOfferCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    accept_offer_handler(Context::new(
+        &nft_evo_tickets::ID,
+        &mut ctx_accounts,
+        remaining,
+        bumps,
+    ))?;
+    Ok(ctx_accounts.ticket_account.owner)
+}
+
+#[test]
+fn offer_escrows_price() {
+    let fixture = Fixture::new();
+    let event = fixture.event_state();
+    for ticket in [Some(fixture.ticket), None] {
+        let mut accounts = make_accounts(&fixture, &event, ticket);
+        let offer = try_make(&mut accounts, ticket, Some(NOW + 60)).unwrap();
+        assert_eq!(offer.ticket, ticket);
+        assert_eq!(accounts[MAKE_OFFER].lamports, offer_account(&fixture, ticket, None).lamports);
+    }
+}
+
+#[test]
+fn offer_needs_sol_event_and_matching_ticket() {
+    let fixture = Fixture::new();
+    let event = fixture.event_state();
+    let mut accounts = make_accounts(&fixture, &event, None);
+    assert_error(try_make(&mut accounts, None, Some(NOW)), ErrorCode::InvalidOffer);
+
+    let token_event = EventAccount {
+        payment_mint: Some(Pubkey::new_unique()),
+        ..event.clone()
+    };
+    let mut accounts = make_accounts(&fixture, &token_event, None);
+    assert_error(try_make(&mut accounts, None, None), ErrorCode::InvalidOffer);
+
+    // A ticket offer has to come with the ticket it names
+    let mut accounts = make_accounts(&fixture, &event, Some(fixture.ticket));
+    accounts[MAKE_TICKET] = TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_make(&mut accounts, Some(fixture.ticket), None), ErrorCode::OfferMismatch);
+}
+
+#[test]
+fn offer_respects_resale_cap() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        max_resale_markup_bps: Some(0),
+        ..fixture.event_state()
+    };
+    let mut accounts = make_accounts(&fixture, &event, Some(fixture.ticket));
+    assert_error(try_make(&mut accounts, Some(fixture.ticket), None), ErrorCode::ResalePriceTooHigh);
+}
+
+#[test]
+fn accept_pays_seller_and_fee() {
+    let fixture = Fixture::new();
+    let mut accounts = accept_accounts(&fixture, offer_account(&fixture, Some(fixture.ticket), None));
+    let (seller_before, treasury_before) = (accounts[ACCEPT_SELLER].lamports, accounts[ACCEPT_TREASURY].lamports);
+    assert_eq!(try_accept(&mut accounts), Ok(fixture.buyer));
+    // 5% marketplace fee to the platform treasury
+    assert_eq!(accounts[ACCEPT_TREASURY].lamports - treasury_before, PRICE / 20);
+    assert_eq!(accounts[ACCEPT_SELLER].lamports - seller_before, PRICE - PRICE / 20);
+    assert_eq!(token_balance(&accounts[ACCEPT_SELLER_NFT_ACCOUNT]), 0);
+    assert_eq!(token_balance(&accounts[ACCEPT_BUYER_NFT_ACCOUNT]), 1);
+}
+
+#[test]
+fn event_offer_accepts_any_ticket_of_event() {
+    let fixture = Fixture::new();
+    let mut accounts = accept_accounts(&fixture, offer_account(&fixture, None, None));
+    assert_eq!(try_accept(&mut accounts), Ok(fixture.buyer));
+}
+
+#[test]
+fn accept_rejects_other_ticket_and_expired_offer() {
+    let fixture = Fixture::new();
+    let other_ticket = Some(Pubkey::new_unique());
+    let mut accounts = accept_accounts(&fixture, offer_account(&fixture, other_ticket, None));
+    assert_error(try_accept(&mut accounts), ErrorCode::OfferMismatch);
+
+    let mut accounts = accept_accounts(&fixture, offer_account(&fixture, None, Some(NOW - 1)));
+    assert_error(try_accept(&mut accounts), ErrorCode::OfferExpired);
+}
+
+#[test]
+fn only_buyer_cancels_offer() {
+    let fixture = Fixture::new();
+    let try_cancel = |signer: Pubkey| -> Result<()> {
+        let mut accounts = vec![TestAccount::signer(signer), offer_account(&fixture, None, None)];
+        let infos = infos(&mut accounts);
+        let mut remaining = &infos[..];
+        let mut bumps = Default::default();
+        let mut ctx_accounts = CancelOfferCtx::try_accounts(
+            &nft_evo_tickets::ID,
+            &mut remaining,
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        cancel_offer_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps))
+    };
+    assert_error(try_cancel(Pubkey::new_unique()), ErrorCode::Unauthorized);
+    assert_eq!(try_cancel(fixture.buyer), Ok(()));
+}
@@ -0,0 +1,69 @@
+//! Per-wallet purchase limits on `buy_event_ticket`.
+mod common;
+
+use anchor_lang::prelude::*;
+
+use common::{
+    assert_error, bump, buy_accounts, pda, try_buy, Fixture, TestAccount, BUY_PURCHASES,
+};
+use nft_evo_tickets::constants::{PROGRAM_SEED, PURCHASES_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{BuyerPurchases, EventAccount};
+
+/// Accounts for `buy_event_ticket` by `buyer` in a SOL-priced legacy event.
+/// `tickets_bought` of None means the buyer has never bought a ticket for this event.
+fn purchase_accounts(
+    fixture: &Fixture,
+    max_tickets_per_wallet: Option<u16>,
+    tickets_bought: Option<u16>,
+) -> Vec<TestAccount> {
+    let event = EventAccount {
+        max_tickets_per_wallet,
+        ..fixture.event_state()
+    };
+    let mut accounts = buy_accounts(fixture.buyer, &event);
+    if let Some(tickets_bought) = tickets_bought {
+        let seeds: &[&[u8]] = &[
+            PROGRAM_SEED.as_bytes(),
+            PURCHASES_SEED.as_bytes(),
+            fixture.event.as_ref(),
+            fixture.buyer.as_ref(),
+        ];
+        let purchases = BuyerPurchases {
+            event: fixture.event,
+            buyer: fixture.buyer,
+            tickets_bought,
+            bump: bump(seeds),
+        };
+        accounts[BUY_PURCHASES] =
+            TestAccount::anchor(pda(seeds), &purchases, 8 + BuyerPurchases::INIT_SPACE);
+    }
+    accounts
+}
+
+/// Runs account validation and the handler, returning the buyer's purchase count.
+fn try_buy_counting(accounts: &mut [TestAccount]) -> Result<u16> {
+    try_buy(accounts, None).map(|bought| bought.tickets_bought)
+}
+
+#[test]
+fn buy_counts_purchases_without_a_limit() {
+    let fixture = Fixture::new();
+    assert_eq!(try_buy_counting(&mut purchase_accounts(&fixture, None, None)), Ok(1));
+    assert_eq!(try_buy_counting(&mut purchase_accounts(&fixture, None, Some(9))), Ok(10));
+}
+
+#[test]
+fn buy_allows_wallet_under_limit() {
+    let fixture = Fixture::new();
+    assert_eq!(try_buy_counting(&mut purchase_accounts(&fixture, Some(2), Some(1))), Ok(2));
+}
+
+#[test]
+fn buy_rejects_wallet_at_limit() {
+    let fixture = Fixture::new();
+    assert_error(
+        try_buy(&mut purchase_accounts(&fixture, Some(2), Some(2)), None),
+        ErrorCode::PurchaseLimitExceeded,
+    );
+}
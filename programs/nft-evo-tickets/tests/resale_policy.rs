@@ -0,0 +1,166 @@
+//! Anti-scalping rules enforced by `list_ticket`.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, config_account, infos, ticket_authority, token_balance, Fixture, TestAccount, NOW,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{list_ticket_handler, EventAccount, ListTicketCtx, ListingAccount, TicketAccount};
+
+/// Price paid recorded by `ticket_state`.
+const PRICE_PAID: u64 = 1_000_000_000;
+
+/// Positions in `list_accounts` that tests check.
+const LIST_SELLER_NFT_ACCOUNT: usize = 7;
+const LIST_ESCROW_NFT_ACCOUNT: usize = 8;
+
+fn policy_event(
+    fixture: &Fixture,
+    max_resale_markup_bps: Option<u16>,
+    resale_cooldown_secs: Option<i64>,
+    max_listings_per_wallet: Option<u16>,
+) -> EventAccount {
+    EventAccount {
+        max_resale_markup_bps,
+        resale_cooldown_secs,
+        max_listings_per_wallet,
+        ..fixture.event_state()
+    }
+}
+
+/// Accounts for `list_ticket` by `holder`, in context order. `active_listings` of
+/// None means the seller has never listed for this event.
+fn list_accounts(
+    fixture: &Fixture,
+    event: EventAccount,
+    ticket: TicketAccount,
+    active_listings: Option<u16>,
+) -> Vec<TestAccount> {
+    let listing = fixture.listing();
+    vec![
+        TestAccount::signer(fixture.holder),
+        config_account(),
+        fixture.ticket_account(&ticket),
+        fixture.event_account(&event),
+        TestAccount::uninitialized(listing, 8 + ListingAccount::INIT_SPACE),
+        fixture.seller_listings_account(active_listings),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.holder, 1),
+        fixture.nft_account(listing, 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+        TestAccount::rent_sysvar(),
+        TestAccount::program(nft_evo_tickets::ID), // bid escrow
+    ]
+}
+
+/// Runs account validation and the handler, returning the seller's open listing count.
+fn try_list(accounts: &mut [TestAccount], price: u64) -> Result<u16> {
+    let mut ix_data = price.to_le_bytes().to_vec();
+    ix_data.push(0); // expires_at: None
+    ix_data.push(0); // auction: None
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = ListTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    list_ticket_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        price,
+        None,
+        None,
+    )?;
+    Ok(ctx_accounts.seller_listings.active_listings)
+}
+
+#[test]
+fn list_counts_open_listings_per_seller() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, None, None, Some(2));
+    let mut accounts = list_accounts(&fixture, event.clone(), fixture.ticket_state(false), None);
+    assert_eq!(try_list(&mut accounts, PRICE_PAID * 5), Ok(1));
+    // The ticket is escrowed with the listing
+    assert_eq!(token_balance(&accounts[LIST_SELLER_NFT_ACCOUNT]), 0);
+    assert_eq!(token_balance(&accounts[LIST_ESCROW_NFT_ACCOUNT]), 1);
+
+    assert_eq!(
+        try_list(
+            &mut list_accounts(&fixture, event, fixture.ticket_state(false), Some(1)),
+            PRICE_PAID * 5
+        ),
+        Ok(2)
+    );
+}
+
+#[test]
+fn list_caps_price_at_markup_on_price_paid() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, Some(1_000), None, None);
+    try_list(
+        &mut list_accounts(&fixture, event.clone(), fixture.ticket_state(false), None),
+        PRICE_PAID * 11 / 10,
+    )
+    .unwrap();
+    assert_error(
+        try_list(
+            &mut list_accounts(&fixture, event.clone(), fixture.ticket_state(false), None),
+            PRICE_PAID * 11 / 10 + 1,
+        ),
+        ErrorCode::ResalePriceTooHigh,
+    );
+
+    // Organizer-minted tickets are capped on the price they were minted at
+    let minted = TicketAccount {
+        tier_index: None,
+        price_paid_lamports: 2 * PRICE_PAID,
+        ..fixture.ticket_state(false)
+    };
+    try_list(
+        &mut list_accounts(&fixture, event, minted, None),
+        2 * PRICE_PAID * 11 / 10,
+    )
+    .unwrap();
+}
+
+#[test]
+fn list_waits_out_cooldown_after_purchase() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, None, Some(3_600), None);
+    let mut ticket = fixture.ticket_state(false);
+    ticket.acquired_at = NOW - 3_599;
+    assert_error(
+        try_list(
+            &mut list_accounts(&fixture, event.clone(), ticket.clone(), None),
+            PRICE_PAID,
+        ),
+        ErrorCode::ResaleCooldownActive,
+    );
+
+    ticket.acquired_at = NOW - 3_600;
+    try_list(&mut list_accounts(&fixture, event, ticket, None), PRICE_PAID).unwrap();
+}
+
+#[test]
+fn list_rejects_seller_at_listing_limit() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, None, None, Some(2));
+    assert_error(
+        try_list(
+            &mut list_accounts(&fixture, event, fixture.ticket_state(false), Some(2)),
+            PRICE_PAID,
+        ),
+        ErrorCode::ListingLimitReached,
+    );
+}
@@ -0,0 +1,123 @@
+//! Organizer royalty schedules set through `set_royalty_split`.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use common::{
+    assert_error, config_account, event_pda, event_state, infos, install_stubs, TestAccount,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    set_royalty_split_handler, EventAccount, RoyaltyRecipient, SetRoyaltySplitCtx,
+};
+
+/// `max_royalty_bps` of `config_account`.
+const MAX_ROYALTY_BPS: u16 = 1_000;
+
+fn accounts(organizer: Pubkey, tickets_sold: u32) -> Vec<TestAccount> {
+    install_stubs();
+    let event = EventAccount {
+        tickets_sold,
+        ..event_state(1, organizer)
+    };
+    vec![
+        TestAccount::signer(organizer),
+        config_account(),
+        TestAccount::anchor(event_pda(1), &event, 8 + EventAccount::INIT_SPACE),
+    ]
+}
+
+fn recipients(shares: &[u16]) -> Vec<RoyaltyRecipient> {
+    shares
+        .iter()
+        .map(|&share_bps| RoyaltyRecipient {
+            address: Pubkey::new_unique(),
+            share_bps,
+        })
+        .collect()
+}
+
+/// Runs account validation and the handler, returning the updated event.
+fn try_set_split(
+    accounts: &mut [TestAccount],
+    royalty_bps: u16,
+    royalty_recipients: Vec<RoyaltyRecipient>,
+) -> Result<EventAccount> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SetRoyaltySplitCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    set_royalty_split_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        royalty_bps,
+        royalty_recipients,
+    )?;
+    Ok(ctx_accounts.event_account.clone().into_inner())
+}
+
+#[test]
+fn split_maps_to_creator_percentages() {
+    let organizer = Pubkey::new_unique();
+    let event = try_set_split(
+        &mut accounts(organizer, 0),
+        900,
+        recipients(&[300, 300, 300]),
+    )
+    .unwrap();
+    let shares: Vec<u8> = event
+        .creator_shares()
+        .into_iter()
+        .map(|(_, share)| share)
+        .collect();
+    assert_eq!(shares, [34, 33, 33]);
+}
+
+#[test]
+fn split_rejects_shares_not_summing_to_total() {
+    let organizer = Pubkey::new_unique();
+    assert_error(
+        try_set_split(&mut accounts(organizer, 0), 1_000, recipients(&[600, 300])),
+        ErrorCode::InvalidRoyaltySplit,
+    );
+}
+
+#[test]
+fn split_rejects_duplicate_recipient() {
+    let organizer = Pubkey::new_unique();
+    let mut royalty_recipients = recipients(&[500, 500]);
+    royalty_recipients[1].address = royalty_recipients[0].address;
+    assert_error(
+        try_set_split(&mut accounts(organizer, 0), 1_000, royalty_recipients),
+        ErrorCode::InvalidRoyaltySplit,
+    );
+}
+
+#[test]
+fn split_rejects_royalty_above_platform_max() {
+    let organizer = Pubkey::new_unique();
+    assert_error(
+        try_set_split(
+            &mut accounts(organizer, 0),
+            MAX_ROYALTY_BPS + 1,
+            recipients(&[MAX_ROYALTY_BPS + 1]),
+        ),
+        ErrorCode::InvalidBasisPoints,
+    );
+}
+
+#[test]
+fn split_is_fixed_once_tickets_sell() {
+    let organizer = Pubkey::new_unique();
+    assert_error(
+        try_set_split(&mut accounts(organizer, 1), 500, recipients(&[500])),
+        ErrorCode::TicketsAlreadySold,
+    );
+}
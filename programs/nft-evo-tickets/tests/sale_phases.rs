@@ -0,0 +1,131 @@
+//! Sale windows enforced by `buy_event_ticket`.
+mod common;
+
+use anchor_lang::prelude::*;
+
+use common::{
+    assert_error, bump, buy_accounts, event_pda, event_state, install_stubs, pda, try_buy,
+    TestAccount, BUY_ALLOWLIST_ENTRY, NOW,
+};
+use nft_evo_tickets::constants::{ALLOWLIST_SEED, PROGRAM_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{AllowlistEntry, EventAccount, SalePhase, SaleSchedule};
+
+const HOUR: i64 = 3600;
+const PRESALE_PRICE: u64 = 500_000_000;
+
+/// Presale, allowlist and public windows an hour apart, the first opening at `presale_start_ts`.
+fn schedule(presale_start_ts: i64) -> SaleSchedule {
+    SaleSchedule {
+        presale_start_ts: Some(presale_start_ts),
+        presale_price_lamports: PRESALE_PRICE,
+        allowlist_start_ts: Some(presale_start_ts + HOUR),
+        public_start_ts: presale_start_ts + 2 * HOUR,
+        sales_close_ts: presale_start_ts + 3 * HOUR,
+    }
+}
+
+fn event(sale_schedule: SaleSchedule) -> EventAccount {
+    EventAccount {
+        sale_schedule: Some(sale_schedule),
+        ..event_state(1, Pubkey::new_unique())
+    }
+}
+
+/// Accounts for `buy_event_ticket`, with an allowlist entry when `allowlisted`.
+fn accounts(buyer: Pubkey, event: &EventAccount, allowlisted: bool) -> Vec<TestAccount> {
+    let mut accounts = buy_accounts(buyer, event);
+    if allowlisted {
+        let event_key = event_pda(1);
+        let seeds: &[&[u8]] = &[
+            PROGRAM_SEED.as_bytes(),
+            ALLOWLIST_SEED.as_bytes(),
+            event_key.as_ref(),
+            buyer.as_ref(),
+        ];
+        let entry = AllowlistEntry {
+            event: event_key,
+            wallet: buyer,
+            bump: bump(seeds),
+        };
+        accounts[BUY_ALLOWLIST_ENTRY] =
+            TestAccount::anchor(pda(seeds), &entry, 8 + AllowlistEntry::INIT_SPACE);
+    }
+    accounts
+}
+
+/// Runs the purchase, returning the price the ticket was sold at.
+fn try_buy_at(event: &EventAccount, allowlisted: bool) -> Result<u64> {
+    install_stubs();
+    let mut accounts = accounts(Pubkey::new_unique(), event, allowlisted);
+    try_buy(&mut accounts, None).map(|bought| bought.ticket.price_paid_lamports)
+}
+
+#[test]
+fn phases_follow_the_schedule() {
+    let event = event(schedule(NOW));
+    assert_eq!(event.sale_phase(NOW - 1), SalePhase::NotOpen);
+    assert_eq!(event.sale_phase(NOW), SalePhase::Presale);
+    assert_eq!(event.sale_phase(NOW + HOUR), SalePhase::Allowlist);
+    assert_eq!(event.sale_phase(NOW + 2 * HOUR), SalePhase::Public);
+    assert_eq!(event.sale_phase(NOW + 3 * HOUR), SalePhase::Closed);
+    assert_eq!(event_state(1, Pubkey::new_unique()).sale_phase(NOW), SalePhase::Public);
+}
+
+#[test]
+fn schedule_must_be_ordered_and_close_by_event_end() {
+    let valid = schedule(NOW);
+    assert!(valid.validate(NOW + 3 * HOUR).is_ok());
+    assert_error(valid.validate(NOW + 3 * HOUR - 1), ErrorCode::InvalidSaleSchedule);
+
+    let no_early_windows = SaleSchedule {
+        presale_start_ts: None,
+        allowlist_start_ts: None,
+        ..valid
+    };
+    assert!(no_early_windows.validate(NOW + 3 * HOUR).is_ok());
+
+    let allowlist_before_presale = SaleSchedule {
+        allowlist_start_ts: Some(NOW - 1),
+        ..valid
+    };
+    assert_error(
+        allowlist_before_presale.validate(NOW + 3 * HOUR),
+        ErrorCode::InvalidSaleSchedule,
+    );
+
+    let closes_before_public_sale = SaleSchedule {
+        sales_close_ts: valid.public_start_ts,
+        ..valid
+    };
+    assert_error(
+        closes_before_public_sale.validate(NOW + 3 * HOUR),
+        ErrorCode::InvalidSaleSchedule,
+    );
+}
+
+#[test]
+fn buy_rejects_outside_sales_window() {
+    assert_error(try_buy_at(&event(schedule(NOW + 1)), true), ErrorCode::SalesNotOpen);
+    assert_error(try_buy_at(&event(schedule(NOW - 3 * HOUR)), true), ErrorCode::SalesClosed);
+}
+
+#[test]
+fn presale_sells_to_allowlist_at_presale_price() {
+    let event = event(schedule(NOW));
+    assert_error(try_buy_at(&event, false), ErrorCode::NotOnAllowlist);
+    assert_eq!(try_buy_at(&event, true), Ok(PRESALE_PRICE));
+}
+
+#[test]
+fn allowlist_window_sells_to_allowlist_at_tier_price() {
+    let event = event(schedule(NOW - HOUR));
+    assert_error(try_buy_at(&event, false), ErrorCode::NotOnAllowlist);
+    assert_eq!(try_buy_at(&event, true), Ok(1_000_000_000));
+}
+
+#[test]
+fn public_window_sells_to_anyone() {
+    let event = event(schedule(NOW - 2 * HOUR));
+    assert_eq!(try_buy_at(&event, false), Ok(1_000_000_000));
+}
@@ -0,0 +1,243 @@
+//! Seat reservations on primary sales, seat sections and seat swaps.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, bump, buy_accounts, config_account, free_seat_account, infos,
+    paused_config_account, pda, seat_reservation_account, try_buy, Fixture, TestAccount,
+    BUY_SEAT_RESERVATION, BUY_SEAT_SECTION,
+};
+use nft_evo_tickets::constants::{PROGRAM_SEED, SECTION_SEED};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{swap_seat_handler, EventAccount, SeatSection, SwapSeatCtx, TicketAccount};
+
+const SECTION_PRICE: u64 = 2_000_000_000;
+
+/// Positions in `swap_accounts` that tests swap out.
+const SWAP_CONFIG: usize = 1;
+const SWAP_NEW_SEAT: usize = 6;
+
+fn event(fixture: &Fixture, section_count: u8) -> EventAccount {
+    EventAccount {
+        section_count,
+        ..fixture.event_state()
+    }
+}
+
+/// "ORCH": 10 rows of 20 seats.
+fn section(fixture: &Fixture) -> TestAccount {
+    let seeds: &[&[u8]] = &[
+        PROGRAM_SEED.as_bytes(),
+        SECTION_SEED.as_bytes(),
+        fixture.event.as_ref(),
+        &[0],
+    ];
+    let section = SeatSection {
+        event: fixture.event,
+        section_index: 0,
+        name: "ORCH".to_string(),
+        rows: 10,
+        seats_per_row: 20,
+        price_lamports: SECTION_PRICE,
+        bump: bump(seeds),
+    };
+    TestAccount::anchor(pda(seeds), &section, 8 + SeatSection::INIT_SPACE)
+}
+
+/// Accounts for `buy_event_ticket` by the fixture's buyer with the given seat accounts.
+fn seated_buy_accounts(
+    fixture: &Fixture,
+    event: EventAccount,
+    seat_section: Option<TestAccount>,
+    seat_reservation: Option<TestAccount>,
+) -> Vec<TestAccount> {
+    let mut accounts = buy_accounts(fixture.buyer, &event);
+    if let Some(seat_section) = seat_section {
+        accounts[BUY_SEAT_SECTION] = seat_section;
+    }
+    if let Some(seat_reservation) = seat_reservation {
+        accounts[BUY_SEAT_RESERVATION] = seat_reservation;
+    }
+    accounts
+}
+
+/// Accounts for `swap_seat` by the holder of the ticket reserved at `current_seat`.
+fn swap_accounts(
+    fixture: &Fixture,
+    event: EventAccount,
+    current_seat: &str,
+    new_seat: &str,
+    seat_section: Option<TestAccount>,
+) -> Vec<TestAccount> {
+    let ticket = TicketAccount {
+        seat: Some(current_seat.to_string()),
+        ..fixture.ticket_state(false)
+    };
+    vec![
+        TestAccount::signer(fixture.holder),
+        config_account(),
+        fixture.event_account(&event),
+        fixture.ticket_account(&ticket),
+        fixture.nft_account(fixture.holder, 1),
+        seat_reservation_account(fixture.event, current_seat, fixture.ticket),
+        free_seat_account(fixture.event, new_seat),
+        seat_section.unwrap_or_else(|| TestAccount::program(nft_evo_tickets::ID)),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(System::id()),
+    ]
+}
+
+/// Runs account validation and the handler, returning the ticket's seat.
+fn try_swap(accounts: &mut [TestAccount], new_seat: &str) -> Result<Option<String>> {
+    let new_seat = new_seat.to_string();
+    let ix_data = new_seat.try_to_vec().unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SwapSeatCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    swap_seat_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        new_seat,
+    )?;
+    Ok(ctx_accounts.ticket_account.seat.clone())
+}
+
+#[test]
+fn buy_reserves_seat_for_ticket() {
+    let fixture = Fixture::new();
+    let mut accounts = seated_buy_accounts(
+        &fixture,
+        event(&fixture, 0),
+        None,
+        Some(free_seat_account(fixture.event, "A1")),
+    );
+    let reservation = try_buy(&mut accounts, Some("A1")).unwrap().seat_reservation.unwrap();
+    assert_eq!(reservation.ticket, TicketAccount::pda(&fixture.event, 2).0);
+}
+
+#[test]
+fn buy_rejects_taken_seat() {
+    let fixture = Fixture::new();
+    let taken = seat_reservation_account(fixture.event, "A1", Pubkey::new_unique());
+    let mut accounts = seated_buy_accounts(&fixture, event(&fixture, 0), None, Some(taken));
+    match try_buy(&mut accounts, Some("A1")) {
+        Err(Error::ProgramError(e)) => assert_eq!(e.program_error, ProgramError::Custom(0)),
+        Err(e) => panic!("expected the seat to be in use, got {e:?}"),
+        Ok(_) => panic!("expected the seat to be in use, but the purchase went through"),
+    }
+}
+
+#[test]
+fn buy_rejects_seat_without_reservation() {
+    let fixture = Fixture::new();
+    let mut accounts = seated_buy_accounts(&fixture, event(&fixture, 0), None, None);
+    assert_error(try_buy(&mut accounts, Some("A1")), ErrorCode::SeatReservationMismatch);
+}
+
+#[test]
+fn buy_charges_section_price() {
+    let fixture = Fixture::new();
+    let mut accounts = seated_buy_accounts(
+        &fixture,
+        event(&fixture, 1),
+        Some(section(&fixture)),
+        Some(free_seat_account(fixture.event, "ORCH-3-12")),
+    );
+    let bought = try_buy(&mut accounts, Some("ORCH-3-12")).unwrap();
+    assert_eq!(bought.ticket.price_paid_lamports, SECTION_PRICE);
+}
+
+#[test]
+fn buy_rejects_seat_outside_section() {
+    let fixture = Fixture::new();
+    for seat in ["ORCH-11-1", "ORCH-3-21", "ORCH-03-12", "BALC-3-12", "ORCH12"] {
+        let mut accounts = seated_buy_accounts(
+            &fixture,
+            event(&fixture, 1),
+            Some(section(&fixture)),
+            Some(free_seat_account(fixture.event, seat)),
+        );
+        assert_error(try_buy(&mut accounts, Some(seat)), ErrorCode::InvalidSeat);
+    }
+}
+
+#[test]
+fn buy_requires_section_in_seated_event() {
+    let fixture = Fixture::new();
+    let mut accounts = seated_buy_accounts(
+        &fixture,
+        event(&fixture, 1),
+        None,
+        Some(free_seat_account(fixture.event, "A1")),
+    );
+    assert_error(try_buy(&mut accounts, Some("A1")), ErrorCode::SeatSectionRequired);
+
+    let mut accounts =
+        seated_buy_accounts(&fixture, event(&fixture, 1), Some(section(&fixture)), None);
+    assert_error(try_buy(&mut accounts, None), ErrorCode::SeatSectionRequired);
+}
+
+#[test]
+fn swap_moves_ticket_to_free_seat() {
+    let fixture = Fixture::new();
+    let mut accounts = swap_accounts(&fixture, event(&fixture, 0), "A1", "B2", None);
+    assert_eq!(try_swap(&mut accounts, "B2"), Ok(Some("B2".to_string())));
+}
+
+#[test]
+fn swap_rejects_taken_seat() {
+    let fixture = Fixture::new();
+    let mut accounts = swap_accounts(&fixture, event(&fixture, 0), "A1", "B2", None);
+    accounts[SWAP_NEW_SEAT] = seat_reservation_account(fixture.event, "B2", Pubkey::new_unique());
+    match try_swap(&mut accounts, "B2") {
+        Err(Error::ProgramError(e)) => assert_eq!(e.program_error, ProgramError::Custom(0)),
+        other => panic!("expected the seat to be in use, got {other:?}"),
+    }
+}
+
+#[test]
+fn swap_waits_for_unpause() {
+    let fixture = Fixture::new();
+    let mut accounts = swap_accounts(&fixture, event(&fixture, 0), "A1", "B2", None);
+    accounts[SWAP_CONFIG] = paused_config_account();
+    assert_error(try_swap(&mut accounts, "B2"), ErrorCode::ProgramPaused);
+
+    let paused = EventAccount {
+        is_paused: true,
+        ..event(&fixture, 0)
+    };
+    let mut accounts = swap_accounts(&fixture, paused, "A1", "B2", None);
+    assert_error(try_swap(&mut accounts, "B2"), ErrorCode::EventPaused);
+}
+
+#[test]
+fn swap_stays_within_section() {
+    let fixture = Fixture::new();
+    let mut accounts = swap_accounts(
+        &fixture,
+        event(&fixture, 1),
+        "ORCH-1-1",
+        "ORCH-1-2",
+        Some(section(&fixture)),
+    );
+    assert_eq!(try_swap(&mut accounts, "ORCH-1-2"), Ok(Some("ORCH-1-2".to_string())));
+
+    let mut accounts = swap_accounts(
+        &fixture,
+        event(&fixture, 1),
+        "BALC-1-1",
+        "ORCH-1-2",
+        Some(section(&fixture)),
+    );
+    assert_error(try_swap(&mut accounts, "ORCH-1-2"), ErrorCode::InvalidSeat);
+}
@@ -0,0 +1,62 @@
+//! Permissionless `sync_ticket_owner` after wallet-to-wallet transfers.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use common::{assert_error, infos, Fixture, TestAccount, NOW};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{sync_ticket_owner_handler, SyncTicketOwnerCtx, TicketAccount};
+
+/// Accounts for `sync_ticket_owner` of a ticket still recorded as `holder`'s,
+/// whose NFT `buyer` now holds `amount` of.
+fn sync_accounts(fixture: &Fixture, is_listed: bool, amount: u64) -> Vec<TestAccount> {
+    vec![
+        fixture.ticket_account(&fixture.ticket_state(is_listed)),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.buyer, amount),
+    ]
+}
+
+/// Runs account validation and the handler, returning the ticket afterwards.
+fn try_sync(accounts: &mut [TestAccount]) -> Result<TicketAccount> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = SyncTicketOwnerCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    sync_ticket_owner_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps))?;
+    Ok(ctx_accounts.ticket_account.clone().into_inner())
+}
+
+#[test]
+fn sync_points_ticket_at_token_holder() {
+    let fixture = Fixture::new();
+    let ticket = try_sync(&mut sync_accounts(&fixture, false, 1)).unwrap();
+    assert_eq!(ticket.owner, fixture.buyer);
+}
+
+#[test]
+fn sync_restarts_resale_cooldown_for_new_holder() {
+    let fixture = Fixture::new();
+    let ticket = try_sync(&mut sync_accounts(&fixture, false, 1)).unwrap();
+    assert_eq!(ticket.acquired_at, NOW);
+}
+
+#[test]
+fn sync_rejects_empty_token_account() {
+    let fixture = Fixture::new();
+    assert_error(try_sync(&mut sync_accounts(&fixture, false, 0)), ErrorCode::NotTicketHolder);
+}
+
+#[test]
+fn sync_rejects_listed_ticket() {
+    let fixture = Fixture::new();
+    assert_error(try_sync(&mut sync_accounts(&fixture, true, 1)), ErrorCode::TicketAlreadyListed);
+}
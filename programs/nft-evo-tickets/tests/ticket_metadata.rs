@@ -0,0 +1,595 @@
+//! Ticket metadata evolution through the ticket authority PDA, on every stage change,
+//! with URIs rendered from the event's templates.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        metadata_pointer::MetadataPointer, BaseStateWithExtensionsMut, ExtensionType,
+        StateWithExtensionsMut,
+    },
+};
+use anchor_spl::token_interface::{
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_metadata_interface::{
+        instruction::TokenMetadataInstruction,
+        state::{Field, TokenMetadata},
+    },
+};
+use mpl_token_metadata::accounts::Metadata;
+
+use common::{
+    assert_error, buy_accounts, config_account, event_pda, free_seat_account, infos,
+    metadata_config_account, take_invoked, ticket_authority, try_buy, Fixture, TestAccount,
+    BUY_EVENT, BUY_METADATA_CONFIG, BUY_SEAT_RESERVATION,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    set_metadata_config_handler, update_ticket_handler, update_ticket_metadata_handler,
+    upgrade_to_collectible_handler, EventAccount, EventMetadataConfig, SetMetadataConfigCtx,
+    StageUriTemplates, TicketAccount, TicketStage, UpdateTicket, UpdateTicketMetadata,
+    UpgradeToCollectible,
+};
+
+const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+const NAME: &str = "TIX • Test Event • A1";
+const MINTED_URI: &str = "https://cdn.test/minted.json";
+
+fn templates() -> StageUriTemplates {
+    let template = "https://cdn.test/{event_id}/{serial}/{stage}.json".to_string();
+    StageUriTemplates {
+        prestige: template.clone(),
+        qr: "https://cdn.test/{event_id}/{serial}/{stage}.json?seat={seat}".to_string(),
+        scanned: template.clone(),
+        collectible: template,
+    }
+}
+
+/// Metadata URI of the test ticket under `templates()`.
+fn stage_uri(stage: &str) -> String {
+    match stage {
+        "qr" => "https://cdn.test/1/1/qr.json?seat=A1".to_string(),
+        _ => format!("https://cdn.test/1/1/{stage}.json"),
+    }
+}
+
+/// Metaplex's on-chain `Metadata` layout up to the fields this program reads.
+#[derive(AnchorSerialize)]
+struct MetaplexMetadata {
+    key: u8,
+    update_authority: Pubkey,
+    mint: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    creators: Option<Vec<(Pubkey, bool, u8)>>,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+}
+
+/// Positions in `update_accounts` that tests swap out.
+const UPDATE_SIGNER: usize = 0;
+const UPDATE_EVENT: usize = 2;
+const UPDATE_TICKET: usize = 3;
+const UPDATE_MINT: usize = 4;
+const UPDATE_METADATA: usize = 5;
+const UPDATE_EVENT_AUTHORITY: usize = 6;
+const UPDATE_SCANNER: usize = 7;
+const UPDATE_TOKEN_PROGRAM: usize = 9;
+const UPDATE_METADATA_CONFIG: usize = 12;
+
+/// The fixture's event, with its stage URI templates set.
+fn templated_event(fixture: &Fixture) -> EventAccount {
+    EventAccount {
+        has_metadata_config: true,
+        ..fixture.event_state()
+    }
+}
+
+/// The fixture's ticket at `stage`.
+fn staged_ticket(fixture: &Fixture, stage: TicketStage) -> TicketAccount {
+    TicketAccount {
+        stage,
+        was_scanned: stage == TicketStage::Scanned,
+        ..fixture.ticket_state(false)
+    }
+}
+
+/// Metaplex metadata for the legacy mint, names padded like Metaplex stores them.
+fn metaplex_metadata_account(fixture: &Fixture, update_authority: Pubkey) -> TestAccount {
+    let metadata = MetaplexMetadata {
+        key: 4, // MetadataV1
+        update_authority,
+        mint: fixture.mint,
+        name: format!("{:\0<32}", "Test Event - S A1"),
+        symbol: format!("{:\0<10}", "TIX"),
+        uri: format!("{:\0<200}", MINTED_URI),
+        seller_fee_basis_points: 500,
+        creators: Some(vec![(fixture.authority, true, 100)]),
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+    };
+    TestAccount {
+        key: Metadata::find_pda(&fixture.mint).0,
+        owner: mpl_token_metadata::ID,
+        lamports: 5_616_720,
+        data: metadata.try_to_vec().unwrap(),
+        is_signer: false,
+        is_writable: true,
+        executable: false,
+    }
+}
+
+/// A Token-2022 mint carrying its own metadata, updatable by the ticket authority.
+fn token_2022_mint(fixture: &Fixture, uri: &str) -> TestAccount {
+    let metadata = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(ticket_authority().key)).unwrap(),
+        mint: fixture.mint,
+        name: "Test Event - S A1".to_string(),
+        symbol: "TIX".to_string(),
+        uri: uri.to_string(),
+        additional_metadata: vec![],
+    };
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::MetadataPointer,
+    ])
+    .unwrap();
+    let mut data = vec![0; space + metadata.tlv_size_of().unwrap()];
+    let mut mint =
+        StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data)
+            .unwrap();
+    mint.init_extension::<MetadataPointer>(true).unwrap().metadata_address =
+        OptionalNonZeroPubkey::try_from(Some(fixture.mint)).unwrap();
+    mint.base.decimals = 0;
+    mint.base.supply = 1;
+    mint.base.is_initialized = true;
+    mint.pack_base();
+    mint.init_account_type().unwrap();
+    mint.init_variable_len_extension(&metadata, false).unwrap();
+    TestAccount {
+        key: fixture.mint,
+        owner: spl_token_2022::ID,
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        is_signer: false,
+        is_writable: true,
+        executable: false,
+    }
+}
+
+/// Accounts for `update_ticket_metadata` by `signer` of the ticket at `stage`, in
+/// the templated event.
+fn update_accounts(fixture: &Fixture, signer: Pubkey, stage: TicketStage) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(signer),
+        config_account(),
+        fixture.event_account(&templated_event(fixture)),
+        fixture.ticket_account(&staged_ticket(fixture, stage)),
+        fixture.nft_mint(),
+        metaplex_metadata_account(fixture, ticket_authority().key),
+        TestAccount::wallet(fixture.authority),
+        TestAccount::wallet(fixture.scanner),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(mpl_token_metadata::ID),
+        TestAccount::program(System::id()),
+        metadata_config_account(fixture.event, templates()),
+    ]
+}
+
+/// `update_accounts`, reordered as `update_ticket` takes them.
+fn in_update_ticket_order(mut accounts: Vec<TestAccount>) -> Vec<TestAccount> {
+    let event_keys: Vec<_> = accounts.drain(UPDATE_EVENT_AUTHORITY..=UPDATE_SCANNER).collect();
+    accounts.splice(UPDATE_MINT..UPDATE_MINT, event_keys);
+    accounts
+}
+
+/// `update_accounts` of a scanned ticket without the event's authority and scanner,
+/// as `upgrade_to_collectible` takes them.
+fn upgrade_accounts(fixture: &Fixture, user: Pubkey) -> Vec<TestAccount> {
+    let mut accounts = update_accounts(fixture, user, TicketStage::Scanned);
+    accounts.drain(UPDATE_EVENT_AUTHORITY..=UPDATE_SCANNER);
+    accounts
+}
+
+/// Runs `update_ticket_metadata`, returning the stage reached and the CPIs made.
+fn try_update(
+    accounts: &mut [TestAccount],
+    new_stage: TicketStage,
+    new_uri: Option<&str>,
+) -> Result<(TicketStage, Vec<Instruction>)> {
+    let new_uri = new_uri.map(str::to_string);
+    let mut ix_data = new_stage.try_to_vec().unwrap();
+    new_uri.serialize(&mut ix_data).unwrap();
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = UpdateTicketMetadata::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    update_ticket_metadata_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        new_stage,
+        new_uri,
+    )?;
+    Ok((ctx_accounts.ticket_account.stage, take_invoked()))
+}
+
+/// Runs `update_ticket`, returning the CPIs made.
+fn try_update_ticket(accounts: &mut [TestAccount], new_stage: TicketStage) -> Result<Vec<Instruction>> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = UpdateTicket::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &new_stage.try_to_vec().unwrap(),
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    update_ticket_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps), new_stage)?;
+    Ok(take_invoked())
+}
+
+/// Runs `upgrade_to_collectible`, returning the CPIs made.
+fn try_upgrade(accounts: &mut [TestAccount]) -> Result<Vec<Instruction>> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    take_invoked();
+    let mut ctx_accounts = UpgradeToCollectible::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    upgrade_to_collectible_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps))?;
+    Ok(take_invoked())
+}
+
+/// Name, symbol, URI and royalty the Metaplex update writes, checking it is signed by the ticket authority.
+fn metaplex_update(invoked: &[Instruction]) -> (String, String, String, u16) {
+    let ix = invoked
+        .iter()
+        .find(|ix| ix.program_id == mpl_token_metadata::ID)
+        .expect("no Metaplex CPI");
+    assert_eq!(ix.data[0], UPDATE_METADATA_ACCOUNT_V2);
+    assert_eq!(ix.accounts[1].pubkey, ticket_authority().key);
+    assert!(ix.accounts[1].is_signer);
+    assert_eq!(ix.data[1], 1, "data should be replaced");
+    AnchorDeserialize::deserialize(&mut &ix.data[2..]).unwrap()
+}
+
+#[test]
+fn uri_changes_on_each_stage_transition() {
+    let fixture = Fixture::new();
+    for (signer, from, to, dir) in [
+        (fixture.authority, TicketStage::Prestige, TicketStage::Qr, "qr"),
+        (fixture.scanner, TicketStage::Qr, TicketStage::Scanned, "scanned"),
+    ] {
+        let mut accounts = update_accounts(&fixture, signer, from);
+        let (stage, invoked) = try_update(&mut accounts, to, None).unwrap();
+        assert!(stage == to);
+        let (name, symbol, uri, royalty_bps) = metaplex_update(&invoked);
+        assert_eq!((name.as_str(), uri), (NAME, stage_uri(dir)));
+        // Everything else is carried over, without Metaplex's padding
+        assert_eq!((symbol.as_str(), royalty_bps), ("TIX", 500));
+    }
+}
+
+#[test]
+fn every_stage_change_evolves_metadata() {
+    let fixture = Fixture::new();
+    let mut accounts = in_update_ticket_order(update_accounts(
+        &fixture,
+        fixture.authority,
+        TicketStage::Prestige,
+    ));
+    let invoked = try_update_ticket(&mut accounts, TicketStage::Qr).unwrap();
+    assert_eq!(metaplex_update(&invoked).2, stage_uri("qr"));
+
+    let mut accounts =
+        in_update_ticket_order(update_accounts(&fixture, fixture.scanner, TicketStage::Qr));
+    let invoked = try_update_ticket(&mut accounts, TicketStage::Scanned).unwrap();
+    assert_eq!(metaplex_update(&invoked).2, stage_uri("scanned"));
+
+    // The holder's own upgrade once the event is over
+    let mut accounts = upgrade_accounts(&fixture, fixture.holder);
+    let (name, _, uri, _) = metaplex_update(&try_upgrade(&mut accounts).unwrap());
+    assert_eq!((name.as_str(), uri), (NAME, stage_uri("collectible")));
+}
+
+#[test]
+fn without_templates_only_the_name_evolves() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_EVENT] = fixture.event_account(&fixture.event_state());
+    accounts[UPDATE_METADATA_CONFIG] = TestAccount::program(nft_evo_tickets::ID);
+    let mut accounts = in_update_ticket_order(accounts);
+    let (name, _, uri, _) = metaplex_update(&try_update_ticket(&mut accounts, TicketStage::Qr).unwrap());
+    assert_eq!((name.as_str(), uri.as_str()), (NAME, MINTED_URI));
+}
+
+#[test]
+fn name_is_capped_at_32_bytes() {
+    // A 26 byte section seat leaves no room for the event name, the seat is cut instead
+    let seat = "ORCHESTRA-LEFT-BALCONY-9-1".to_string();
+    let name = TicketStage::Qr.get_name("Test Event", Some(&seat));
+    assert_eq!(name, "TIX •  • ORCHESTRA-LEFT-BALC");
+    assert!(name.len() <= 32);
+
+    // Multibyte event names are cut on a char boundary, not through 'ë'
+    let seat = "A-101".to_string();
+    let name = TicketStage::Prestige.get_name("Ünïcödé Fëstïväl 2026", Some(&seat));
+    assert_eq!(name, "TIX • Ünïcödé F • A-101");
+    assert!(name.len() <= 32);
+}
+
+#[test]
+fn purchased_ticket_is_minted_with_its_stage_name() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        name: "Coachella Valley Music and Arts Festival".to_string(),
+        ..fixture.event_state()
+    };
+    let mut accounts = buy_accounts(fixture.buyer, &event);
+    accounts[BUY_SEAT_RESERVATION] = free_seat_account(fixture.event, "ORCH-12-7");
+    take_invoked();
+    try_buy(&mut accounts, Some("ORCH-12-7")).unwrap();
+    let ix = take_invoked()
+        .into_iter()
+        .find(|ix| ix.program_id == mpl_token_metadata::ID)
+        .expect("no Metaplex CPI");
+    assert_eq!(ix.data[0], CREATE_METADATA_ACCOUNT_V3);
+    let (name, _, _): (String, String, String) = AnchorDeserialize::deserialize(&mut &ix.data[1..]).unwrap();
+    // The same name the ticket keeps through its stage changes
+    assert_eq!(name, TicketStage::Qr.get_name(&event.name, Some(&"ORCH-12-7".to_string())));
+    assert_eq!(name, "TIX • Coachella  • ORCH-12-7");
+}
+
+#[test]
+fn placeholder_values_are_url_encoded() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    let ticket = TicketAccount {
+        seat: Some("Row 5/#7é".to_string()),
+        ..staged_ticket(&fixture, TicketStage::Prestige)
+    };
+    accounts[UPDATE_TICKET] = fixture.ticket_account(&ticket);
+    accounts[UPDATE_METADATA_CONFIG] = metadata_config_account(fixture.event, StageUriTemplates {
+        qr: "https://cdn.test/{seat}/{mint}.json?stage={stage}".to_string(),
+        ..templates()
+    });
+    let (_, invoked) = try_update(&mut accounts, TicketStage::Qr, None).unwrap();
+    assert_eq!(
+        metaplex_update(&invoked).2,
+        format!("https://cdn.test/Row%205%2F%237%C3%A9/{}.json?stage=qr", fixture.mint)
+    );
+}
+
+#[test]
+fn events_with_templates_need_their_config() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_METADATA_CONFIG] = TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_update(&mut accounts, TicketStage::Qr, None), ErrorCode::MetadataConfigMismatch);
+
+    // Another event's templates
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_METADATA_CONFIG] = metadata_config_account(event_pda(2), templates());
+    let result = try_update(&mut accounts, TicketStage::Qr, None);
+    assert!(matches!(result, Err(Error::AnchorError(e)) if e.error_name == "ConstraintSeeds"));
+
+    // Templates left over from before are ignored without the event's flag
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_EVENT] = fixture.event_account(&fixture.event_state());
+    assert_error(try_update(&mut accounts, TicketStage::Qr, None), ErrorCode::MetadataConfigMismatch);
+}
+
+#[test]
+fn organizer_sets_valid_templates() {
+    let fixture = Fixture::new();
+    let try_set = |signer: Pubkey, templates: StageUriTemplates| -> Result<(EventAccount, EventMetadataConfig)> {
+        let event = fixture.event_state();
+        let mut accounts = vec![
+            TestAccount::signer(signer),
+            fixture.event_account(&event),
+            TestAccount::uninitialized(metadata_config_account(fixture.event, templates.clone()).key, 8 + EventMetadataConfig::INIT_SPACE),
+            TestAccount::program(System::id()),
+        ];
+        let infos = infos(&mut accounts);
+        let mut remaining = &infos[..];
+        let mut bumps = Default::default();
+        let mut ctx_accounts = SetMetadataConfigCtx::try_accounts(
+            &nft_evo_tickets::ID,
+            &mut remaining,
+            &templates.try_to_vec().unwrap(),
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )?;
+        set_metadata_config_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps), templates)?;
+        Ok(((*ctx_accounts.event_account).clone(), (*ctx_accounts.metadata_config).clone()))
+    };
+    assert_error(try_set(fixture.scanner, templates()), ErrorCode::Unauthorized);
+    let (event, config) = try_set(fixture.authority, templates()).unwrap();
+    assert!(event.has_metadata_config);
+    assert_eq!((config.event, config.templates), (fixture.event, templates()));
+
+    for bad_qr in [
+        "",
+        "https://cdn.test/{name}.json",
+        "https://cdn.test/{seat.json",
+        "https://cdn.test/seat}.json",
+        "https://cdn.test/{{seat}}.json",
+        "https://cdn.test/a b.json",
+    ] {
+        let bad = StageUriTemplates {
+            qr: bad_qr.to_string(),
+            ..templates()
+        };
+        assert_error(try_set(fixture.authority, bad), ErrorCode::InvalidMetadataTemplate);
+    }
+
+    // Room is kept for a 32 byte seat with every byte percent-encoded
+    let one_seat = StageUriTemplates {
+        scanned: format!("https://cdn.test/{{seat}}/{}", "s".repeat(200 - 17 - 1 - 96)),
+        ..templates()
+    };
+    assert!(try_set(fixture.authority, one_seat.clone()).is_ok());
+    let too_long = StageUriTemplates {
+        scanned: format!("{}s", one_seat.scanned),
+        ..templates()
+    };
+    assert_error(try_set(fixture.authority, too_long), ErrorCode::MetadataUriTooLong);
+}
+
+#[test]
+fn explicit_uri_is_written_and_capped() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    let (_, invoked) = try_update(&mut accounts, TicketStage::Qr, Some("https://cdn.test/qr.json")).unwrap();
+    assert_eq!(metaplex_update(&invoked).2, "https://cdn.test/qr.json");
+
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    let too_long = format!("https://cdn.test/{}", "a".repeat(200));
+    assert_error(try_update(&mut accounts, TicketStage::Qr, Some(&too_long)), ErrorCode::MetadataUriTooLong);
+
+    // Only the organizer picks the artwork, the scanner just moves the ticket along
+    let mut accounts = update_accounts(&fixture, fixture.scanner, TicketStage::Qr);
+    assert_error(
+        try_update(&mut accounts, TicketStage::Scanned, Some("https://cdn.test/qr.json")),
+        ErrorCode::Unauthorized,
+    );
+}
+
+#[test]
+fn stage_rules_still_apply() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.scanner, TicketStage::Prestige);
+    assert_error(try_update(&mut accounts, TicketStage::Qr, None), ErrorCode::Unauthorized);
+
+    let mut accounts = update_accounts(&fixture, fixture.scanner, TicketStage::Prestige);
+    assert_error(try_update(&mut accounts, TicketStage::Scanned, None), ErrorCode::InvalidTicketStage);
+}
+
+#[test]
+fn event_must_be_the_program_event_pda() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    // The seeds this instruction used to expect, which no event is created at
+    accounts[UPDATE_EVENT].key = Pubkey::find_program_address(
+        &[b"event", fixture.authority.as_ref(), &1u64.to_le_bytes()],
+        &nft_evo_tickets::ID,
+    )
+    .0;
+    let result = try_update(&mut accounts, TicketStage::Qr, None);
+    assert!(matches!(result, Err(Error::AnchorError(e)) if e.error_name == "ConstraintSeeds"));
+}
+
+#[test]
+fn metadata_outside_ticket_authority_is_rejected() {
+    let fixture = Fixture::new();
+    // Tickets minted with the organizer as update authority
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_METADATA] = metaplex_metadata_account(&fixture, fixture.authority);
+    assert_error(try_update(&mut accounts, TicketStage::Qr, None), ErrorCode::MetadataAuthorityMismatch);
+
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_METADATA].key = Pubkey::new_unique();
+    assert_error(try_update(&mut accounts, TicketStage::Qr, None), ErrorCode::InvalidInput);
+}
+
+#[test]
+fn token_2022_metadata_is_updated_on_the_mint() {
+    let fixture = Fixture::new();
+    let mut accounts = update_accounts(&fixture, fixture.authority, TicketStage::Prestige);
+    accounts[UPDATE_MINT] = token_2022_mint(&fixture, MINTED_URI);
+    accounts[UPDATE_TOKEN_PROGRAM] = TestAccount::program(spl_token_2022::ID);
+    let (signer_before, mint_before) = (accounts[UPDATE_SIGNER].lamports, accounts[UPDATE_MINT].lamports);
+
+    let (_, invoked) = try_update(&mut accounts, TicketStage::Qr, None).unwrap();
+    let updates: Vec<_> = invoked
+        .iter()
+        .filter(|ix| ix.program_id == spl_token_2022::ID)
+        .map(|ix| {
+            assert_eq!(ix.accounts[1].pubkey, ticket_authority().key);
+            match TokenMetadataInstruction::unpack(&ix.data).unwrap() {
+                TokenMetadataInstruction::UpdateField(update) => (update.field, update.value),
+                _ => panic!("expected a field update"),
+            }
+        })
+        .collect();
+    assert_eq!(updates, vec![(Field::Name, NAME.to_string()), (Field::Uri, stage_uri("qr"))]);
+
+    // The signer covers the rent of the longer metadata
+    let grown = NAME.len() + stage_uri("qr").len() - "Test Event - S A1".len() - MINTED_URI.len();
+    let topped_up = Rent::default().minimum_balance(accounts[UPDATE_MINT].data.len() + grown) - mint_before;
+    assert_eq!(accounts[UPDATE_MINT].lamports - mint_before, topped_up);
+    assert_eq!(signer_before - accounts[UPDATE_SIGNER].lamports, topped_up);
+}
+
+#[test]
+fn purchased_ticket_metadata_belongs_to_ticket_authority() {
+    let fixture = Fixture::new();
+    let mut accounts = buy_accounts(fixture.buyer, &fixture.event_state());
+    take_invoked();
+    try_buy(&mut accounts, None).unwrap();
+    let metaplex: Vec<_> = take_invoked()
+        .into_iter()
+        .filter(|ix| ix.program_id == mpl_token_metadata::ID)
+        .collect();
+    // CreateMetadataAccountV3 and CreateMasterEditionV3 name it update authority
+    assert_eq!(metaplex.len(), 2);
+    assert_eq!(metaplex[0].accounts[4].pubkey, ticket_authority().key);
+    assert_eq!(metaplex[1].accounts[2].pubkey, ticket_authority().key);
+    assert!(metaplex.iter().all(|ix| ix.accounts.iter().any(|meta| meta.pubkey == ticket_authority().key && meta.is_signer)));
+}
+
+#[test]
+fn purchased_ticket_uri_is_rendered_from_its_stage_template() {
+    let fixture = Fixture::new();
+    let create_metadata_uri = |accounts: &mut [TestAccount]| -> String {
+        take_invoked();
+        try_buy(accounts, None).unwrap();
+        let ix = take_invoked()
+            .into_iter()
+            .find(|ix| ix.program_id == mpl_token_metadata::ID)
+            .expect("no Metaplex CPI");
+        assert_eq!(ix.data[0], CREATE_METADATA_ACCOUNT_V3);
+        let (_, _, uri): (String, String, String) = AnchorDeserialize::deserialize(&mut &ix.data[1..]).unwrap();
+        uri
+    };
+
+    let event = templated_event(&fixture);
+    let mut accounts = buy_accounts(fixture.buyer, &event);
+    // The event is under way, so tickets start at the QR stage
+    accounts[BUY_METADATA_CONFIG] = metadata_config_account(fixture.event, StageUriTemplates {
+        qr: "https://cdn.test/{event_id}/{serial}/{mint}/{stage}/{seat}".to_string(),
+        ..templates()
+    });
+    let serial = event.next_ticket_serial();
+    let (mint, _) = TicketAccount::nft_mint_pda(&fixture.event, serial);
+    assert_eq!(create_metadata_uri(&mut accounts), format!("https://cdn.test/1/{serial}/{mint}/qr/"));
+
+    // Without templates there is no URI to mint the ticket with, so sales wait for them
+    let mut accounts = buy_accounts(fixture.buyer, &fixture.event_state());
+    accounts[BUY_EVENT] = fixture.event_account(&fixture.event_state());
+    accounts[BUY_METADATA_CONFIG] = TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_buy(&mut accounts, None), ErrorCode::MetadataConfigRequired);
+}
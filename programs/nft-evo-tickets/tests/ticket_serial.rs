@@ -0,0 +1,126 @@
+//! Ticket serials, shared by `buy_event_ticket` and `mint_ticket`.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+
+use common::{
+    buy_accounts, config_account, infos, metadata_config_account, stage_templates,
+    ticket_authority, try_buy, Fixture, TestAccount, BUY_TICKET,
+};
+use nft_evo_tickets::{mint_ticket_handler, EventAccount, MintTicketCtx, TicketAccount};
+
+/// Accounts for `mint_ticket` of an unseated ticket for `holder`, in context order.
+fn mint_accounts(fixture: &Fixture, event: &EventAccount) -> Vec<TestAccount> {
+    let event = &EventAccount {
+        has_metadata_config: true,
+        ..event.clone()
+    };
+    let (ticket, _) = TicketAccount::pda(&fixture.event, event.next_ticket_serial());
+    let (mint, _) = TicketAccount::nft_mint_pda(&fixture.event, event.next_ticket_serial());
+    let none = || TestAccount::program(nft_evo_tickets::ID);
+    vec![
+        TestAccount::signer(fixture.authority),
+        config_account(),
+        fixture.event_account(event),
+        fixture.vault_account(),
+        TestAccount::uninitialized(ticket, 8 + TicketAccount::INIT_SPACE),
+        TestAccount::wallet(fixture.holder),
+        TestAccount::uninitialized(mint, spl_token::state::Mint::LEN),
+        ticket_authority(),
+        TestAccount::wallet(Pubkey::new_unique()), // metadata
+        TestAccount::wallet(Pubkey::new_unique()), // master edition
+        TestAccount::uninitialized(
+            get_associated_token_address(&fixture.holder, &mint),
+            spl_token::state::Account::LEN,
+        ),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+        TestAccount::program(mpl_token_metadata::ID),
+        TestAccount::rent_sysvar(),
+        none(), // extra account metas
+        none(), // seat section
+        none(), // seat reservation
+        metadata_config_account(fixture.event, stage_templates()),
+        none(), // collection mint
+        none(), // collection metadata
+        none(), // collection master edition
+        none(), // authority payment account
+        none(), // vault payment account
+    ]
+}
+
+/// Runs account validation and the `mint_ticket` handler for a free ticket,
+/// returning the event and the minted ticket.
+fn try_mint(accounts: &mut [TestAccount]) -> Result<(EventAccount, TicketAccount)> {
+    let mut ix_data = None::<String>.try_to_vec().unwrap(); // seat
+    0u64.serialize(&mut ix_data).unwrap(); // price_lamports
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = MintTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &ix_data,
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    mint_ticket_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        None,
+        0,
+    )?;
+    Ok((
+        (*ctx_accounts.event_account).clone(),
+        (*ctx_accounts.ticket_account).clone(),
+    ))
+}
+
+#[test]
+fn buy_assigns_next_serial() {
+    let fixture = Fixture::new();
+    // One ticket already sold, so this purchase is serial 2
+    let bought = try_buy(&mut buy_accounts(fixture.buyer, &fixture.event_state()), None).unwrap();
+    assert_eq!(bought.ticket.serial, 2);
+}
+
+#[test]
+fn buy_rejects_ticket_at_another_serial() {
+    let fixture = Fixture::new();
+    let mut accounts = buy_accounts(fixture.buyer, &fixture.event_state());
+    let (taken, _) = TicketAccount::pda(&fixture.event, 1);
+    accounts[BUY_TICKET] = TestAccount::uninitialized(taken, 8 + TicketAccount::INIT_SPACE);
+    match try_buy(&mut accounts, None) {
+        Err(Error::AnchorError(e)) => assert_eq!(
+            e.error_code_number,
+            u32::from(anchor_lang::error::ErrorCode::ConstraintSeeds)
+        ),
+        Err(e) => panic!("expected ConstraintSeeds, got {e:?}"),
+        Ok(_) => panic!("expected ConstraintSeeds, but the purchase went through"),
+    }
+}
+
+#[test]
+fn mint_and_buy_share_one_serial_sequence() {
+    let fixture = Fixture::new();
+    let (event, minted) = try_mint(&mut mint_accounts(&fixture, &fixture.event_state())).unwrap();
+    assert_eq!(minted.serial, 2);
+    assert_eq!(event.tickets_sold, 2);
+
+    let bought = try_buy(&mut buy_accounts(fixture.buyer, &event), None).unwrap();
+    assert_eq!(bought.ticket.serial, 3);
+
+    // The purchase took serial 3, so the next organizer mint follows it
+    let event = EventAccount {
+        tickets_sold: 3,
+        ..event
+    };
+    let (_, minted) = try_mint(&mut mint_accounts(&fixture, &event)).unwrap();
+    assert_eq!(minted.serial, 4);
+}
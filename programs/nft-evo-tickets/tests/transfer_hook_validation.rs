@@ -0,0 +1,127 @@
+//! The Token-2022 transfer hook that keeps `TicketAccount.owner` in sync.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_hook::TransferHookAccount, BaseStateWithExtensionsMut, ExtensionType,
+        StateWithExtensionsMut,
+    },
+    state::{Account, AccountState},
+};
+
+use common::{assert_error, event_account, event_pda, infos, pda, Fixture, TestAccount};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{transfer_hook_handler, TicketAccount, TransferHookCtx};
+
+/// Positions in `hook_accounts` that tests swap out.
+const HOOK_TICKET: usize = 5;
+const HOOK_EVENT: usize = 6;
+
+/// Accounts of the Execute instruction for a transfer from `holder` to `buyer`,
+/// in the order Token-2022 passes them.
+fn hook_accounts(fixture: &Fixture, transferring: bool) -> Vec<TestAccount> {
+    let mut mint = fixture.nft_mint();
+    mint.owner = spl_token_2022::ID;
+    vec![
+        token_2022_account(fixture.mint, fixture.holder, transferring),
+        mint,
+        token_2022_account(fixture.mint, fixture.buyer, false),
+        TestAccount::wallet(fixture.holder),
+        TestAccount::wallet(pda(&[b"extra-account-metas", fixture.mint.as_ref()])),
+        fixture.ticket_account(&fixture.ticket_state(false)),
+        fixture.event_account(&fixture.event_state()),
+    ]
+}
+
+/// A Token-2022 account carrying the TransferHookAccount extension.
+fn token_2022_account(mint: Pubkey, owner: Pubkey, transferring: bool) -> TestAccount {
+    let space =
+        ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferHookAccount])
+            .unwrap();
+    let mut data = vec![0; space];
+    let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+    state.base = Account {
+        mint,
+        owner,
+        amount: u64::from(transferring),
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    state.init_extension::<TransferHookAccount>(true).unwrap().transferring = transferring.into();
+
+    let mut account = TestAccount::token(mint, owner, 0);
+    account.key = Pubkey::new_unique();
+    account.owner = spl_token_2022::ID;
+    account.data = data;
+    account
+}
+
+/// Runs account validation and the handler, returning the ticket owner afterwards.
+fn try_hook(accounts: &mut [TestAccount]) -> Result<Pubkey> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = TransferHookCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &1u64.to_le_bytes(),
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    transfer_hook_handler(
+        Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps),
+        1,
+    )?;
+    Ok(ctx_accounts.ticket_account.owner)
+}
+
+#[test]
+fn hook_moves_ticket_owner_mid_transfer() {
+    let fixture = Fixture::new();
+    let owner = try_hook(&mut hook_accounts(&fixture, true)).unwrap();
+    assert_eq!(owner, fixture.buyer);
+}
+
+#[test]
+fn hook_rejects_direct_invocation() {
+    let fixture = Fixture::new();
+    assert_error(try_hook(&mut hook_accounts(&fixture, false)), ErrorCode::NotTransferring);
+}
+
+#[test]
+fn hook_rejects_ticket_of_another_mint() {
+    let fixture = Fixture::new();
+    let mut accounts = hook_accounts(&fixture, true);
+    let other_mint = TicketAccount {
+        nft_mint: Pubkey::new_unique(),
+        ..fixture.ticket_state(false)
+    };
+    accounts[HOOK_TICKET] = fixture.ticket_account(&other_mint);
+    assert_error(try_hook(&mut accounts), ErrorCode::MintMismatch);
+}
+
+#[test]
+fn hook_rejects_listed_ticket() {
+    let fixture = Fixture::new();
+    let mut accounts = hook_accounts(&fixture, true);
+    accounts[HOOK_TICKET] = fixture.ticket_account(&fixture.ticket_state(true));
+    assert_error(try_hook(&mut accounts), ErrorCode::TicketAlreadyListed);
+}
+
+#[test]
+fn hook_rejects_ticket_of_another_event() {
+    let fixture = Fixture::new();
+    let mut accounts = hook_accounts(&fixture, true);
+    accounts[HOOK_EVENT] = event_account(event_pda(2), 2, Pubkey::new_unique());
+    assert_error(try_hook(&mut accounts), ErrorCode::EventMismatch);
+}
@@ -0,0 +1,106 @@
+//! `transfer_ticket` under the organizer's transfer policy.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, config_account, infos, ticket_authority, token_balance, Fixture, TestAccount, NOW,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{transfer_ticket_handler, EventAccount, TransferPolicy, TransferTicketCtx};
+
+/// Positions in `transfer_accounts` that tests swap out.
+const TRANSFER_OWNER_NFT_ACCOUNT: usize = 5;
+const TRANSFER_RECIPIENT_NFT_ACCOUNT: usize = 7;
+
+/// Accounts for `transfer_ticket` of the fixture's ticket from `holder` to `buyer`.
+fn transfer_accounts(fixture: &Fixture, event: EventAccount) -> Vec<TestAccount> {
+    vec![
+        TestAccount::signer(fixture.holder),
+        config_account(),
+        fixture.event_account(&event),
+        fixture.ticket_account(&fixture.ticket_state(false)),
+        fixture.nft_mint(),
+        fixture.nft_account(fixture.holder, 1),
+        TestAccount::wallet(fixture.buyer),
+        fixture.nft_account(fixture.buyer, 0),
+        ticket_authority(),
+        TestAccount::program(spl_token::ID),
+        TestAccount::program(anchor_spl::associated_token::ID),
+        TestAccount::program(System::id()),
+    ]
+}
+
+fn policy_event(fixture: &Fixture, transfer_policy: TransferPolicy, max_transfers: Option<u16>) -> EventAccount {
+    EventAccount {
+        transfer_policy,
+        max_transfers_per_ticket: max_transfers,
+        ..fixture.event_state()
+    }
+}
+
+/// Runs account validation and the handler, returning the ticket's owner and transfer count.
+fn try_transfer(accounts: &mut [TestAccount]) -> Result<(Pubkey, u16)> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = TransferTicketCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    transfer_ticket_handler(Context::new(&nft_evo_tickets::ID, &mut ctx_accounts, &[], bumps))?;
+    let ticket = &ctx_accounts.ticket_account;
+    Ok((ticket.owner, ticket.transfer_count))
+}
+
+#[test]
+fn transfer_moves_ticket_and_counts_it() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, TransferPolicy::Allowed, Some(1));
+    let mut accounts = transfer_accounts(&fixture, event);
+    let (owner, transfer_count) = try_transfer(&mut accounts).unwrap();
+    assert_eq!(owner, fixture.buyer);
+    assert_eq!(transfer_count, 1);
+    assert_eq!(token_balance(&accounts[TRANSFER_OWNER_NFT_ACCOUNT]), 0);
+    assert_eq!(token_balance(&accounts[TRANSFER_RECIPIENT_NFT_ACCOUNT]), 1);
+}
+
+#[test]
+fn transfer_rejects_disallowed_policy() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, TransferPolicy::Disallowed, None);
+    assert_error(try_transfer(&mut transfer_accounts(&fixture, event)), ErrorCode::TransfersDisabled);
+}
+
+#[test]
+fn transfer_before_start_closes_at_start_ts() {
+    let fixture = Fixture::new();
+    let mut event = policy_event(&fixture, TransferPolicy::BeforeStart, None);
+    event.start_ts = NOW;
+    assert_error(try_transfer(&mut transfer_accounts(&fixture, event)), ErrorCode::TransferWindowClosed);
+
+    let mut event = policy_event(&fixture, TransferPolicy::BeforeStart, None);
+    event.start_ts = NOW + 1;
+    try_transfer(&mut transfer_accounts(&fixture, event)).unwrap();
+}
+
+#[test]
+fn transfer_rejects_ticket_at_its_limit() {
+    let fixture = Fixture::new();
+    let event = policy_event(&fixture, TransferPolicy::Allowed, Some(0));
+    assert_error(try_transfer(&mut transfer_accounts(&fixture, event)), ErrorCode::TransferLimitReached);
+}
+
+#[test]
+fn transfer_rejects_signer_without_the_token() {
+    let fixture = Fixture::new();
+    let mut accounts = transfer_accounts(&fixture, policy_event(&fixture, TransferPolicy::Allowed, None));
+    accounts[TRANSFER_OWNER_NFT_ACCOUNT] = fixture.nft_account(fixture.holder, 0);
+    assert_error(try_transfer(&mut accounts), ErrorCode::NotTicketHolder);
+}
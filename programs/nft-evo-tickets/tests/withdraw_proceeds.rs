@@ -0,0 +1,176 @@
+//! Sweeping event proceeds out of the vault once the dispute window has closed.
+mod common;
+
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+
+use common::{
+    assert_error, config_account, infos, token_balance, vault_state, vault_state_account, Fixture,
+    TestAccount, NOW,
+};
+use nft_evo_tickets::error::ErrorCode;
+use nft_evo_tickets::{
+    withdraw_event_proceeds_handler, DutchAuction, EventAccount, EventStatus, EventVault,
+    WithdrawEventProceedsCtx,
+};
+
+const SOL: u64 = 1_000_000_000;
+
+/// Positions in `withdraw_accounts` that tests swap out.
+const WITHDRAW_AUTHORITY: usize = 0;
+const WITHDRAW_VAULT: usize = 3;
+const WITHDRAW_VAULT_PAYMENT: usize = 5;
+const WITHDRAW_AUTHORITY_PAYMENT: usize = 6;
+
+/// Accounts for `withdraw_event_proceeds` with 3 SOL of proceeds in the vault, or
+/// 3 SOL worth of `payment_mint` base units when the event is token priced.
+fn withdraw_accounts(fixture: &Fixture, event: &EventAccount) -> Vec<TestAccount> {
+    let mut vault = fixture.vault_account();
+    let (vault_payment, authority_payment) = match event.payment_mint {
+        Some(payment_mint) => (
+            TestAccount::token(payment_mint, vault.key, 3 * SOL),
+            TestAccount::token(payment_mint, fixture.authority, 0),
+        ),
+        None => {
+            vault.lamports += 3 * SOL;
+            (
+                TestAccount::program(nft_evo_tickets::ID),
+                TestAccount::program(nft_evo_tickets::ID),
+            )
+        }
+    };
+    vec![
+        TestAccount::signer(fixture.authority),
+        config_account(),
+        fixture.event_account(event),
+        vault,
+        TestAccount::program(spl_token::ID),
+        vault_payment,
+        authority_payment,
+    ]
+}
+
+/// Withdraws the proceeds, returning the event and vault afterwards.
+fn try_withdraw(accounts: &mut [TestAccount]) -> Result<(EventAccount, EventVault)> {
+    let infos = infos(accounts);
+    let mut remaining = &infos[..];
+    let mut bumps = Default::default();
+    let mut ctx_accounts = WithdrawEventProceedsCtx::try_accounts(
+        &nft_evo_tickets::ID,
+        &mut remaining,
+        &[],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    withdraw_event_proceeds_handler(Context::new(
+        &nft_evo_tickets::ID,
+        &mut ctx_accounts,
+        &[],
+        bumps,
+    ))?;
+    Ok((
+        (*ctx_accounts.event_account).clone(),
+        (*ctx_accounts.event_vault).clone(),
+    ))
+}
+
+#[test]
+fn withdraw_waits_for_dispute_window() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        end_ts: NOW,
+        ..fixture.event_state()
+    };
+    let mut accounts = withdraw_accounts(&fixture, &event);
+    assert_error(try_withdraw(&mut accounts), ErrorCode::DisputeWindowActive);
+
+    let ended = EventAccount {
+        end_ts: NOW - 1,
+        ..event
+    };
+    let mut accounts = withdraw_accounts(&fixture, &ended);
+    assert!(try_withdraw(&mut accounts).is_ok());
+}
+
+#[test]
+fn withdraw_sweeps_lamports_above_rent() {
+    let fixture = Fixture::new();
+    let event = fixture.event_state();
+    let mut accounts = withdraw_accounts(&fixture, &event);
+    let authority_lamports = accounts[WITHDRAW_AUTHORITY].lamports;
+    let rent = Rent::default().minimum_balance(accounts[WITHDRAW_VAULT].data.len());
+
+    let (event, vault) = try_withdraw(&mut accounts).unwrap();
+    assert_eq!(
+        accounts[WITHDRAW_AUTHORITY].lamports - authority_lamports,
+        3 * SOL
+    );
+    assert_eq!(accounts[WITHDRAW_VAULT].lamports, rent);
+    assert_eq!(vault.total_withdrawn, 3 * SOL);
+    assert!(event.status == EventStatus::Settled);
+}
+
+#[test]
+fn withdraw_sweeps_token_balance() {
+    let fixture = Fixture::new();
+    let event = EventAccount {
+        payment_mint: Some(Pubkey::new_unique()),
+        ..fixture.event_state()
+    };
+    let mut accounts = withdraw_accounts(&fixture, &event);
+    let vault_lamports = accounts[WITHDRAW_VAULT].lamports;
+
+    let (settled, vault) = try_withdraw(&mut accounts).unwrap();
+    assert_eq!(accounts[WITHDRAW_VAULT].lamports, vault_lamports);
+    assert_eq!(token_balance(&accounts[WITHDRAW_VAULT_PAYMENT]), 0);
+    assert_eq!(token_balance(&accounts[WITHDRAW_AUTHORITY_PAYMENT]), 3 * SOL);
+    assert_eq!(vault.total_withdrawn, 3 * SOL);
+    assert!(settled.status == EventStatus::Settled);
+
+    let mut missing_payment_accounts = withdraw_accounts(&fixture, &event);
+    missing_payment_accounts[WITHDRAW_AUTHORITY_PAYMENT] =
+        TestAccount::program(nft_evo_tickets::ID);
+    assert_error(try_withdraw(&mut missing_payment_accounts), ErrorCode::MissingPaymentAccounts);
+}
+
+#[test]
+fn withdraw_leaves_unclaimed_rebates_in_vault() {
+    let fixture = Fixture::new();
+    // Two tickets sold at 2 SOL by an auction that ended at its 1 SOL floor
+    let auction_event = |payment_mint| EventAccount {
+        dutch_auction: Some(DutchAuction {
+            start_price_lamports: 5 * SOL,
+            floor_price_lamports: SOL,
+            start_ts: NOW - 7_200,
+            end_ts: NOW - 3_600,
+            step_secs: 0,
+            rebate: true,
+        }),
+        payment_mint,
+        ..fixture.event_state()
+    };
+    let with_auction_sales = |accounts: &mut Vec<TestAccount>| {
+        let lamports = accounts[WITHDRAW_VAULT].lamports;
+        accounts[WITHDRAW_VAULT] = vault_state_account(&EventVault {
+            unrebated_auction_sales: 2,
+            unrebated_auction_proceeds: 4 * SOL,
+            ..vault_state(fixture.event)
+        });
+        accounts[WITHDRAW_VAULT].lamports = lamports;
+    };
+
+    let mut accounts = withdraw_accounts(&fixture, &auction_event(None));
+    with_auction_sales(&mut accounts);
+    let authority_lamports = accounts[WITHDRAW_AUTHORITY].lamports;
+    let (_, vault) = try_withdraw(&mut accounts).unwrap();
+    assert_eq!(accounts[WITHDRAW_AUTHORITY].lamports - authority_lamports, SOL);
+    assert_eq!(vault.total_withdrawn, SOL);
+
+    let mut accounts = withdraw_accounts(&fixture, &auction_event(Some(Pubkey::new_unique())));
+    with_auction_sales(&mut accounts);
+    try_withdraw(&mut accounts).unwrap();
+    assert_eq!(token_balance(&accounts[WITHDRAW_VAULT_PAYMENT]), 2 * SOL);
+    assert_eq!(token_balance(&accounts[WITHDRAW_AUTHORITY_PAYMENT]), SOL);
+}
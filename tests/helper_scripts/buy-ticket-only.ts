@@ -118,6 +118,9 @@ describe("Buy Ticket - Simple Test", function() {
         ticketAccount: ticketPda,
         authority: seller.publicKey,
         scanner: scanner.publicKey,
+        ticketMint: nftMint,
+        metadataAccount: metadataPda,
+        ticketTokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([seller])
       .rpc();
@@ -143,7 +146,7 @@ describe("Buy Ticket - Simple Test", function() {
     );
 
     await program.methods
-      .listTicket(priceLamports, null)
+      .listTicket(priceLamports, null, null)
       .accounts({
         seller: seller.publicKey,
         ticketAccount: ticketPda,
@@ -163,6 +163,9 @@ async function main() {
       ticketAccount: ticketPda,
       authority: authorityKeypair.publicKey,
       scanner: scanner.publicKey,
+      ticketMint: nftMint,
+      metadataAccount: metadataPda,
+      ticketTokenProgram: TOKEN_PROGRAM_ID,
     })
     .rpc();
 
@@ -1,6 +1,7 @@
 import * as anchor from '@coral-xyz/anchor';
 import { Program, AnchorProvider } from '@coral-xyz/anchor';
 import { Connection, PublicKey, clusterApiUrl } from '@solana/web3.js';
+import { TOKEN_PROGRAM_ID } from '@solana/spl-token';
 import { NftEvoTickets } from '../../target/types/nft_evo_tickets';
 import * as fs from 'fs';
 
@@ -144,6 +145,13 @@ async function validateTicketFromMint(
 
     console.log('\n✅ Scanner authorized. Proceeding with validation...');
 
+    // The NFT's Metaplex metadata evolves with the ticket
+    const metaplexMetadataProgramId = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
+    const [metadataPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), metaplexMetadataProgramId.toBuffer(), ticket.nftMint.toBuffer()],
+      metaplexMetadataProgramId
+    );
+
     // Call update_ticket instruction with Scanned stage
     const tx = await program.methods
       .updateTicket({ scanned: {} })
@@ -153,6 +161,9 @@ async function validateTicketFromMint(
         ticketAccount: ticketPDA,
         authority: eventAccount.authority,
         scanner: eventAccount.scanner,
+        ticketMint: ticket.nftMint,
+        metadataAccount: metadataPda,
+        ticketTokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([scannerKeypair])
       .rpc();
@@ -0,0 +1,19 @@
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { NftEvoTickets } from "../../target/types/nft_evo_tickets";
+import { metadataConfigPda } from "./pdas";
+
+/** Gives the event the same URI template at every stage, tickets can't be minted without one. */
+export async function setTicketTemplates(
+  program: Program<NftEvoTickets>,
+  eventPda: PublicKey,
+  organizer?: Keypair,
+  template = "https://cdn.example.com/{event_id}/{serial}/{stage}.json"
+) {
+  const authority = organizer?.publicKey ?? program.provider.publicKey!;
+  await program.methods
+    .setMetadataConfig({ prestige: template, qr: template, scanned: template, collectible: template })
+    .accounts({ authority, eventAccount: eventPda, metadataConfig: metadataConfigPda(program.programId, eventPda) })
+    .signers(organizer ? [organizer] : [])
+    .rpc();
+}
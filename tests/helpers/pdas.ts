@@ -0,0 +1,44 @@
+import { Program } from "@coral-xyz/anchor";
+import { PublicKey } from "@solana/web3.js";
+import { NftEvoTickets } from "../../target/types/nft_evo_tickets";
+
+const PROGRAM_SEED = Buffer.from("nft-evo-tickets");
+
+/** Ticket and NFT mint PDAs of ticket `serial` of an event (serials start at 1). */
+export function ticketPdas(programId: PublicKey, eventPda: PublicKey, serial: number) {
+  const serialBytes = Buffer.alloc(4);
+  serialBytes.writeUInt32LE(serial);
+  const [ticketPda] = PublicKey.findProgramAddressSync(
+    [PROGRAM_SEED, Buffer.from("ticket"), eventPda.toBuffer(), serialBytes],
+    programId
+  );
+  const [nftMint] = PublicKey.findProgramAddressSync(
+    [PROGRAM_SEED, Buffer.from("nft-mint"), eventPda.toBuffer(), serialBytes],
+    programId
+  );
+  return { ticketPda, nftMint };
+}
+
+/** PDAs the next `mintTicket` or `buyEventTicket` on the event will create. */
+export async function nextTicketPdas(program: Program<NftEvoTickets>, eventPda: PublicKey) {
+  const event = await program.account.eventAccount.fetch(eventPda);
+  return ticketPdas(program.programId, eventPda, event.ticketsSold + 1);
+}
+
+/** Reservation PDA that makes `seat` unique within an event. */
+export function seatPda(programId: PublicKey, eventPda: PublicKey, seat: string) {
+  const [seatReservation] = PublicKey.findProgramAddressSync(
+    [PROGRAM_SEED, Buffer.from("seat"), eventPda.toBuffer(), Buffer.from(seat)],
+    programId
+  );
+  return seatReservation;
+}
+
+/** Stage URI templates PDA of an event, every mint renders its URI from them. */
+export function metadataConfigPda(programId: PublicKey, eventPda: PublicKey) {
+  const [metadataConfig] = PublicKey.findProgramAddressSync(
+    [PROGRAM_SEED, Buffer.from("metadata-config"), eventPda.toBuffer()],
+    programId
+  );
+  return metadataConfig;
+}
@@ -2,8 +2,10 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { NftEvoTickets } from "../target/types/nft_evo_tickets";
 import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddress } from "@solana/spl-token";
+import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddress, createMint, TOKEN_2022_PROGRAM_ID, getMint, getExtensionTypes, ExtensionType } from "@solana/spl-token";
 import { expect } from "chai";
+import { setTicketTemplates } from "./helpers/metadata";
+import { metadataConfigPda, nextTicketPdas, seatPda } from "./helpers/pdas";
 
 describe("Instructions (programs) Tests", function() {
   // to run them: yarn test:instructions
@@ -14,9 +16,40 @@ describe("Instructions (programs) Tests", function() {
   const provider = anchor.getProvider();
 
   describe("Initialize", () => {
-    it("should initialize successfully", async () => {
-      const tx = await program.methods.initialize().rpc();
-      expect(tx).to.be.a("string");
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nft-evo-tickets"), Buffer.from("config")],
+      program.programId
+    );
+    const [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    it("should initialize the program config once", async () => {
+      const existing = await program.account.programConfig.fetchNullable(configPda);
+      if (!existing) {
+        await program.methods
+          .initialize(provider.wallet!.publicKey, 500, 1000, new BN(0))
+          .accounts({ admin: provider.wallet!.publicKey, programData: programDataPda })
+          .rpc();
+      }
+
+      const config = await program.account.programConfig.fetch(configPda);
+      expect(config.marketplaceFeeBps).to.be.at.most(config.maxRoyaltyBps);
+    });
+
+    it("should fail when a non-admin updates the config", async () => {
+      const attacker = Keypair.generate();
+      try {
+        await program.methods
+          .updateConfig(attacker.publicKey, 0, 0, new BN(0))
+          .accounts({ admin: attacker.publicKey, programConfig: configPda })
+          .signers([attacker])
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("Unauthorized operation for caller");
+      }
     });
   });
 
@@ -150,21 +183,14 @@ describe("Instructions (programs) Tests", function() {
           systemProgram: SystemProgram.programId,
         })
         .rpc();
+      await setTicketTemplates(program, eventPda);
     });
 
     it("should mint a ticket successfully", async () => {
       const ticketOwner = provider.wallet!.publicKey;
       const seat = "A1";
 
-      const [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
-
-      const [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
+      const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
 
       const [metadataPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -179,10 +205,12 @@ describe("Instructions (programs) Tests", function() {
       const tokenAccountPda = await getAssociatedTokenAddress(nftMint, ticketOwner);
 
       await program.methods
-        .mintTicket(seat, null)
+        .mintTicket(seat, new anchor.BN(0))
         .accounts({
           authority: provider.wallet!.publicKey,
           eventAccount: eventPda,
+          metadataConfig: metadataConfigPda(program.programId, eventPda),
+          seatReservation: seatPda(program.programId, eventPda, seat),
           ticketAccount: ticketPda,
           owner: ticketOwner,
           nftMint: nftMint,
@@ -190,6 +218,7 @@ describe("Instructions (programs) Tests", function() {
           masterEdition: masterEditionPda,
           tokenAccount: tokenAccountPda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          ticketTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -218,15 +247,7 @@ describe("Instructions (programs) Tests", function() {
 
       const ticketOwner = provider.wallet!.publicKey;
 
-      const [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
-
-      const [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
+      const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
 
       const [metadataPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -242,10 +263,12 @@ describe("Instructions (programs) Tests", function() {
 
       try {
         await program.methods
-          .mintTicket("B1", null)
+          .mintTicket("B1", new anchor.BN(0))
           .accounts({
             authority: unauthorizedUser.publicKey,
             eventAccount: eventPda,
+            metadataConfig: metadataConfigPda(program.programId, eventPda),
+            seatReservation: seatPda(program.programId, eventPda, "B1"),
             ticketAccount: ticketPda,
             owner: ticketOwner,
             nftMint: nftMint,
@@ -253,6 +276,7 @@ describe("Instructions (programs) Tests", function() {
             masterEdition: masterEditionPda,
             tokenAccount: tokenAccountPda,
             tokenProgram: TOKEN_PROGRAM_ID,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -293,18 +317,11 @@ describe("Instructions (programs) Tests", function() {
           systemProgram: SystemProgram.programId,
         })
         .rpc();
+      await setTicketTemplates(program, eventPda);
 
       ticketOwner = provider.wallet!.publicKey;
 
-      [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
-
-      [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
+      ({ ticketPda, nftMint } = await nextTicketPdas(program, eventPda));
 
       const [metadataPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -319,10 +336,12 @@ describe("Instructions (programs) Tests", function() {
       const tokenAccountPda = await getAssociatedTokenAddress(nftMint, ticketOwner);
 
       await program.methods
-        .mintTicket("C1", null)
+        .mintTicket("C1", new anchor.BN(0))
         .accounts({
           authority: provider.wallet!.publicKey,
           eventAccount: eventPda,
+          metadataConfig: metadataConfigPda(program.programId, eventPda),
+          seatReservation: seatPda(program.programId, eventPda, "C1"),
           ticketAccount: ticketPda,
           owner: ticketOwner,
           nftMint: nftMint,
@@ -330,6 +349,7 @@ describe("Instructions (programs) Tests", function() {
           masterEdition: masterEditionPda,
           tokenAccount: tokenAccountPda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          ticketTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -352,7 +372,7 @@ describe("Instructions (programs) Tests", function() {
 
       try {
         await program.methods
-          .listTicket(price, expiresAt)
+          .listTicket(price, expiresAt, null)
           .accounts({
             seller: ticketOwner,
             ticketAccount: ticketPda,
@@ -398,7 +418,7 @@ describe("Instructions (programs) Tests", function() {
 
       try {
         await program.methods
-          .listTicket(price, null)
+          .listTicket(price, null, null)
           .accounts({
             seller: unauthorizedUser.publicKey,
             ticketAccount: ticketPda,
@@ -441,18 +461,11 @@ describe("Instructions (programs) Tests", function() {
           systemProgram: SystemProgram.programId,
         })
         .rpc();
+      await setTicketTemplates(program, eventPda);
 
       const ticketOwner = provider.wallet!.publicKey;
 
-      const [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
-
-      const [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
+      const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
 
       const [metadataPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -467,10 +480,12 @@ describe("Instructions (programs) Tests", function() {
       const tokenAccountPda = await getAssociatedTokenAddress(nftMint, ticketOwner);
 
       await program.methods
-        .mintTicket("D1", null)
+        .mintTicket("D1", new anchor.BN(0))
         .accounts({
           authority: provider.wallet!.publicKey,
           eventAccount: eventPda,
+          metadataConfig: metadataConfigPda(program.programId, eventPda),
+          seatReservation: seatPda(program.programId, eventPda, "D1"),
           ticketAccount: ticketPda,
           owner: ticketOwner,
           nftMint: nftMint,
@@ -478,6 +493,7 @@ describe("Instructions (programs) Tests", function() {
           masterEdition: masterEditionPda,
           tokenAccount: tokenAccountPda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          ticketTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -534,18 +550,11 @@ describe("Instructions (programs) Tests", function() {
           systemProgram: SystemProgram.programId,
         })
         .rpc();
+      await setTicketTemplates(program, eventPda);
 
       const ticketOwner = provider.wallet!.publicKey;
 
-      const [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
-
-      const [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-      );
+      const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
 
       const [metadataPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -560,10 +569,12 @@ describe("Instructions (programs) Tests", function() {
       const tokenAccountPda = await getAssociatedTokenAddress(nftMint, ticketOwner);
 
       await program.methods
-        .mintTicket("E1", null)
+        .mintTicket("E1", new anchor.BN(0))
         .accounts({
           authority: provider.wallet!.publicKey,
           eventAccount: eventPda,
+          metadataConfig: metadataConfigPda(program.programId, eventPda),
+          seatReservation: seatPda(program.programId, eventPda, "E1"),
           ticketAccount: ticketPda,
           owner: ticketOwner,
           nftMint: nftMint,
@@ -571,6 +582,7 @@ describe("Instructions (programs) Tests", function() {
           masterEdition: masterEditionPda,
           tokenAccount: tokenAccountPda,
           tokenProgram: TOKEN_PROGRAM_ID,
+          ticketTokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -613,4 +625,872 @@ describe("Instructions (programs) Tests", function() {
       }
     });
   });
+
+  describe("Ticket Tiers", () => {
+    let eventPda: PublicKey;
+
+    const tierPda = (index: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("tier"), eventPda.toBuffer(), Buffer.from([index])],
+        program.programId
+      )[0];
+
+    beforeEach(async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Tier Test Event", startTs, endTs, 100, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("should create tiers with sequential indexes", async () => {
+      await program.methods
+        .createTicketTier("GA", new BN(100_000_000), 80)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda, ticketTier: tierPda(0) })
+        .rpc();
+      await program.methods
+        .createTicketTier("VIP", new BN(500_000_000), 20)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda, ticketTier: tierPda(1) })
+        .rpc();
+
+      const vip = await program.account.ticketTier.fetch(tierPda(1));
+      expect(vip.name).to.equal("VIP");
+      expect(vip.priceLamports.toString()).to.equal("500000000");
+      expect(vip.supply).to.equal(20);
+      expect(vip.sold).to.equal(0);
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.tierCount).to.equal(2);
+      expect(eventAccount.tieredSupply).to.equal(100);
+    });
+
+    it("should fail when tier supply exceeds the event supply", async () => {
+      try {
+        await program.methods
+          .createTicketTier("Backstage", new BN(1_000_000_000), 101)
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda, ticketTier: tierPda(0) })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("Tier supply exceeds the event ticket supply");
+      }
+    });
+  });
+
+  describe("Pause", () => {
+    it("should reject minting while the event is paused", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+      const ticketOwner = Keypair.generate().publicKey;
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Paused Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await setTicketTemplates(program, eventPda);
+
+      await program.methods
+        .setEventPaused(true)
+        .accounts({ signer: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
+      const [metadataPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
+        new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s")
+      );
+      const [masterEditionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer(), Buffer.from("edition")],
+        new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s")
+      );
+
+      try {
+        await program.methods
+          .mintTicket("P1", new anchor.BN(0))
+          .accounts({
+            authority: provider.wallet!.publicKey,
+            eventAccount: eventPda,
+            metadataConfig: metadataConfigPda(program.programId, eventPda),
+            seatReservation: seatPda(program.programId, eventPda, "P1"),
+            ticketAccount: ticketPda,
+            owner: ticketOwner,
+            nftMint: nftMint,
+            metadata: metadataPda,
+            masterEdition: masterEditionPda,
+            tokenAccount: await getAssociatedTokenAddress(nftMint, ticketOwner),
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("This event is paused");
+      }
+    });
+  });
+
+  describe("Cancel Event", () => {
+    let eventPda: PublicKey;
+    let eventId: BN;
+
+    beforeEach(async () => {
+      eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Cancelled Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("should cancel an event only once", async () => {
+      await program.methods
+        .cancelEvent()
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.status).to.deep.equal({ cancelled: {} });
+
+      try {
+        await program.methods
+          .cancelEvent()
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("Event is not active");
+      }
+    });
+
+    it("should delete a cancelled event with no tickets sold", async () => {
+      await program.methods
+        .cancelEvent()
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      await program.methods
+        .deleteEvent(eventId)
+        .accounts({
+          authority: provider.wallet!.publicKey,
+          tokenProgram: null,
+          vaultPaymentAccount: null,
+        })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetchNullable(eventPda);
+      expect(eventAccount).to.be.null;
+    });
+  });
+
+  describe("Payment Mint", () => {
+    it("should price an event in an SPL token and back in SOL", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [vaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("vault"), eventPda.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "USDC Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const usdc = await createMint(
+        provider.connection,
+        (provider.wallet as anchor.Wallet).payer,
+        provider.wallet!.publicKey,
+        null,
+        6
+      );
+      const vaultPaymentAccount = await getAssociatedTokenAddress(usdc, vaultPda, true);
+
+      await program.methods
+        .setPaymentMint()
+        .accountsPartial({
+          authority: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          eventVault: vaultPda,
+          paymentMint: usdc,
+          vaultPaymentAccount,
+        })
+        .rpc();
+
+      let eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.paymentMint?.toString()).to.equal(usdc.toString());
+
+      await program.methods
+        .setPaymentMint()
+        .accountsPartial({
+          authority: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          eventVault: vaultPda,
+          paymentMint: null,
+          vaultPaymentAccount: null,
+        })
+        .rpc();
+
+      eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.paymentMint).to.be.null;
+    });
+  });
+
+  describe("Ticket Mint Mode", () => {
+    it("should mint soulbound Token-2022 tickets", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+      const ticketOwner = provider.wallet!.publicKey;
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
+      const metadataProgram = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+      const [metadataPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), metadataProgram.toBuffer(), nftMint.toBuffer()],
+        metadataProgram
+      );
+      const [masterEditionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), metadataProgram.toBuffer(), nftMint.toBuffer(), Buffer.from("edition")],
+        metadataProgram
+      );
+
+      await program.methods
+        .createEvent(eventId, "Soulbound Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await setTicketTemplates(program, eventPda);
+
+      await program.methods
+        .setTicketMintMode({ nonTransferable: {} })
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      await program.methods
+        .mintTicket("S1", new anchor.BN(0))
+        .accountsPartial({
+          authority: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          metadataConfig: metadataConfigPda(program.programId, eventPda),
+          seatReservation: seatPda(program.programId, eventPda, "S1"),
+          ticketAccount: ticketPda,
+          owner: ticketOwner,
+          nftMint,
+          metadata: metadataPda,
+          masterEdition: masterEditionPda,
+          tokenAccount: await getAssociatedTokenAddress(nftMint, ticketOwner, false, TOKEN_2022_PROGRAM_ID),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ticketTokenProgram: TOKEN_2022_PROGRAM_ID,
+          extraAccountMetaList: null,
+        })
+        .rpc();
+
+      const mint = await getMint(provider.connection, nftMint, undefined, TOKEN_2022_PROGRAM_ID);
+      expect(getExtensionTypes(mint.tlvData)).to.include(ExtensionType.NonTransferable);
+    });
+  });
+
+  describe("Transfer Policy", () => {
+    it("should let the organizer restrict ticket transfers", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Policy Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      let eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.transferPolicy).to.deep.equal({ allowed: {} });
+      expect(eventAccount.maxTransfersPerTicket).to.be.null;
+
+      await program.methods
+        .setTransferPolicy({ beforeStart: {} }, 2)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.transferPolicy).to.deep.equal({ beforeStart: {} });
+      expect(eventAccount.maxTransfersPerTicket).to.equal(2);
+    });
+  });
+
+  describe("Resale Policy", () => {
+    it("should let the organizer cap resale prices and listings", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Resale Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .setResalePolicy(1000, new BN(3600), 2)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.maxResaleMarkupBps).to.equal(1000);
+      expect(eventAccount.resaleCooldownSecs?.toNumber()).to.equal(3600);
+      expect(eventAccount.maxListingsPerWallet).to.equal(2);
+
+      try {
+        await program.methods
+          .setResalePolicy(null, new BN(-1), null)
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("InvalidInput");
+      }
+    });
+  });
+
+  describe("Royalty Split", () => {
+    it("should store an organizer royalty split", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+      const artist = Keypair.generate().publicKey;
+      const venue = Keypair.generate().publicKey;
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Royalty Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .setRoyaltySplit(800, [
+          { address: provider.wallet!.publicKey, shareBps: 400 },
+          { address: artist, shareBps: 250 },
+          { address: venue, shareBps: 150 },
+        ])
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.royaltyBps).to.equal(800);
+      expect(eventAccount.royaltyRecipients.map((r: any) => r.address.toString())).to.deep.equal([
+        provider.wallet!.publicKey.toString(),
+        artist.toString(),
+        venue.toString(),
+      ]);
+
+      try {
+        await program.methods
+          .setRoyaltySplit(800, [{ address: artist, shareBps: 500 }])
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("InvalidRoyaltySplit");
+      }
+    });
+  });
+
+  describe("Purchase Limit", () => {
+    it("should cap tickets per wallet", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Limited Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .setPurchaseLimit(2)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.maxTicketsPerWallet).to.equal(2);
+    });
+  });
+
+  describe("Seat Map", () => {
+    it("should let the organizer define and reprice seat sections", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const startTs = new BN(Math.floor(Date.now() / 1000) + 3600);
+      const endTs = new BN(Math.floor(Date.now() / 1000) + 7200);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [sectionPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("section"), eventPda.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Seated Event", startTs, endTs, 100, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .createSeatSection("ORCH", 10, 20, new BN(2_000_000_000))
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      await program.methods
+        .updateSeatSection(0, new BN(1_500_000_000))
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const section = await program.account.seatSection.fetch(sectionPda);
+      expect(section.name).to.equal("ORCH");
+      expect(section.rows).to.equal(10);
+      expect(section.seatsPerRow).to.equal(20);
+      expect(section.priceLamports.toNumber()).to.equal(1_500_000_000);
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.sectionCount).to.equal(1);
+    });
+  });
+
+  describe("Sale Schedule", () => {
+    it("should let the organizer schedule sale windows and an allowlist", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+      const startTs = new BN(now + 7200);
+      const endTs = new BN(now + 10800);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const fan = Keypair.generate().publicKey;
+      const [allowlistPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("allowlist"), eventPda.toBuffer(), fan.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Scheduled Event", startTs, endTs, 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const schedule = {
+        presaleStartTs: new BN(now + 600),
+        presalePriceLamports: new BN(500_000_000),
+        allowlistStartTs: new BN(now + 1200),
+        publicStartTs: new BN(now + 1800),
+        salesCloseTs: startTs,
+      };
+      await program.methods
+        .setSaleSchedule(schedule)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      await program.methods
+        .addToAllowlist(fan)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.saleSchedule!.publicStartTs.toNumber()).to.equal(now + 1800);
+      const entry = await program.account.allowlistEntry.fetch(allowlistPda);
+      expect(entry.wallet.toBase58()).to.equal(fan.toBase58());
+
+      await program.methods
+        .removeFromAllowlist()
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda, allowlistEntry: allowlistPda })
+        .rpc();
+      expect(await program.account.allowlistEntry.fetchNullable(allowlistPda)).to.be.null;
+
+      try {
+        await program.methods
+          .setSaleSchedule({ ...schedule, salesCloseTs: new BN(now + 20000) })
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("InvalidSaleSchedule");
+      }
+    });
+  });
+
+  describe("Merkle Allowlist", () => {
+    it("should let the organizer set and rotate a presale allowlist root", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const presale = { presale: {} };
+      const [merkleAllowlistPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("merkle-allowlist"), eventPda.toBuffer(), Buffer.from([1])],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Fan Club Presale", new BN(now + 7200), new BN(now + 10800), 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const firstRoot = Array(32).fill(1);
+      await program.methods
+        .setAllowlistRoot(presale, firstRoot, 100)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const secondRoot = Array(32).fill(2);
+      await program.methods
+        .setAllowlistRoot(presale, secondRoot, 120)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const allowlist = await program.account.merkleAllowlist.fetch(merkleAllowlistPda);
+      expect(allowlist.root).to.deep.equal(secondRoot);
+      expect(allowlist.slotCount).to.equal(120);
+
+      try {
+        await program.methods
+          .setAllowlistRoot({ public: {} }, firstRoot, 100)
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("InvalidInput");
+      }
+    });
+  });
+
+  describe("Dutch Auction", () => {
+    it("should let the organizer set a declining price auction before sales start", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Auctioned Event", new BN(now + 7200), new BN(now + 10800), 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const auction = {
+        startPriceLamports: new BN(5_000_000_000),
+        floorPriceLamports: new BN(1_000_000_000),
+        startTs: new BN(now),
+        endTs: new BN(now + 3600),
+        stepSecs: 600,
+        rebate: true,
+      };
+      await program.methods
+        .setDutchAuction(auction)
+        .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.dutchAuction!.floorPriceLamports.toNumber()).to.equal(1_000_000_000);
+      expect(eventAccount.dutchAuction!.rebate).to.be.true;
+
+      try {
+        await program.methods
+          .setDutchAuction({ ...auction, floorPriceLamports: new BN(6_000_000_000) })
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("InvalidDutchAuction");
+      }
+    });
+  });
+
+  describe("Offers", () => {
+    it("should escrow an event-wide offer and return it on cancel", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+      const buyer = provider.wallet!.publicKey;
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [offerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("offer"), eventPda.toBuffer(), buyer.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Offer Event", new BN(now + 7200), new BN(now + 10800), 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: buyer,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const price = new BN(100_000_000);
+      await program.methods
+        .makeOffer(null, price, new BN(now + 3600))
+        .accounts({ buyer, eventAccount: eventPda, ticketAccount: null })
+        .rpc();
+
+      const offer = await program.account.offerAccount.fetch(offerPda);
+      expect(offer.ticket).to.be.null;
+      expect(offer.priceLamports.toNumber()).to.equal(100_000_000);
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(
+        (await provider.connection.getAccountInfo(offerPda))!.data.length
+      );
+      expect(await provider.connection.getBalance(offerPda)).to.equal(rent + 100_000_000);
+
+      await program.methods
+        .cancelOffer()
+        .accounts({ buyer, offerAccount: offerPda })
+        .rpc();
+      expect(await program.account.offerAccount.fetchNullable(offerPda)).to.be.null;
+    });
+  });
+
+  describe("Metadata Config", () => {
+    it("should store per-stage URI templates and reject unknown placeholders", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+      const authority = provider.wallet!.publicKey;
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [metadataConfigPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("metadata-config"), eventPda.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Evolving Event", new BN(now + 3600), new BN(now + 7200), 10, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: authority,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const template = "https://cdn.example.com/{event_id}/{serial}/{stage}.json";
+      const templates = {
+        prestige: template,
+        qr: "https://cdn.example.com/{event_id}/{serial}/{stage}.json?seat={seat}",
+        scanned: template,
+        collectible: template,
+      };
+      await program.methods
+        .setMetadataConfig(templates)
+        .accounts({ authority, eventAccount: eventPda, metadataConfig: metadataConfigPda })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.hasMetadataConfig).to.equal(true);
+      const metadataConfig = await program.account.eventMetadataConfig.fetch(metadataConfigPda);
+      expect(metadataConfig.templates.qr).to.equal(templates.qr);
+
+      try {
+        await program.methods
+          .setMetadataConfig({ ...templates, collectible: "https://cdn.example.com/{name}.json" })
+          .accounts({ authority, eventAccount: eventPda, metadataConfig: metadataConfigPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("InvalidMetadataTemplate");
+      }
+    });
+  });
+
+  describe("Event Collection", () => {
+    it("should mint a collection NFT held by the ticket authority when collection accounts are passed", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+      const organizer = provider.wallet!.publicKey;
+      const tokenMetadataProgram = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [collectionMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("collection"), eventPda.toBuffer()],
+        program.programId
+      );
+      const [ticketAuthority] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket-authority")],
+        program.programId
+      );
+      const [collectionMetadata] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), tokenMetadataProgram.toBuffer(), collectionMint.toBuffer()],
+        tokenMetadataProgram
+      );
+      const [collectionMasterEdition] = PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata"), tokenMetadataProgram.toBuffer(), collectionMint.toBuffer(), Buffer.from("edition")],
+        tokenMetadataProgram
+      );
+      const collectionTokenAccount = await getAssociatedTokenAddress(collectionMint, ticketAuthority, true);
+
+      await program.methods
+        .createEvent(eventId, "Collection Event", new BN(now + 3600), new BN(now + 7200), 10, "https://example.com/cover.jpg")
+        .accountsPartial({
+          organizer,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+          collectionMint,
+          collectionMetadata,
+          collectionMasterEdition,
+          collectionTokenAccount,
+          ticketAuthority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenMetadataProgram,
+        })
+        .rpc();
+
+      const eventAccount = await program.account.eventAccount.fetch(eventPda);
+      expect(eventAccount.collectionMint!.toBase58()).to.equal(collectionMint.toBase58());
+      const metadataInfo = await provider.connection.getAccountInfo(collectionMetadata);
+      expect(metadataInfo!.owner.toBase58()).to.equal(tokenMetadataProgram.toBase58());
+    });
+  });
+
+  describe("Compressed Tickets", () => {
+    it("should not switch an event to compressed tickets before its ticket tree exists", async () => {
+      const eventId = new BN(Date.now() + Math.random() * 10000);
+      const now = Math.floor(Date.now() / 1000);
+      const [eventPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nft-evo-tickets"), Buffer.from("event"), eventId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+
+      await program.methods
+        .createEvent(eventId, "Stadium Event", new BN(now + 3600), new BN(now + 7200), 50000, "https://example.com/cover.jpg")
+        .accounts({
+          organizer: provider.wallet!.publicKey,
+          eventAccount: eventPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .setTicketMintMode({ compressed: {} })
+          .accounts({ authority: provider.wallet!.publicKey, eventAccount: eventPda })
+          .rpc();
+        expect.fail("Should have thrown an error");
+      } catch (err: any) {
+        expect(err.message).to.include("TicketTreeMismatch");
+      }
+    });
+  });
 });
@@ -11,6 +11,8 @@ import { LAMPORTS_PER_SOL } from "@solana/web3.js";
 import fs from "fs";
 import path from "path";
 import { getPinataClient, uploadCompleteNFTToPinata } from "./helpers/pinata";
+import { setTicketTemplates } from "./helpers/metadata";
+import { metadataConfigPda, nextTicketPdas, seatPda } from "./helpers/pdas";
 import QRCode from "qrcode";
 
 async function ensureBalance(conn: Connection, pubkey: PublicKey, wantLamports: number) {
@@ -63,7 +65,23 @@ describe("nft-evo-tickets", function() {
   const program = anchor.workspace.NftEvoTickets as Program<NftEvoTickets>;
 
   it("Initialize program", async () => {
-    const tx = await program.methods.initialize().rpc();
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("nft-evo-tickets"), Buffer.from("config")],
+      program.programId
+    );
+    if (await program.account.programConfig.fetchNullable(configPda)) {
+      console.log("Program config already initialized: " + configPda.toString());
+      return;
+    }
+
+    const [programDataPda] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    const tx = await program.methods
+      .initialize(provider.wallet!.publicKey, 500, 1000, new anchor.BN(0))
+      .accounts({ admin: provider.wallet!.publicKey, programData: programDataPda })
+      .rpc();
     console.log("Program initialized: https://solscan.io/tx/" + tx + "?cluster=devnet");
   });
 
@@ -125,15 +143,7 @@ describe("nft-evo-tickets", function() {
 
     const ticketOwner = provider.wallet!.publicKey;
     
-    const [ticketPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-      program.programId
-    );
-
-    const [nftMint] = PublicKey.findProgramAddressSync(
-      [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-      program.programId
-    );
+    const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
 
     const [metadataPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -221,11 +231,16 @@ describe("nft-evo-tickets", function() {
     console.log("QR Code saved to:", qrCodePath);
     console.log("QR Code contains NFT Mint:", nftMint.toString());
 
+    // The uploaded metadata serves every stage of this ticket
+    await setTicketTemplates(program, eventPda, undefined, metadataUrl);
+
     const tx = await program.methods
-      .mintTicket("A1", metadataUrl)
+      .mintTicket("A1", new anchor.BN(0))
       .accounts({
         authority: provider.wallet!.publicKey,
         eventAccount: eventPda,
+        metadataConfig: metadataConfigPda(program.programId, eventPda),
+        seatReservation: seatPda(program.programId, eventPda, "A1"),
         ticketAccount: ticketPda,
         owner: ticketOwner,
         nftMint: nftMint,
@@ -233,6 +248,7 @@ describe("nft-evo-tickets", function() {
         masterEdition: masterEditionPda,
         tokenAccount: tokenAccountPda,
         tokenProgram: TOKEN_PROGRAM_ID,
+        ticketTokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -281,15 +297,9 @@ describe("nft-evo-tickets", function() {
         .accounts({ organizer: seller.publicKey, eventAccount: eventPda, systemProgram: SystemProgram.programId })
         .signers([seller])
         .rpc();
+    await setTicketTemplates(program, eventPda, seller);
 
-    const [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), seller.publicKey.toBuffer()],
-        program.programId
-    );
-    const [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), seller.publicKey.toBuffer()],
-        program.programId
-    );
+    const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
 
     const [metadataPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
@@ -307,10 +317,12 @@ describe("nft-evo-tickets", function() {
     );
 
     await program.methods
-        .mintTicket("C3", "http://some.uri/for-buy-test.json")
+        .mintTicket("C3", new anchor.BN(0))
         .accounts({
             authority: seller.publicKey,
             eventAccount: eventPda,
+            metadataConfig: metadataConfigPda(program.programId, eventPda),
+            seatReservation: seatPda(program.programId, eventPda, "C3"),
             ticketAccount: ticketPda,
             owner: seller.publicKey,
             nftMint: nftMint,
@@ -318,6 +330,7 @@ describe("nft-evo-tickets", function() {
             masterEdition: masterEditionPda,
             tokenAccount: tokenAccountPda,
             tokenProgram: TOKEN_PROGRAM_ID,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),
@@ -338,9 +351,13 @@ describe("nft-evo-tickets", function() {
         .accounts({
             signer: seller.publicKey,
             eventAccount: eventPda,
+            metadataConfig: metadataConfigPda(program.programId, eventPda),
             ticketAccount: ticketPda,
             authority: seller.publicKey,
             scanner: scanner.publicKey,
+            ticketMint: nftMint,
+            metadataAccount: metadataPda,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([seller])
         .rpc();
@@ -359,7 +376,7 @@ describe("nft-evo-tickets", function() {
         ASSOCIATED_TOKEN_PROGRAM_ID
     );
     await program.methods
-        .listTicket(priceLamports, null)
+        .listTicket(priceLamports, null, null)
         .accounts({
             seller: seller.publicKey,
             ticketAccount: ticketPda,
@@ -392,8 +409,10 @@ describe("nft-evo-tickets", function() {
             buyer: buyer.publicKey,
             ticketAccount: ticketPda,
             eventAccount: eventPda,
+            treasury: provider.wallet!.publicKey,
             seller: seller.publicKey,
             nftMint: nftMint,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([buyer])
         .rpc();
@@ -460,9 +479,13 @@ describe("nft-evo-tickets", function() {
       .accounts({
         signer: authority,
         eventAccount: eventPda,
+        metadataConfig: metadataConfigPda(program.programId, eventPda),
         ticketAccount: ticketPda,
         authority: authority,
         scanner: scanner.publicKey,
+        ticketMint: nftMint,
+        metadataAccount: metadataPda,
+        ticketTokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
 
@@ -478,9 +501,13 @@ describe("nft-evo-tickets", function() {
         .accounts({
             signer: scanner.publicKey,
             eventAccount: eventPda,
+            metadataConfig: metadataConfigPda(program.programId, eventPda),
             ticketAccount: ticketPda,
             authority: authority,
             scanner: scanner.publicKey,
+            ticketMint: nftMint,
+            metadataAccount: metadataPda,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
         })
         .signers([scanner])
         .rpc();
@@ -524,14 +551,14 @@ describe("nft-evo-tickets", function() {
         .accounts({ authority: authority, eventAccount: eventPda })
         .rpc();
 
-    const [ticketPda] = await mintTestTicket(program, eventPda, authority, ticketOwner.publicKey);
+    const [ticketPda, nftMint, metadataPda] = await mintTestTicket(program, eventPda, authority, ticketOwner.publicKey);
     await program.methods
         .updateTicket({ qr: {} })
-        .accounts({ signer: authority, eventAccount: eventPda, ticketAccount: ticketPda, authority: authority, scanner: scanner.publicKey })
+        .accounts({ signer: authority, eventAccount: eventPda, metadataConfig: metadataConfigPda(program.programId, eventPda), ticketAccount: ticketPda, authority: authority, scanner: scanner.publicKey, ticketMint: nftMint, metadataAccount: metadataPda, ticketTokenProgram: TOKEN_PROGRAM_ID })
         .rpc();
     await program.methods
         .updateTicket({ scanned: {} })
-        .accounts({ signer: scanner.publicKey, eventAccount: eventPda, ticketAccount: ticketPda, authority: authority, scanner: scanner.publicKey })
+        .accounts({ signer: scanner.publicKey, eventAccount: eventPda, metadataConfig: metadataConfigPda(program.programId, eventPda), ticketAccount: ticketPda, authority: authority, scanner: scanner.publicKey, ticketMint: nftMint, metadataAccount: metadataPda, ticketTokenProgram: TOKEN_PROGRAM_ID })
         .signers([scanner])
         .rpc();
 
@@ -544,7 +571,11 @@ describe("nft-evo-tickets", function() {
       .accounts({
         user: ticketOwner.publicKey,
         eventAccount: eventPda,
+        metadataConfig: metadataConfigPda(program.programId, eventPda),
         ticketAccount: ticketPda,
+        ticketMint: nftMint,
+        metadataAccount: metadataPda,
+        ticketTokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([ticketOwner])
       .rpc();
@@ -563,14 +594,8 @@ async function mintTestTicket(
     authority: PublicKey, 
     ticketOwner: PublicKey
 ): Promise<[PublicKey, PublicKey, PublicKey, PublicKey, PublicKey]> {
-    const [ticketPda] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("ticket"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-    );
-    const [nftMint] = PublicKey.findProgramAddressSync(
-        [Buffer.from("nft-evo-tickets"), Buffer.from("nft-mint"), eventPda.toBuffer(), ticketOwner.toBuffer()],
-        program.programId
-    );
+    await setTicketTemplates(program, eventPda);
+    const { ticketPda, nftMint } = await nextTicketPdas(program, eventPda);
     const [metadataPda] = PublicKey.findProgramAddressSync(
         [Buffer.from("metadata"), new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s").toBuffer(), nftMint.toBuffer()],
         new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s")
@@ -585,10 +610,12 @@ async function mintTestTicket(
     );
 
     await program.methods
-        .mintTicket("TestSeat", null)
+        .mintTicket("TestSeat", new anchor.BN(0))
         .accounts({
             authority: authority,
             eventAccount: eventPda,
+            metadataConfig: metadataConfigPda(program.programId, eventPda),
+            seatReservation: seatPda(program.programId, eventPda, "TestSeat"),
             ticketAccount: ticketPda,
             owner: ticketOwner,
             nftMint: nftMint,
@@ -596,6 +623,7 @@ async function mintTestTicket(
             masterEdition: masterEditionPda,
             tokenAccount: tokenAccountPda,
             tokenProgram: TOKEN_PROGRAM_ID,
+            ticketTokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             systemProgram: SystemProgram.programId,
             tokenMetadataProgram: new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"),